@@ -0,0 +1,186 @@
+//! Criterion benchmarks for the native core.
+//!
+//! Run with `cargo bench`, or `cargo bench -- <group>` for one group. The
+//! wasm build is typically 1.5-3x slower than these numbers.
+//!
+//! The `budget` group fails the run when a user-facing operation exceeds its
+//! native budget below, leaving room for the wasm slowdown on low-end laptops.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use aes_gcm::aead::OsRng;
+use criterion::{Bencher, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use rust::encrypt_file::{EncryptOptions, RecipientInput, encrypt_file_multi_with_rng, wrap_dek_for_public_key};
+use rust::kdf::KdfParams;
+use rust::payload_frame::{CompressionScheme, PaddingScheme};
+use rust::{bytes_to_hex, derive_key_encryption_key, get_key_encryption_key, hash_file, hex_to_bytes};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+const KIB: usize = 1024;
+const MIB: usize = 1024 * KIB;
+
+/// Uploading 100 MB to 10 recipients
+const ENCRYPT_100_MIB_BUDGET: Duration = Duration::from_secs(2);
+/// Wrapping one DEK for one more recipient
+const DEK_WRAP_BUDGET: Duration = Duration::from_millis(1);
+/// One password unlock (`get_key_encryption_key` at the default parameters)
+const KDF_BUDGET: Duration = Duration::from_millis(1500);
+/// Hashing 100 MB (at least 200 MB/s)
+const HASH_100_MIB_BUDGET: Duration = Duration::from_millis(500);
+/// Hex-encoding a 1 KB field (hex only carries keys, hashes and metadata)
+const HEX_1_KIB_BUDGET: Duration = Duration::from_millis(1);
+
+fn recipients(count: usize) -> Vec<RecipientInput> {
+    (0..count)
+        .map(|i| RecipientInput {
+            user_id: format!("user-{}", i),
+            group_id: None,
+            public_key: PublicKey::from(&StaticSecret::random_from_rng(OsRng)).as_bytes().to_vec(),
+            not_before: None,
+            not_after: None,
+        })
+        .collect()
+}
+
+/// Pseudo-random bytes, so deflate has nothing to find.
+fn file_data(len: usize) -> Vec<u8> {
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+fn file_encryption(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encrypt_file_multi");
+    group.sample_size(10);
+    let recipients = recipients(10);
+
+    for size in [KIB, MIB, 16 * MIB, 100 * MIB] {
+        let data = file_data(size);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("10_recipients", size), &data, |b, data| {
+            b.iter(|| encrypt_file_multi_with_rng(&mut OsRng, black_box(data), &recipients, &EncryptOptions::default()))
+        });
+    }
+
+    let data = file_data(16 * MIB);
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    for (name, padding, compression, chunked) in [
+        ("padme", PaddingScheme::Padme, CompressionScheme::None, false),
+        ("deflate", PaddingScheme::None, CompressionScheme::Deflate, false),
+        ("chunked", PaddingScheme::None, CompressionScheme::None, true),
+    ] {
+        let options = EncryptOptions { metadata: None, padding, compression, chunked };
+        group.bench_with_input(BenchmarkId::new(name, data.len()), &data, |b, data| {
+            b.iter(|| encrypt_file_multi_with_rng(&mut OsRng, black_box(data), &recipients, &options))
+        });
+    }
+    group.finish();
+}
+
+fn dek_wrap(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dek_wrap");
+    let dek = [0x42u8; 32];
+    let public_key = recipients(1).remove(0).public_key;
+    group.bench_function("per_recipient", |b| b.iter(|| wrap_dek_for_public_key(black_box(&dek), &public_key)));
+
+    // The per-recipient cost inside a real upload, on a small file
+    let data = file_data(KIB);
+    for count in [1, 10, 100] {
+        let recipients = recipients(count);
+        group.throughput(Throughput::Elements(count as u64));
+        group.bench_with_input(BenchmarkId::new("encrypt_file_multi_1KiB", count), &recipients, |b, recipients| {
+            b.iter(|| encrypt_file_multi_with_rng(&mut OsRng, &data, recipients, &EncryptOptions::default()))
+        });
+    }
+    group.finish();
+}
+
+fn kdf(c: &mut Criterion) {
+    let mut group = c.benchmark_group("kdf");
+    group.sample_size(10).measurement_time(Duration::from_secs(20));
+    group.bench_function("get_key_encryption_key", |b| {
+        b.iter(|| get_key_encryption_key(black_box("correct horse battery staple"), "ZmlsZWNoYWluLXZlY3Rvcg"))
+    });
+
+    // How cost scales with memory, for comparing against low-end devices
+    for memory_mib in [16u32, 32, 128] {
+        let params = KdfParams { memory_kib: memory_mib * 1024, ..KdfParams::default() };
+        group.bench_with_input(BenchmarkId::new("memory_mib", memory_mib), &params, |b, params| {
+            b.iter(|| derive_key_encryption_key("correct horse battery staple", "ZmlsZWNoYWluLXZlY3Rvcg", params))
+        });
+    }
+    group.finish();
+}
+
+fn hashing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash_file");
+    for size in [KIB, MIB, 16 * MIB] {
+        let data = file_data(size);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &data, |b, data| b.iter(|| hash_file(black_box(data))));
+    }
+    group.finish();
+}
+
+fn hex(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hex");
+    for size in [32, KIB, MIB] {
+        let data = file_data(size);
+        let encoded = bytes_to_hex(&data);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("encode", size), &data, |b, data| b.iter(|| bytes_to_hex(black_box(data))));
+        group.bench_with_input(BenchmarkId::new("decode", size), &encoded, |b, encoded| {
+            b.iter(|| hex_to_bytes(black_box(encoded)))
+        });
+    }
+    group.finish();
+}
+
+/// Times `op` like `Bencher::iter`, failing if one run averages over `budget`.
+fn within_budget<T>(b: &mut Bencher, budget: Duration, mut op: impl FnMut() -> T) {
+    b.iter_custom(|iters| {
+        let start = Instant::now();
+        for _ in 0..iters {
+            black_box(op());
+        }
+        let elapsed = start.elapsed();
+        let per_run = elapsed.div_f64(iters as f64);
+        assert!(per_run <= budget, "over budget: {:?} per run, budget {:?}", per_run, budget);
+        elapsed
+    });
+}
+
+fn budgets(c: &mut Criterion) {
+    let mut group = c.benchmark_group("budget");
+    group.sample_size(10).measurement_time(Duration::from_secs(20));
+
+    let recipients = recipients(10);
+    let data = file_data(100 * MIB);
+    group.bench_function("encrypt_file_multi_100MiB_10_recipients", |b| {
+        within_budget(b, ENCRYPT_100_MIB_BUDGET, || {
+            encrypt_file_multi_with_rng(&mut OsRng, &data, &recipients, &EncryptOptions::default())
+        })
+    });
+    group.bench_function("hash_file_100MiB", |b| within_budget(b, HASH_100_MIB_BUDGET, || hash_file(&data)));
+
+    let dek = [0x42u8; 32];
+    let public_key = &recipients[0].public_key;
+    group.bench_function("dek_wrap", |b| within_budget(b, DEK_WRAP_BUDGET, || wrap_dek_for_public_key(&dek, public_key)));
+    group.bench_function("get_key_encryption_key", |b| {
+        within_budget(b, KDF_BUDGET, || get_key_encryption_key("correct horse battery staple", "ZmlsZWNoYWluLXZlY3Rvcg"))
+    });
+
+    let hex_data = file_data(KIB);
+    group.bench_function("hex_encode_1KiB", |b| within_budget(b, HEX_1_KIB_BUDGET, || bytes_to_hex(&hex_data)));
+    group.finish();
+}
+
+criterion_group!(benches, file_encryption, dek_wrap, kdf, hashing, hex, budgets);
+criterion_main!(benches);
@@ -0,0 +1,33 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust::block_signature::{BlockKind, LedgerEntry, generate_block_signature, generate_file_block_signature, verify_ledger_chain};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(entries) = serde_json::from_slice::<Vec<LedgerEntry>>(data) else {
+        return;
+    };
+
+    for entry in &entries {
+        let result = if entry.ciphertext_hash.is_empty() {
+            generate_block_signature(
+                &entry.uploader_id,
+                entry.timestamp_ms as f64,
+                &entry.file_hash,
+                &entry.previous_block_hash,
+            )
+        } else {
+            generate_file_block_signature(
+                &entry.uploader_id,
+                entry.timestamp_ms as f64,
+                &entry.file_hash,
+                &entry.ciphertext_hash,
+                &entry.previous_block_hash,
+            )
+        };
+        if result.success() && entry.timestamp_ms < 1 << 53 && entry.kind == BlockKind::Upload {
+            assert_eq!(result.signature_hex().eq_ignore_ascii_case(&entry.signature_hex), entry.is_consistent());
+        }
+    }
+    let _ = verify_ledger_chain(&entries);
+});
@@ -0,0 +1,29 @@
+#![no_main]
+
+//! `decrypt_file` from a database-shaped context, past the password step.
+//!
+//! Argon2 is skipped (it would cap the fuzzer at a few runs per second): the
+//! context is decrypted with the corpus identity's private key directly.
+
+use libfuzzer_sys::fuzz_target;
+use rust::decrypt_file::{DecryptionContext, decrypt_with_private_key};
+use rust::grant_window::GrantWindow;
+
+/// `private_key_wrap[0].private_key` in `tests/vectors/filechain-v1.json`
+const PRIVATE_KEY: &str = "a8abababababababababababababababababababababababababababababab6b";
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(context) = serde_json::from_slice::<DecryptionContext>(data) else {
+        return;
+    };
+
+    if let Some(manifest) = &context.manifest {
+        let _ = manifest.verify_signature();
+    }
+    let _ = GrantWindow::new(context.not_before, context.not_after).check(context.now_ms.map(|ms| ms as u64));
+
+    let result = decrypt_with_private_key(&context, rust::hex_to_bytes(PRIVATE_KEY).unwrap());
+    if result.success() {
+        assert_eq!(result.file_hash_hex(), rust::hash_file(&result.decrypted_data()));
+    }
+});
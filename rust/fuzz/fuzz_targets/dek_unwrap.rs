@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust::encrypt_file::FileDekInput;
+
+/// `private_key_wrap[0].private_key` in `tests/vectors/filechain-v1.json`
+const PRIVATE_KEY: &str = "a8abababababababababababababababababababababababababababababab6b";
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(entry) = serde_json::from_slice::<FileDekInput>(data) else {
+        return;
+    };
+    if let Ok(dek) = entry.unwrap_dek(&rust::hex_to_bytes(PRIVATE_KEY).unwrap()) {
+        assert_eq!(dek.len(), 32);
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust::{bytes_to_hex, hex_to_bytes};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(hex) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(bytes) = hex_to_bytes(hex) {
+        assert_eq!(bytes_to_hex(&bytes), hex.to_ascii_lowercase());
+    }
+});
@@ -0,0 +1,33 @@
+#![no_main]
+
+//! The payload frame parser.
+//!
+//! Random blobs almost never authenticate, so the input is also used as a
+//! frame body: the first byte picks the flags, the rest is encrypted under the
+//! fixed DEK and fed back through `open_payload`, which reaches the body
+//! decoder (length prefix, padding, inflate) with arbitrary contents.
+
+use aes_gcm::{Aes256Gcm, Nonce, aead::{Aead, KeyInit, Payload}};
+use libfuzzer_sys::fuzz_target;
+use rust::payload_frame::open_payload;
+
+const DEK: [u8; 32] = [0x42; 32];
+const FILE_NONCE: [u8; 12] = [0xc0, 0xff, 0xee, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+const MAGIC: &[u8] = b"FCFRAME\x01";
+
+fuzz_target!(|data: &[u8]| {
+    let _ = open_payload(&DEK, &FILE_NONCE, data);
+
+    let Some((&flags, body)) = data.split_first() else {
+        return;
+    };
+    let mut header = MAGIC.to_vec();
+    header.push(flags);
+    let ciphertext = Aes256Gcm::new(&DEK.into())
+        .encrypt(Nonce::from_slice(&FILE_NONCE), Payload { msg: body, aad: &header })
+        .unwrap();
+
+    let mut blob = header;
+    blob.extend_from_slice(&ciphertext);
+    let _ = open_payload(&DEK, &FILE_NONCE, &blob);
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+//! `re_encrypt_dek_for_recipient` past the password step (see `decryption_context`).
+
+use libfuzzer_sys::fuzz_target;
+use rust::encrypt_file::{ReShareDekInput, re_share_dek_with_private_key};
+
+/// `private_key_wrap[0].private_key` in `tests/vectors/filechain-v1.json`
+const PRIVATE_KEY: &str = "a8abababababababababababababababababababababababababababababab6b";
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = serde_json::from_slice::<ReShareDekInput>(data) else {
+        return;
+    };
+    let _ = re_share_dek_with_private_key(&input, rust::hex_to_bytes(PRIVATE_KEY).unwrap());
+});
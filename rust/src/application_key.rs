@@ -0,0 +1,548 @@
+//! Application-level key hierarchy.
+//!
+//! Each IP application gets a random 32-byte application key, wrapped to every
+//! member's X25519 public key the same way file DEKs are, but with the
+//! application ID bound as AAD so a wrapping only opens as this application's
+//! key. File DEKs are then wrapped under the application key instead of per
+//! recipient:
+//!
+//!   member private key → application key → file DEK → file
+//!
+//! Adding a member only adds one application key wrapping. Removing a member
+//! rotates the application key and re-wraps the file DEKs under the new one;
+//! file contents are not re-encrypted (use `rotate_file_dek` for that).
+
+use wasm_bindgen::prelude::*;
+use aes_gcm::aead::{OsRng, rand_core::{CryptoRng, RngCore}};
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroize;
+
+pub use crate::{bytes_to_hex, hash_file, log};
+use crate::decrypt_file::{DecryptedFileResult, decrypt_with_dek};
+use crate::encrypt_file::{
+    EncryptOptions, RecipientInput, canonical_public_key, generate_dek_with_rng, seal_file_with_rng, unwrap_dek_with_private_key,
+    unwrap_key_with_key, unwrap_key_with_private_key, wrap_key_for_public_key_with_rng, wrap_key_with_key_with_rng,
+};
+use crate::masterkey_decryptor::PrivateKeyCredentials;
+use crate::progress::NoProgress;
+
+/// A member's wrapping of the application key (bytes), as stored.
+#[derive(Deserialize)]
+pub struct ApplicationKeyEntryInput {
+    pub encrypted_app_key: Vec<u8>,
+    pub app_key_nonce: Vec<u8>,
+    pub ephemeral_public_key: Vec<u8>,
+}
+
+/// A member's wrapping of the application key, ready for storage.
+#[derive(Serialize)]
+pub struct ApplicationKeyEntry {
+    pub user_id: String,
+    pub encrypted_app_key_hex: String,
+    pub app_key_nonce_hex: String,
+    pub ephemeral_public_key_hex: String,
+}
+
+/// A file DEK wrapped under the application key (bytes), as stored.
+#[derive(Deserialize)]
+pub struct ApplicationFileDekInput {
+    pub file_id: String,
+    pub wrapped_dek: Vec<u8>,
+    pub wrapped_dek_nonce: Vec<u8>,
+}
+
+/// A file DEK wrapped under the application key, ready for storage.
+#[derive(Serialize)]
+pub struct ApplicationFileDekEntry {
+    pub file_id: String,
+    pub wrapped_dek_hex: String,
+    pub wrapped_dek_nonce_hex: String,
+}
+
+/// Result of an application key membership or file DEK operation.
+#[derive(Serialize)]
+pub struct ApplicationKeyResult {
+    pub success: bool,
+    pub member_entries: Vec<ApplicationKeyEntry>,
+    pub file_dek_entries: Vec<ApplicationFileDekEntry>,
+    pub error_message: String,
+}
+
+impl ApplicationKeyResult {
+    fn error(msg: String) -> Self {
+        ApplicationKeyResult {
+            success: false,
+            member_entries: vec![],
+            file_dek_entries: vec![],
+            error_message: msg,
+        }
+    }
+}
+
+/// AAD binding a wrapped file DEK to its application.
+fn file_dek_aad(application_id: &str) -> Vec<u8> {
+    format!("filechain-application-dek-v1|{}", application_id).into_bytes()
+}
+
+/// AAD binding a member's wrapping of the application key to its application.
+fn member_key_aad(application_id: &str) -> Vec<u8> {
+    format!("filechain-application-key-v1|{}", application_id).into_bytes()
+}
+
+fn wrap_for_members<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    app_key: &[u8],
+    application_id: &str,
+    members: &[RecipientInput],
+) -> Result<Vec<ApplicationKeyEntry>, String> {
+    for member in members {
+        member.validate()?;
+        if member.group_id.is_some() {
+            return Err("Application members must have a user_id".to_string());
+        }
+        canonical_public_key(&member.public_key, "Public key")
+            .map_err(|e| format!("Invalid public key for user {}: {}", member.user_id, e))?;
+    }
+
+    let aad = member_key_aad(application_id);
+    members
+        .iter()
+        .map(|member| {
+            let wrapped = wrap_key_for_public_key_with_rng(rng, app_key, &member.public_key, &aad)
+                .map_err(|e| format!("Failed to wrap application key for user {}: {}", member.user_id, e))?;
+            Ok(ApplicationKeyEntry {
+                user_id: member.user_id.clone(),
+                encrypted_app_key_hex: bytes_to_hex(&wrapped.encrypted_dek),
+                app_key_nonce_hex: bytes_to_hex(&wrapped.dek_nonce),
+                ephemeral_public_key_hex: bytes_to_hex(&wrapped.ephemeral_public_key),
+            })
+        })
+        .collect()
+}
+
+/// Unwraps a member's copy of an application key with their private key.
+pub fn unwrap_application_key(
+    private_key: &[u8],
+    application_id: &str,
+    entry: &ApplicationKeyEntryInput,
+) -> Result<Vec<u8>, String> {
+    unwrap_key_with_private_key(
+        private_key,
+        &entry.ephemeral_public_key,
+        &entry.app_key_nonce,
+        &entry.encrypted_app_key,
+        &member_key_aad(application_id),
+    )
+    .map_err(|e| format!("Failed to unwrap application key: {}", e))
+}
+
+/// Unlocks the caller's private key and unwraps their copy of the application key.
+pub fn unlock_application_key(
+    credentials: &PrivateKeyCredentials,
+    application_id: &str,
+    entry: &ApplicationKeyEntryInput,
+) -> Result<Vec<u8>, String> {
+    let mut private_key = credentials.unlock()?;
+    let app_key = unwrap_application_key(&private_key, application_id, entry);
+    private_key.zeroize();
+    app_key
+}
+
+/// Wraps a file DEK under an application key.
+pub fn wrap_file_dek(app_key: &[u8], application_id: &str, dek: &[u8]) -> Result<(Vec<u8>, Vec<u8>), String> {
+    wrap_file_dek_with_rng(&mut OsRng, app_key, application_id, dek)
+}
+
+/// [`wrap_file_dek`] with the nonce drawn from `rng`.
+pub fn wrap_file_dek_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    app_key: &[u8],
+    application_id: &str,
+    dek: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), String> {
+    wrap_key_with_key_with_rng(rng, app_key, dek, &file_dek_aad(application_id))
+}
+
+/// Unwraps a file DEK wrapped under an application key.
+pub fn unwrap_file_dek(
+    app_key: &[u8],
+    application_id: &str,
+    wrapped_dek: &[u8],
+    wrapped_dek_nonce: &[u8],
+) -> Result<Vec<u8>, String> {
+    unwrap_key_with_key(app_key, wrapped_dek, wrapped_dek_nonce, &file_dek_aad(application_id))
+        .map_err(|e| format!("Failed to unwrap file DEK: {}", e))
+}
+
+/// Creates a new application key and wraps it for the initial members.
+///
+/// # Arguments
+/// * `application_id` - The application the key belongs to
+/// * `members_js` - A JsValue representing an array of { user_id: string, public_key: number[] }
+///
+/// # Returns
+/// A JsValue containing `ApplicationKeyResult` with one entry per member.
+#[wasm_bindgen]
+pub fn create_application_key(application_id: &str, members_js: JsValue) -> JsValue {
+    log("[create_application_key] Creating application key...");
+
+    let result = match serde_wasm_bindgen::from_value::<Vec<RecipientInput>>(members_js) {
+        Ok(members) => create_key_with_rng(&mut OsRng, application_id, &members).unwrap_or_else(ApplicationKeyResult::error),
+        Err(e) => ApplicationKeyResult::error(format!("Failed to parse members: {}", e)),
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// The core of [`create_application_key`] for parsed members, with the key
+/// and its wrappings drawn from `rng`.
+pub fn create_key_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    application_id: &str,
+    members: &[RecipientInput],
+) -> Result<ApplicationKeyResult, String> {
+    if members.is_empty() {
+        return Err("At least one member is required".to_string());
+    }
+
+    let mut app_key = generate_dek_with_rng(rng);
+    let entries = wrap_for_members(rng, &app_key, application_id, members);
+    app_key.zeroize();
+
+    Ok(ApplicationKeyResult {
+        success: true,
+        member_entries: entries?,
+        file_dek_entries: vec![],
+        error_message: String::new(),
+    })
+}
+
+/// Input for adding members to an application.
+#[derive(Deserialize)]
+pub struct AddApplicationMembersInput {
+    pub credentials: PrivateKeyCredentials,
+    pub application_id: String,
+    /// The caller's own application key entry
+    pub caller_entry: ApplicationKeyEntryInput,
+    pub new_members: Vec<RecipientInput>,
+}
+
+/// Wraps the application key for new members using the caller's copy.
+///
+/// The new members can read every file in the application at once.
+#[wasm_bindgen]
+pub fn add_application_members(input_js: JsValue) -> JsValue {
+    log("[add_application_members] Adding application members...");
+
+    let result = match serde_wasm_bindgen::from_value::<AddApplicationMembersInput>(input_js) {
+        Ok(input) => add_members(&mut OsRng, &input).unwrap_or_else(ApplicationKeyResult::error),
+        Err(e) => ApplicationKeyResult::error(format!("Failed to parse input: {}", e)),
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+fn add_members<R: RngCore + CryptoRng>(rng: &mut R, input: &AddApplicationMembersInput) -> Result<ApplicationKeyResult, String> {
+    let mut app_key = unlock_application_key(&input.credentials, &input.application_id, &input.caller_entry)?;
+    let entries = wrap_for_members(rng, &app_key, &input.application_id, &input.new_members);
+    app_key.zeroize();
+
+    Ok(ApplicationKeyResult {
+        success: true,
+        member_entries: entries?,
+        file_dek_entries: vec![],
+        error_message: String::new(),
+    })
+}
+
+/// Input for removing members from an application.
+#[derive(Deserialize)]
+pub struct RemoveApplicationMembersInput {
+    pub credentials: PrivateKeyCredentials,
+    pub application_id: String,
+    /// The caller's own application key entry
+    pub caller_entry: ApplicationKeyEntryInput,
+    /// Members who keep access; everyone else is removed
+    pub remaining_members: Vec<RecipientInput>,
+    /// Every file DEK currently wrapped under the application key
+    pub file_deks: Vec<ApplicationFileDekInput>,
+}
+
+/// Rotates the application key, leaving out removed members.
+///
+/// # Returns
+/// A JsValue containing `ApplicationKeyResult` with replacement member entries
+/// and every file DEK re-wrapped under the new application key. All existing
+/// rows for the application are replaced.
+#[wasm_bindgen]
+pub fn remove_application_members(input_js: JsValue) -> JsValue {
+    log("[remove_application_members] Rotating application key...");
+
+    let result = match serde_wasm_bindgen::from_value::<RemoveApplicationMembersInput>(input_js) {
+        Ok(input) => remove_members(&mut OsRng, &input).unwrap_or_else(ApplicationKeyResult::error),
+        Err(e) => ApplicationKeyResult::error(format!("Failed to parse input: {}", e)),
+    };
+
+    if !result.success {
+        log(&format!("[remove_application_members] Failed: {}", result.error_message));
+    }
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+fn remove_members<R: RngCore + CryptoRng>(rng: &mut R, input: &RemoveApplicationMembersInput) -> Result<ApplicationKeyResult, String> {
+    if input.remaining_members.is_empty() {
+        return Err("At least one remaining member is required".to_string());
+    }
+
+    let mut old_app_key = unlock_application_key(&input.credentials, &input.application_id, &input.caller_entry)?;
+    let mut new_app_key = generate_dek_with_rng(rng);
+
+    let file_dek_entries = rewrap_file_deks(rng, &old_app_key, &new_app_key, &input.application_id, &input.file_deks);
+    old_app_key.zeroize();
+    let member_entries = wrap_for_members(rng, &new_app_key, &input.application_id, &input.remaining_members);
+    new_app_key.zeroize();
+
+    log(&format!(
+        "[remove_application_members] {} members keep access",
+        input.remaining_members.len()
+    ));
+
+    Ok(ApplicationKeyResult {
+        success: true,
+        member_entries: member_entries?,
+        file_dek_entries: file_dek_entries?,
+        error_message: String::new(),
+    })
+}
+
+fn rewrap_file_deks<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    old_app_key: &[u8],
+    new_app_key: &[u8],
+    application_id: &str,
+    file_deks: &[ApplicationFileDekInput],
+) -> Result<Vec<ApplicationFileDekEntry>, String> {
+    file_deks
+        .iter()
+        .map(|entry| {
+            let mut dek = unwrap_file_dek(old_app_key, application_id, &entry.wrapped_dek, &entry.wrapped_dek_nonce)
+                .map_err(|e| format!("File {}: {}", entry.file_id, e))?;
+            let wrapped = wrap_file_dek_with_rng(rng, new_app_key, application_id, &dek);
+            dek.zeroize();
+            let (wrapped_dek, wrapped_dek_nonce) = wrapped?;
+            Ok(ApplicationFileDekEntry {
+                file_id: entry.file_id.clone(),
+                wrapped_dek_hex: bytes_to_hex(&wrapped_dek),
+                wrapped_dek_nonce_hex: bytes_to_hex(&wrapped_dek_nonce),
+            })
+        })
+        .collect()
+}
+
+/// Input for encrypting or migrating a file under an application key.
+#[derive(Deserialize)]
+pub struct ApplicationFileInput {
+    pub credentials: PrivateKeyCredentials,
+    pub application_id: String,
+    /// The caller's own application key entry
+    pub caller_entry: ApplicationKeyEntryInput,
+    /// Framing and metadata, as for `encrypt_file_multi_with_options`
+    #[serde(default)]
+    pub options: EncryptOptions,
+}
+
+/// Result of encrypting a file under an application key.
+#[derive(Serialize)]
+pub struct ApplicationEncryptResult {
+    pub success: bool,
+    pub encrypted_data: Vec<u8>,
+    pub file_nonce_hex: String,
+    pub original_hash_hex: String,
+    /// Merkle root over `MERKLE_CHUNK_SIZE` chunks of the original file
+    pub merkle_root_hex: String,
+    /// SHA-256 of `encrypted_data`, for the ledger block
+    pub ciphertext_hash_hex: String,
+    /// The metadata record encrypted under the file DEK (empty without metadata)
+    pub encrypted_metadata: Vec<u8>,
+    pub metadata_nonce_hex: String,
+    pub wrapped_dek_hex: String,
+    pub wrapped_dek_nonce_hex: String,
+    pub error_message: String,
+}
+
+impl ApplicationEncryptResult {
+    fn error(msg: String) -> Self {
+        ApplicationEncryptResult {
+            success: false,
+            encrypted_data: vec![],
+            file_nonce_hex: String::new(),
+            original_hash_hex: String::new(),
+            merkle_root_hex: String::new(),
+            ciphertext_hash_hex: String::new(),
+            encrypted_metadata: vec![],
+            metadata_nonce_hex: String::new(),
+            wrapped_dek_hex: String::new(),
+            wrapped_dek_nonce_hex: String::new(),
+            error_message: msg,
+        }
+    }
+}
+
+/// Encrypts a file with a fresh DEK and wraps that DEK under the application key.
+///
+/// The file is framed as `input.options` ask, exactly as `encrypt_file_multi_with_options` would.
+///
+/// # Arguments
+/// * `file_data` - The raw file bytes to encrypt
+/// * `input_js` - A JsValue representing an `ApplicationFileInput`
+#[wasm_bindgen]
+pub fn encrypt_file_for_application(file_data: &[u8], input_js: JsValue) -> JsValue {
+    log("[encrypt_file_for_application] Starting application file encryption...");
+    log(&format!("[encrypt_file_for_application] File size: {} bytes", file_data.len()));
+
+    let result = match serde_wasm_bindgen::from_value::<ApplicationFileInput>(input_js) {
+        Ok(input) => encrypt_for_application_with_rng(&mut OsRng, file_data, &input).unwrap_or_else(ApplicationEncryptResult::error),
+        Err(e) => ApplicationEncryptResult::error(format!("Failed to parse input: {}", e)),
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// The core of [`encrypt_file_for_application`] for a parsed input, with the
+/// DEK, nonces and object name drawn from `rng`.
+pub fn encrypt_for_application_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    file_data: &[u8],
+    input: &ApplicationFileInput,
+) -> Result<ApplicationEncryptResult, String> {
+    let mut app_key = unlock_application_key(&input.credentials, &input.application_id, &input.caller_entry)?;
+    let mut dek = generate_dek_with_rng(rng);
+
+    let sealed = seal_file_with_rng(rng, &dek, file_data, &input.options, &mut NoProgress);
+    let wrapped = wrap_file_dek_with_rng(rng, &app_key, &input.application_id, &dek);
+    dek.zeroize();
+    app_key.zeroize();
+
+    let sealed = sealed?;
+    let (wrapped_dek, wrapped_dek_nonce) = wrapped?;
+
+    Ok(ApplicationEncryptResult {
+        success: true,
+        encrypted_data: sealed.encrypted_data,
+        file_nonce_hex: sealed.file_nonce_hex,
+        original_hash_hex: sealed.original_hash_hex,
+        merkle_root_hex: sealed.merkle_root_hex,
+        ciphertext_hash_hex: sealed.ciphertext_hash_hex,
+        encrypted_metadata: sealed.encrypted_metadata,
+        metadata_nonce_hex: sealed.metadata_nonce_hex,
+        wrapped_dek_hex: bytes_to_hex(&wrapped_dek),
+        wrapped_dek_nonce_hex: bytes_to_hex(&wrapped_dek_nonce),
+        error_message: String::new(),
+    })
+}
+
+/// Input for moving an existing per-recipient file DEK under the application key.
+#[derive(Deserialize)]
+pub struct MigrateFileDekInput {
+    pub credentials: PrivateKeyCredentials,
+    pub application_id: String,
+    /// The caller's own application key entry
+    pub caller_entry: ApplicationKeyEntryInput,
+    pub file_id: String,
+    /// The caller's `file_dek` row for the file (bytes)
+    pub encrypted_dek: Vec<u8>,
+    pub dek_nonce: Vec<u8>,
+    pub ephemeral_public_key: Vec<u8>,
+}
+
+/// Wraps an existing file's DEK under the application key, using the caller's
+/// per-recipient `file_dek` row. Used to migrate files uploaded before the
+/// application had a key.
+#[wasm_bindgen]
+pub fn wrap_file_dek_for_application(input_js: JsValue) -> JsValue {
+    log("[wrap_file_dek_for_application] Wrapping file DEK under application key...");
+
+    let result = match serde_wasm_bindgen::from_value::<MigrateFileDekInput>(input_js) {
+        Ok(input) => migrate_file_dek(&mut OsRng, &input).unwrap_or_else(ApplicationKeyResult::error),
+        Err(e) => ApplicationKeyResult::error(format!("Failed to parse input: {}", e)),
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+fn migrate_file_dek<R: RngCore + CryptoRng>(rng: &mut R, input: &MigrateFileDekInput) -> Result<ApplicationKeyResult, String> {
+    let mut private_key = input.credentials.unlock()?;
+
+    let dek = unwrap_dek_with_private_key(
+        &private_key,
+        &input.ephemeral_public_key,
+        &input.dek_nonce,
+        &input.encrypted_dek,
+    );
+    let app_key = unwrap_application_key(&private_key, &input.application_id, &input.caller_entry);
+    private_key.zeroize();
+
+    let mut dek = dek?;
+    let mut app_key = app_key?;
+    let wrapped = wrap_file_dek_with_rng(rng, &app_key, &input.application_id, &dek);
+    dek.zeroize();
+    app_key.zeroize();
+    let (wrapped_dek, wrapped_dek_nonce) = wrapped?;
+
+    Ok(ApplicationKeyResult {
+        success: true,
+        member_entries: vec![],
+        file_dek_entries: vec![ApplicationFileDekEntry {
+            file_id: input.file_id.clone(),
+            wrapped_dek_hex: bytes_to_hex(&wrapped_dek),
+            wrapped_dek_nonce_hex: bytes_to_hex(&wrapped_dek_nonce),
+        }],
+        error_message: String::new(),
+    })
+}
+
+/// Input for decrypting a file through the application key chain.
+#[derive(Deserialize)]
+pub struct ApplicationDecryptionContext {
+    pub credentials: PrivateKeyCredentials,
+    pub application_id: String,
+    pub caller_entry: ApplicationKeyEntryInput,
+    pub encrypted_data: Vec<u8>,
+    pub file_nonce: Vec<u8>,
+    pub wrapped_dek: Vec<u8>,
+    pub wrapped_dek_nonce: Vec<u8>,
+}
+
+/// Decrypts a file: private key → application key → file DEK → file.
+///
+/// # Returns
+/// DecryptedFileResult containing decrypted data and its hash for verification
+#[wasm_bindgen]
+pub fn decrypt_application_file(val: JsValue) -> DecryptedFileResult {
+    log("[decrypt_application_file] Starting application file decryption...");
+
+    let result = serde_wasm_bindgen::from_value::<ApplicationDecryptionContext>(val)
+        .map_err(|e| format!("Argument parsing failed: {}", e))
+        .and_then(|context| {
+            let mut app_key = unlock_application_key(&context.credentials, &context.application_id, &context.caller_entry)?;
+            let dek = unwrap_file_dek(
+                &app_key,
+                &context.application_id,
+                &context.wrapped_dek,
+                &context.wrapped_dek_nonce,
+            );
+            app_key.zeroize();
+
+            let mut dek = dek?;
+            let decrypted = decrypt_with_dek(&dek, &context.file_nonce, &context.encrypted_data);
+            dek.zeroize();
+            decrypted
+        });
+
+    if let Err(e) = &result {
+        log(&format!("[decrypt_application_file] Decryption failed: {}", e));
+    }
+
+    DecryptedFileResult::from_decryption(result)
+}
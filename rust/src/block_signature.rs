@@ -1,19 +1,37 @@
 /// Generates a SHA-256 block signature for file ledger entries.
 ///
 /// The signature is computed by hashing the concatenation of:
+///   "{uploader_id}-{timestamp_ms}-{file_hash}-{ciphertext_hash}-{previous_block_hash}"
+///
+/// `ciphertext_hash` is the SHA-256 of the stored `.enc` object, so an auditor
+/// without the DEK can check the object against the ledger. Blocks without
+/// one (older uploads, destruction certificates) omit the field:
 ///   "{uploader_id}-{timestamp_ms}-{file_hash}-{previous_block_hash}"
 ///
 /// For genesis blocks (no previous entry), previous_block_hash should be "0".
+/// The hashes are SHA-256 hex digests, which never contain a `-`, so each
+/// input has exactly one split.
+///
+/// Revocation blocks (see `dek_rotation`) hash the same fields behind the
+/// line "filechain-revocation-v1", and destruction blocks (see `crypto_shred`)
+/// behind "filechain-destruction-v1", so neither can pass for an upload.
 
 use wasm_bindgen::prelude::*;
 use sha2::{Sha256, Digest};
+use serde::{Serialize, Deserialize};
+
+pub use crate::{bytes_to_hex, hex_to_bytes, log};
+use crate::clock::{Clock, SystemClock};
+use crate::identity_signature::SignatureVerificationResult;
 
-pub use crate::{bytes_to_hex, log};
+const REVOCATION_PREFIX: &str = "filechain-revocation-v1\n";
+const DESTRUCTION_PREFIX: &str = "filechain-destruction-v1\n";
 
 #[wasm_bindgen]
 pub struct BlockSignatureResult {
     success: bool,
     signature_hex: String,
+    timestamp_ms: u64,
     error_message: String,
 }
 
@@ -29,12 +47,29 @@ impl BlockSignatureResult {
         self.signature_hex.clone()
     }
 
+    /// The timestamp covered by the signature (Unix ms)
+    #[wasm_bindgen(getter)]
+    pub fn timestamp_ms(&self) -> f64 {
+        self.timestamp_ms as f64
+    }
+
     #[wasm_bindgen(getter)]
     pub fn error_message(&self) -> String {
         self.error_message.clone()
     }
 }
 
+impl BlockSignatureResult {
+    fn error(msg: String) -> Self {
+        BlockSignatureResult {
+            success: false,
+            signature_hex: String::new(),
+            timestamp_ms: 0,
+            error_message: msg,
+        }
+    }
+}
+
 /// Generates a SHA-256 block signature for a file ledger entry.
 ///
 /// # Arguments
@@ -51,47 +86,367 @@ pub fn generate_block_signature(
     timestamp_ms: f64,
     file_hash: &str,
     previous_block_hash: &str,
+) -> BlockSignatureResult {
+    // Convert f64 timestamp to integer milliseconds
+    block_signature_at(uploader_id, timestamp_ms as u64, file_hash, "", previous_block_hash)
+}
+
+/// Generates a block signature for an upload, covering the ciphertext hash.
+///
+/// # Arguments
+/// * `uploader_id` - The UUID of the uploader
+/// * `timestamp_ms` - Unix timestamp in milliseconds (from Date.now())
+/// * `file_hash` - The SHA-256 hash hex of the original file
+/// * `ciphertext_hash` - The SHA-256 hash hex of the stored ciphertext (`ciphertext_hash_hex`); required
+/// * `previous_block_hash` - The signature of the previous block, or "0" for genesis
+#[wasm_bindgen]
+pub fn generate_file_block_signature(
+    uploader_id: &str,
+    timestamp_ms: f64,
+    file_hash: &str,
+    ciphertext_hash: &str,
+    previous_block_hash: &str,
+) -> BlockSignatureResult {
+    if let Err(e) = check_digest(ciphertext_hash, "Ciphertext hash") {
+        return BlockSignatureResult::error(e);
+    }
+    block_signature_at(uploader_id, timestamp_ms as u64, file_hash, ciphertext_hash, previous_block_hash)
+}
+
+/// Generates a block signature stamped with the current time.
+///
+/// # Returns
+/// BlockSignatureResult with the signature and the `timestamp_ms` it covers
+#[wasm_bindgen]
+pub fn generate_block_signature_now(
+    uploader_id: &str,
+    file_hash: &str,
+    previous_block_hash: &str,
+) -> BlockSignatureResult {
+    generate_block_signature_with_clock(&SystemClock, uploader_id, file_hash, "", previous_block_hash)
+}
+
+/// [`generate_file_block_signature`] stamped with the current time.
+#[wasm_bindgen]
+pub fn generate_file_block_signature_now(
+    uploader_id: &str,
+    file_hash: &str,
+    ciphertext_hash: &str,
+    previous_block_hash: &str,
+) -> BlockSignatureResult {
+    if let Err(e) = check_digest(ciphertext_hash, "Ciphertext hash") {
+        return BlockSignatureResult::error(e);
+    }
+    generate_block_signature_with_clock(&SystemClock, uploader_id, file_hash, ciphertext_hash, previous_block_hash)
+}
+
+/// Generates a block signature stamped with `clock`.
+///
+/// Pass an empty `ciphertext_hash` for blocks that do not cover a stored object;
+/// otherwise it must be a SHA-256 hex digest.
+pub fn generate_block_signature_with_clock(
+    clock: &impl Clock,
+    uploader_id: &str,
+    file_hash: &str,
+    ciphertext_hash: &str,
+    previous_block_hash: &str,
+) -> BlockSignatureResult {
+    block_signature_at(uploader_id, clock.now_ms(), file_hash, ciphertext_hash, previous_block_hash)
+}
+
+fn block_signature_at(
+    uploader_id: &str,
+    ts: u64,
+    file_hash: &str,
+    ciphertext_hash: &str,
+    previous_block_hash: &str,
 ) -> BlockSignatureResult {
     log("[block_signature] Generating block signature...");
 
     if uploader_id.is_empty() {
-        return BlockSignatureResult {
-            success: false,
-            signature_hex: String::new(),
-            error_message: "Uploader ID cannot be empty".to_string(),
-        };
+        return BlockSignatureResult::error("Uploader ID cannot be empty".to_string());
     }
 
     if file_hash.is_empty() {
-        return BlockSignatureResult {
-            success: false,
-            signature_hex: String::new(),
-            error_message: "File hash cannot be empty".to_string(),
-        };
+        return BlockSignatureResult::error("File hash cannot be empty".to_string());
     }
 
-    // Convert f64 timestamp to integer milliseconds
-    let ts = timestamp_ms as u64;
+    if let Err(e) = check_hashes(file_hash, ciphertext_hash, previous_block_hash) {
+        return BlockSignatureResult::error(e);
+    }
+
+    let signature = compute_block_signature(uploader_id, ts, file_hash, ciphertext_hash, previous_block_hash);
+
+    log(&format!("[block_signature] Signature: {}", signature));
 
+    BlockSignatureResult {
+        success: true,
+        signature_hex: signature,
+        timestamp_ms: ts,
+        error_message: String::new(),
+    }
+}
+
+/// Computes the block signature hex without input validation or logging.
+///
+/// An empty `previous_block_hash` is treated as the genesis marker "0"; an
+/// empty `ciphertext_hash` gives the format without it.
+pub fn compute_block_signature(
+    uploader_id: &str,
+    timestamp_ms: u64,
+    file_hash: &str,
+    ciphertext_hash: &str,
+    previous_block_hash: &str,
+) -> String {
+    let input = signature_input(uploader_id, timestamp_ms, file_hash, ciphertext_hash, previous_block_hash);
+
+    let mut hasher = Sha256::new();
+    hasher.update(input.as_bytes());
+    let result = hasher.finalize();
+    bytes_to_hex(&result)
+}
+
+/// [`compute_block_signature`] for a revocation block.
+pub fn compute_revocation_signature(
+    actor_id: &str,
+    timestamp_ms: u64,
+    file_hash: &str,
+    ciphertext_hash: &str,
+    previous_block_hash: &str,
+) -> String {
+    compute_prefixed_signature(REVOCATION_PREFIX, actor_id, timestamp_ms, file_hash, ciphertext_hash, previous_block_hash)
+}
+
+/// [`compute_block_signature`] for a destruction certificate block.
+pub fn compute_destruction_signature(
+    actor_id: &str,
+    timestamp_ms: u64,
+    file_hash: &str,
+    ciphertext_hash: &str,
+    previous_block_hash: &str,
+) -> String {
+    compute_prefixed_signature(DESTRUCTION_PREFIX, actor_id, timestamp_ms, file_hash, ciphertext_hash, previous_block_hash)
+}
+
+fn compute_prefixed_signature(
+    prefix: &str,
+    actor_id: &str,
+    timestamp_ms: u64,
+    file_hash: &str,
+    ciphertext_hash: &str,
+    previous_block_hash: &str,
+) -> String {
+    let input = signature_input(actor_id, timestamp_ms, file_hash, ciphertext_hash, previous_block_hash);
+
+    let mut hasher = Sha256::new();
+    hasher.update(prefix.as_bytes());
+    hasher.update(input.as_bytes());
+    bytes_to_hex(&hasher.finalize())
+}
+
+/// Checks a hash is a SHA-256 hex digest. Anything else could contain a `-`
+/// and read as a different split of the signature input.
+fn check_digest(hash: &str, name: &str) -> Result<(), String> {
+    if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("{} must be 64 hex characters", name));
+    }
+    Ok(())
+}
+
+/// Checks the hashed fields of a block: `file_hash` is a digest,
+/// `ciphertext_hash` is empty or a digest, and `previous_block_hash` is empty,
+/// the genesis marker "0" or a digest.
+fn check_hashes(file_hash: &str, ciphertext_hash: &str, previous_block_hash: &str) -> Result<(), String> {
+    check_digest(file_hash, "File hash")?;
+    if !ciphertext_hash.is_empty() {
+        check_digest(ciphertext_hash, "Ciphertext hash")?;
+    }
+    if !previous_block_hash.is_empty() && previous_block_hash != "0" {
+        check_digest(previous_block_hash, "Previous block hash")
+            .map_err(|e| format!("{} (or \"0\" for a genesis block)", e))?;
+    }
+    Ok(())
+}
+
+fn signature_input(
+    uploader_id: &str,
+    timestamp_ms: u64,
+    file_hash: &str,
+    ciphertext_hash: &str,
+    previous_block_hash: &str,
+) -> String {
     let prev = if previous_block_hash.is_empty() {
         "0"
     } else {
         previous_block_hash
     };
 
-    let input = format!("{}-{}-{}-{}", uploader_id, ts, file_hash, prev);
-    log(&format!("[block_signature] Hash input: {}", input));
+    if ciphertext_hash.is_empty() {
+        format!("{}-{}-{}-{}", uploader_id, timestamp_ms, file_hash, prev)
+    } else {
+        format!("{}-{}-{}-{}-{}", uploader_id, timestamp_ms, file_hash, ciphertext_hash, prev)
+    }
+}
 
-    let mut hasher = Sha256::new();
-    hasher.update(input.as_bytes());
-    let result = hasher.finalize();
-    let signature = bytes_to_hex(&result);
+/// What a ledger block records.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    /// An upload (or a block that predates `kind`)
+    #[default]
+    Upload,
+    /// A DEK rotation that revoked access to the file
+    Revocation,
+    /// A destruction certificate; `file_hash` is the certificate hash
+    Destruction,
+}
 
-    log(&format!("[block_signature] Signature: {}", signature));
+impl BlockKind {
+    fn is_upload(&self) -> bool {
+        *self == BlockKind::Upload
+    }
+}
 
-    BlockSignatureResult {
-        success: true,
-        signature_hex: signature,
-        error_message: String::new(),
+/// A ledger block produced by a crate operation, ready for `file_ledger`.
+///
+/// Carries the inputs next to the signature so the chain can be re-verified.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LedgerEntry {
+    pub uploader_id: String,
+    pub timestamp_ms: u64,
+    pub file_hash: String,
+    /// Empty for blocks that do not cover a stored object
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ciphertext_hash: String,
+    pub previous_block_hash: String,
+    pub signature_hex: String,
+    /// Covered by the signature; omitted for uploads
+    #[serde(default, skip_serializing_if = "BlockKind::is_upload")]
+    pub kind: BlockKind,
+}
+
+impl LedgerEntry {
+    /// An upload block. `file_hash` is a SHA-256 hex digest, `ciphertext_hash`
+    /// is empty or one, and `previous_block_hash` is empty, "0" or one.
+    pub fn new(
+        uploader_id: &str,
+        timestamp_ms: u64,
+        file_hash: &str,
+        ciphertext_hash: &str,
+        previous_block_hash: &str,
+    ) -> Result<Self, String> {
+        check_hashes(file_hash, ciphertext_hash, previous_block_hash)?;
+        let previous_block_hash = if previous_block_hash.is_empty() { "0" } else { previous_block_hash };
+        Ok(LedgerEntry {
+            uploader_id: uploader_id.to_string(),
+            timestamp_ms,
+            file_hash: file_hash.to_string(),
+            ciphertext_hash: ciphertext_hash.to_string(),
+            previous_block_hash: previous_block_hash.to_string(),
+            signature_hex: compute_block_signature(
+                uploader_id,
+                timestamp_ms,
+                file_hash,
+                ciphertext_hash,
+                previous_block_hash,
+            ),
+            kind: BlockKind::Upload,
+        })
+    }
+
+    /// A revocation block: `actor_id` rotated the file's DEK, leaving
+    /// `ciphertext_hash` as the stored object.
+    pub fn revocation(
+        actor_id: &str,
+        timestamp_ms: u64,
+        file_hash: &str,
+        ciphertext_hash: &str,
+        previous_block_hash: &str,
+    ) -> Result<Self, String> {
+        let mut entry = LedgerEntry::new(actor_id, timestamp_ms, file_hash, ciphertext_hash, previous_block_hash)?;
+        entry.kind = BlockKind::Revocation;
+        entry.signature_hex = entry.expected_signature();
+        Ok(entry)
+    }
+
+    /// A destruction block: `actor_id` destroyed the wrappings listed in the
+    /// certificate whose hash is `certificate_hash`.
+    pub fn destruction(
+        actor_id: &str,
+        timestamp_ms: u64,
+        certificate_hash: &str,
+        previous_block_hash: &str,
+    ) -> Result<Self, String> {
+        let mut entry = LedgerEntry::new(actor_id, timestamp_ms, certificate_hash, "", previous_block_hash)?;
+        entry.kind = BlockKind::Destruction;
+        entry.signature_hex = entry.expected_signature();
+        Ok(entry)
+    }
+
+    pub fn is_revocation(&self) -> bool {
+        self.kind == BlockKind::Revocation
+    }
+
+    pub fn is_destruction(&self) -> bool {
+        self.kind == BlockKind::Destruction
+    }
+
+    fn expected_signature(&self) -> String {
+        let compute = match self.kind {
+            BlockKind::Upload => compute_block_signature,
+            BlockKind::Revocation => compute_revocation_signature,
+            BlockKind::Destruction => compute_destruction_signature,
+        };
+        compute(&self.uploader_id, self.timestamp_ms, &self.file_hash, &self.ciphertext_hash, &self.previous_block_hash)
+    }
+
+    /// Recomputes this block's signature from its inputs.
+    pub fn is_consistent(&self) -> bool {
+        check_hashes(&self.file_hash, &self.ciphertext_hash, &self.previous_block_hash).is_ok()
+            && self.expected_signature().eq_ignore_ascii_case(&self.signature_hex)
+    }
+}
+
+/// Checks a ledger chain in order: every block's signature matches its inputs
+/// and links to the block before it. The first block may link to anything
+/// (a genesis "0" or a block outside the given slice).
+pub fn verify_ledger_chain(entries: &[LedgerEntry]) -> Result<(), String> {
+    for (i, entry) in entries.iter().enumerate() {
+        if !entry.is_consistent() {
+            return Err(format!("Ledger block {} has an invalid signature", i));
+        }
+        if i > 0 && !entry.previous_block_hash.eq_ignore_ascii_case(&entries[i - 1].signature_hex) {
+            return Err(format!("Ledger block {} does not link to block {}", i, i - 1));
+        }
+    }
+    Ok(())
+}
+
+/// Checks a stored ciphertext against the hash recorded for it.
+pub fn verify_ciphertext_hash(blob: &[u8], expected_hash: &str) -> Result<(), String> {
+    let expected = hex_to_bytes(expected_hash)?;
+    if expected.len() != 32 {
+        return Err(format!("Ciphertext hash must be 32 bytes, got {}", expected.len()));
+    }
+    if Sha256::digest(blob).as_slice() != expected.as_slice() {
+        return Err("Ciphertext does not match the recorded hash".to_string());
+    }
+    Ok(())
+}
+
+/// Checks a stored `.enc` object against the ciphertext hash in its ledger
+/// block. Needs no DEK, so it works for auditors without access to the file.
+///
+/// # Arguments
+/// * `blob` - The stored object's bytes
+/// * `expected_hash` - The block's `ciphertext_hash`
+#[wasm_bindgen]
+pub fn verify_ciphertext(blob: &[u8], expected_hash: &str) -> SignatureVerificationResult {
+    log(&format!("[verify_ciphertext] Hashing {} bytes...", blob.len()));
+
+    let result = verify_ciphertext_hash(blob, expected_hash);
+    if let Err(e) = &result {
+        log(&format!("[verify_ciphertext] Verification failed: {}", e));
     }
+    result.into()
 }
@@ -0,0 +1,263 @@
+//! Crypto-shredding with signed destruction certificates.
+//!
+//! A file is unreadable once every wrapping of its DEK is gone, whatever
+//! happens to the ciphertext. `shred_file` records which `file_dek` rows are
+//! about to be deleted in a certificate signed with the actor's identity key:
+//! the file hash, a hash of each destroyed wrapped DEK (as in recipient
+//! manifests) and a timestamp. The certificate hash is chained into the ledger
+//! as a destruction block (`LedgerEntry::destruction`), which is signed under
+//! its own prefix so it cannot be mistaken for an upload.
+//!
+//! Deleting the rows is still up to the caller; the certificate only proves
+//! which wrappings the actor destroyed and when.
+
+use wasm_bindgen::prelude::*;
+use sha2::{Sha256, Digest};
+use x25519_dalek::{PublicKey, StaticSecret};
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroize;
+
+pub use crate::{bytes_to_hex, hex_to_bytes, log, push_length_prefixed};
+use crate::block_signature::{LedgerEntry, verify_ledger_chain};
+use crate::encrypt_file::FileDekInput;
+use crate::identity_signature::{SignatureVerificationResult, sign_with_identity_key, verify_identity_signature};
+use crate::masterkey_decryptor::PrivateKeyCredentials;
+use crate::recipient_manifest::wrapped_dek_hash;
+
+const CERTIFICATE_LABEL: &[u8] = b"filechain-destruction-certificate-v1";
+const CERTIFICATE_VERSION: u32 = 1;
+
+/// A signed record of the DEK wrappings destroyed for a file.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DestructionCertificate {
+    pub version: u32,
+    pub file_id: String,
+    pub file_hash: String,
+    /// `wrapped_dek_hash` of every destroyed `file_dek` row
+    pub destroyed_dek_hashes: Vec<String>,
+    pub actor_id: String,
+    pub signer_public_key_hex: String,
+    pub timestamp_ms: u64,
+    pub signature_hex: String,
+}
+
+impl DestructionCertificate {
+    /// The canonical bytes covered by `signature_hex`.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        push_length_prefixed(&mut out, CERTIFICATE_LABEL);
+        out.extend_from_slice(&self.version.to_be_bytes());
+        push_length_prefixed(&mut out, self.file_id.as_bytes());
+        push_length_prefixed(&mut out, self.file_hash.as_bytes());
+        out.extend_from_slice(&(self.destroyed_dek_hashes.len() as u32).to_be_bytes());
+        for hash in &self.destroyed_dek_hashes {
+            push_length_prefixed(&mut out, hash.as_bytes());
+        }
+        push_length_prefixed(&mut out, self.actor_id.as_bytes());
+        push_length_prefixed(&mut out, self.signer_public_key_hex.as_bytes());
+        out.extend_from_slice(&self.timestamp_ms.to_be_bytes());
+        out
+    }
+
+    /// The hash recorded as `file_hash` in the certificate's ledger block.
+    /// Covers the signature too, so the block commits to the signed certificate.
+    pub fn certificate_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.signing_bytes());
+        hasher.update(self.signature_hex.to_ascii_lowercase().as_bytes());
+        bytes_to_hex(&hasher.finalize())
+    }
+
+    /// Checks the version and the actor's signature.
+    pub fn verify_signature(&self) -> Result<(), String> {
+        if self.version != CERTIFICATE_VERSION {
+            return Err(format!("Unsupported certificate version {}", self.version));
+        }
+        let signer_public_key = hex_to_bytes(&self.signer_public_key_hex)?;
+        let signature = hex_to_bytes(&self.signature_hex)?;
+        verify_identity_signature(&signer_public_key, &self.signing_bytes(), &signature)
+            .map_err(|e| format!("Certificate signature invalid: {}", e))
+    }
+
+    /// Checks that the certificate was signed by `expected_signer_public_key_hex`,
+    /// the actor's key as the caller knows it. An empty key is an error: any
+    /// key can sign a certificate, so the signature alone proves nothing.
+    pub fn verify_signer(&self, expected_signer_public_key_hex: &str) -> Result<(), String> {
+        if expected_signer_public_key_hex.is_empty() {
+            return Err("The expected certificate signer key is required".to_string());
+        }
+        if !self.signer_public_key_hex.eq_ignore_ascii_case(expected_signer_public_key_hex) {
+            return Err("Certificate was signed by an unexpected key".to_string());
+        }
+        self.verify_signature()
+    }
+
+    /// Checks the certificate against a ledger chain: the signer must be the
+    /// expected key, and the chain must be intact and contain the
+    /// certificate's destruction block.
+    ///
+    /// Returns the index of that block.
+    pub fn verify_against_ledger(
+        &self,
+        ledger: &[LedgerEntry],
+        expected_signer_public_key_hex: &str,
+    ) -> Result<usize, String> {
+        self.verify_signer(expected_signer_public_key_hex)?;
+        verify_ledger_chain(ledger)?;
+
+        let certificate_hash = self.certificate_hash();
+        ledger
+            .iter()
+            .position(|entry| {
+                entry.is_destruction()
+                    && entry.uploader_id == self.actor_id
+                    && entry.timestamp_ms == self.timestamp_ms
+                    && entry.file_hash.eq_ignore_ascii_case(&certificate_hash)
+            })
+            .ok_or_else(|| "Certificate is not recorded in the ledger".to_string())
+    }
+}
+
+/// Input for shredding a file.
+#[derive(Deserialize)]
+pub struct ShredFileInput {
+    /// The actor's own credentials, used to sign the certificate
+    pub credentials: PrivateKeyCredentials,
+    pub actor_id: String,
+    pub file_id: String,
+    /// The file's `original_hash_hex`
+    pub file_hash: String,
+    /// Every `file_dek` row of the file, all of which will be deleted
+    pub dek_entries: Vec<FileDekInput>,
+    /// Unix timestamp in milliseconds (from Date.now())
+    pub timestamp_ms: f64,
+    /// The signature of the latest ledger block, or "0" for genesis
+    pub previous_block_hash: String,
+}
+
+#[derive(Serialize)]
+pub struct ShredFileResult {
+    pub success: bool,
+    pub certificate: Option<DestructionCertificate>,
+    /// The ledger block recording the certificate
+    pub ledger_entry: Option<LedgerEntry>,
+    pub error_message: String,
+}
+
+/// Produces a signed destruction certificate for a file's DEK entries.
+///
+/// # Arguments
+/// * `input_js` - A JsValue representing a `ShredFileInput`
+///
+/// # Returns
+/// A JsValue containing `ShredFileResult`. Store the certificate and the
+/// ledger entry, then delete the listed `file_dek` rows (and the ciphertext).
+#[wasm_bindgen]
+pub fn shred_file(input_js: JsValue) -> JsValue {
+    log("[shred_file] Creating destruction certificate...");
+
+    let result = match serde_wasm_bindgen::from_value::<ShredFileInput>(input_js)
+        .map_err(|e| format!("Failed to parse input: {}", e))
+        .and_then(|input| shred(&input))
+    {
+        Ok((certificate, ledger_entry)) => {
+            log(&format!(
+                "[shred_file] Certified destruction of {} DEK entries",
+                certificate.destroyed_dek_hashes.len()
+            ));
+            ShredFileResult {
+                success: true,
+                certificate: Some(certificate),
+                ledger_entry: Some(ledger_entry),
+                error_message: String::new(),
+            }
+        }
+        Err(e) => {
+            log(&format!("[shred_file] Failed: {}", e));
+            ShredFileResult { success: false, certificate: None, ledger_entry: None, error_message: e }
+        }
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// The core of [`shred_file`] for a parsed input.
+pub fn shred(input: &ShredFileInput) -> Result<(DestructionCertificate, LedgerEntry), String> {
+    if input.actor_id.is_empty() {
+        return Err("Actor ID cannot be empty".to_string());
+    }
+    if input.file_hash.is_empty() {
+        return Err("File hash cannot be empty".to_string());
+    }
+    if input.dek_entries.is_empty() {
+        return Err("At least one DEK entry is required".to_string());
+    }
+
+    let destroyed_dek_hashes = input
+        .dek_entries
+        .iter()
+        .map(|entry| {
+            if entry.file_id != input.file_id {
+                return Err(format!("DEK entry belongs to file {}, not {}", entry.file_id, input.file_id));
+            }
+            Ok(wrapped_dek_hash(&entry.encrypted_dek, &entry.dek_nonce, &entry.ephemeral_public_key))
+        })
+        .collect::<Result<Vec<String>, String>>()?;
+
+    let mut private_key = input.credentials.unlock()?;
+    let private_array: [u8; 32] = private_key.as_slice().try_into().unwrap();
+    let signer_public = PublicKey::from(&StaticSecret::from(private_array));
+
+    let mut certificate = DestructionCertificate {
+        version: CERTIFICATE_VERSION,
+        file_id: input.file_id.clone(),
+        file_hash: input.file_hash.clone(),
+        destroyed_dek_hashes,
+        actor_id: input.actor_id.clone(),
+        signer_public_key_hex: bytes_to_hex(signer_public.as_bytes()),
+        timestamp_ms: input.timestamp_ms as u64,
+        signature_hex: String::new(),
+    };
+
+    let signature = sign_with_identity_key(&private_key, &certificate.signing_bytes());
+    private_key.zeroize();
+    certificate.signature_hex = bytes_to_hex(&signature?);
+
+    let ledger_entry = LedgerEntry::destruction(
+        &certificate.actor_id,
+        certificate.timestamp_ms,
+        &certificate.certificate_hash(),
+        &input.previous_block_hash,
+    )?;
+
+    Ok((certificate, ledger_entry))
+}
+
+/// Verifies a destruction certificate against the ledger.
+///
+/// # Arguments
+/// * `certificate_js` - A JsValue representing a `DestructionCertificate`
+/// * `ledger_js` - A JsValue representing the ledger blocks (`LedgerEntry[]`) in chain order
+/// * `expected_signer_public_key_hex` - The actor's known public key (required)
+#[wasm_bindgen]
+pub fn verify_destruction_certificate(
+    certificate_js: JsValue,
+    ledger_js: JsValue,
+    expected_signer_public_key_hex: &str,
+) -> SignatureVerificationResult {
+    log("[verify_destruction_certificate] Verifying certificate...");
+
+    let certificate = match serde_wasm_bindgen::from_value::<DestructionCertificate>(certificate_js) {
+        Ok(certificate) => certificate,
+        Err(e) => return Err(format!("Failed to parse certificate: {}", e)).into(),
+    };
+    let ledger = match serde_wasm_bindgen::from_value::<Vec<LedgerEntry>>(ledger_js) {
+        Ok(ledger) => ledger,
+        Err(e) => return Err(format!("Failed to parse ledger: {}", e)).into(),
+    };
+
+    certificate
+        .verify_against_ledger(&ledger, expected_signer_public_key_hex)
+        .map(|_| ())
+        .into()
+}
@@ -1,13 +1,19 @@
 use wasm_bindgen::prelude::*;
 use aes_gcm::{
-    Aes256Gcm, Nonce, aead::{Aead, KeyInit, generic_array::GenericArray}
+    Aes256Gcm, Nonce, aead::{Aead, KeyInit, Payload, generic_array::GenericArray}
 };
-use x25519_dalek::{PublicKey, StaticSecret};
+use x25519_dalek::StaticSecret;
 use serde::Deserialize;
 use zeroize::Zeroize;
 
-pub use crate::{bytes_to_hex, log};
+pub use crate::{bytes_to_hex, hash_file_with_progress, log};
 pub use crate::encrypt_file::hash_file;
+use crate::encrypt_file::canonical_public_key;
+use crate::grant_window::GrantWindow;
+use crate::group_key::{GroupKeyEntryInput, unwrap_group_private_key};
+use crate::payload_frame::{open_payload, open_payload_with_progress};
+use crate::progress::{ERROR_CANCELLED, ERROR_FAILED, JsProgress, NoProgress, Progress, ProgressError, ProgressEvent, checkpoint};
+use crate::recipient_manifest::RecipientManifest;
 
 #[derive(Deserialize, Zeroize)]
 #[zeroize(drop)]
@@ -21,6 +27,49 @@ pub struct DecryptionContext {
     pub encrypted_dek: Vec<u8>,
     pub dek_nonce: Vec<u8>,
     pub file_nonce: Vec<u8>,
+    /// The user's group key entry, when the DEK was wrapped to a group
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub group_entry: Option<GroupKeyEntryInput>,
+    /// Optional signed recipient manifest; when set, the DEK entry must be covered by it
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub manifest: Option<RecipientManifest>,
+    /// The requesting user's id, looked up in the manifest
+    #[serde(default)]
+    pub user_id: String,
+    /// The uploader's pinned public key (hex); required with `manifest`
+    #[serde(default)]
+    pub manifest_signer_public_key_hex: String,
+    /// The DEK entry's grant window, if it is time-limited
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub not_before: Option<u64>,
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub not_after: Option<u64>,
+    /// The caller's clock (Unix ms); required for time-limited entries
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub now_ms: Option<f64>,
+}
+
+impl DecryptionContext {
+    fn window(&self) -> GrantWindow {
+        GrantWindow::new(self.not_before, self.not_after)
+    }
+}
+
+/// Checks that the requesting user's DEK entry is covered by a valid manifest.
+fn check_manifest(context: &DecryptionContext, manifest: &RecipientManifest) -> Result<(), String> {
+    manifest.verify_signer(&context.manifest_signer_public_key_hex)?;
+    manifest.authorises(
+        &context.user_id,
+        context.group_entry.is_some(),
+        &context.encrypted_dek,
+        &context.dek_nonce,
+        &context.ephemeral_public_key,
+    )
 }
 
 fn validate_inputs(private_key: &[u8], ephemeral_public_key: &[u8], dek_nonce: &[u8], file_nonce: &[u8]) -> Result<(), String> {
@@ -46,6 +95,7 @@ pub struct DecryptedFileResult {
     decrypted_data: Vec<u8>,
     file_hash_hex: String,
     error_message: String,
+    cancelled: bool,
 }
 
 /// Decrypts file data using hybrid decryption (X25519 + AES-256-GCM)
@@ -66,8 +116,30 @@ pub struct DecryptedFileResult {
 /// DecryptedFileResult containing decrypted data and its hash for verification
 #[wasm_bindgen]
 pub fn decrypt_file(val: JsValue) -> DecryptedFileResult {
+    decrypt_file_with_progress(val, JsValue::UNDEFINED)
+}
+
+/// Like [`decrypt_file`], reporting progress and stopping when asked.
+///
+/// # Arguments
+/// * `val` - A serialized `DecryptionContext` object containing all required data
+/// * `controls_js` - `{ on_progress, signal }` (see `progress`), or undefined
+///
+/// # Returns
+/// DecryptedFileResult; an aborted call fails with `error_code` "cancelled"
+///
+/// Payloads encrypted without `chunked` are decrypted in one piece: `Decrypt`
+/// progress goes straight from 0 to the total and an abort only takes effect
+/// once that step is done.
+#[wasm_bindgen]
+pub fn decrypt_file_with_progress(val: JsValue, controls_js: JsValue) -> DecryptedFileResult {
     log("[decrypt_file] Starting file decryption...");
 
+    let mut progress = match JsProgress::from_controls(&controls_js) {
+        Ok(progress) => progress,
+        Err(e) => return DecryptedFileResult::from_decryption(Err(format!("Failed to parse controls: {}", e))),
+    };
+
     // Deserialize arguments
     let context: DecryptionContext = match serde_wasm_bindgen::from_value(val) {
         Ok(c) => c,
@@ -78,21 +150,68 @@ pub fn decrypt_file(val: JsValue) -> DecryptedFileResult {
                 decrypted_data: vec![],
                 file_hash_hex: String::new(),
                 error_message: format!("Argument parsing failed: {}", e),
+                cancelled: false,
             };
         }
     };
 
     log(&format!("[decrypt_file] Encrypted size: {} bytes", context.encrypted_data.len()));
+    decrypt_with_context_and_progress(&context, &mut progress)
+}
+
+/// The core of [`decrypt_file`] for a parsed context.
+pub fn decrypt_with_context(context: &DecryptionContext) -> DecryptedFileResult {
+    decrypt_with_context_and_progress(context, &mut NoProgress)
+}
+
+/// [`decrypt_with_context`], reporting to `progress` and checking it for an
+/// abort before and after the KDF and between chunks.
+pub fn decrypt_with_context_and_progress(context: &DecryptionContext, progress: &mut dyn Progress) -> DecryptedFileResult {
+    if let Some(manifest) = &context.manifest {
+        log("[decrypt_file] Verifying recipient manifest...");
+        if let Err(e) = check_manifest(context, manifest) {
+            log(&format!("[decrypt_file] Manifest verification failed: {}", e));
+            return DecryptedFileResult {
+                success: false,
+                decrypted_data: vec![],
+                file_hash_hex: String::new(),
+                error_message: e,
+                cancelled: false,
+            };
+        }
+    }
+
+    let window = context.window();
+    if let Err(e) = window.check(context.now_ms.map(|ms| ms as u64)) {
+        log(&format!("[decrypt_file] Access grant refused: {}", e));
+        return DecryptedFileResult {
+            success: false,
+            decrypted_data: vec![],
+            file_hash_hex: String::new(),
+            error_message: e,
+            cancelled: false,
+        };
+    }
 
     // Step 1: Decrypt the private key from the user's secrets
     log("[decrypt_file] Decrypting private key...");
-    
+    if let Err(e) = checkpoint(progress) {
+        return DecryptedFileResult::from_error(e);
+    }
+
+    progress.report(ProgressEvent::KdfStarted);
     let key_result = crate::masterkey_decryptor::decrypt_private_key(
         &context.password,
         &context.pk_salt,
         &context.encrypted_private_key,
         &context.pk_nonce
     );
+    progress.report(ProgressEvent::KdfFinished);
+
+    if let Err(e) = checkpoint(progress) {
+        log("[decrypt_file] Cancelled after unlocking the private key");
+        return DecryptedFileResult::from_error(e);
+    }
 
     if !key_result.success() {
          log(&format!("[decrypt_file] Private key decryption failed: {}", key_result.error_message()));
@@ -101,10 +220,48 @@ pub fn decrypt_file(val: JsValue) -> DecryptedFileResult {
             decrypted_data: vec![],
             file_hash_hex: String::new(),
             error_message: format!("Private key decryption failed: {}", key_result.error_message()),
+            cancelled: false,
         };
     }
-    
-    let private_key_bytes = key_result.private_key();
+
+    decrypt_with_private_key_and_progress(context, key_result.private_key(), progress)
+}
+
+/// Steps 2-4 of [`decrypt_file`], for a private key that is already unlocked.
+///
+/// Does not check the manifest signature or the grant window; `decrypt_file`
+/// does that before unlocking the key.
+pub fn decrypt_with_private_key(context: &DecryptionContext, private_key: Vec<u8>) -> DecryptedFileResult {
+    decrypt_with_private_key_and_progress(context, private_key, &mut NoProgress)
+}
+
+/// [`decrypt_with_private_key`], reporting to `progress` and checking it for
+/// an abort between chunks.
+pub fn decrypt_with_private_key_and_progress(
+    context: &DecryptionContext,
+    private_key: Vec<u8>,
+    progress: &mut dyn Progress,
+) -> DecryptedFileResult {
+    let window = context.window();
+    let private_key_bytes = match &context.group_entry {
+        Some(group_entry) => {
+            log("[decrypt_file] Unwrapping group private key...");
+            match unwrap_group_private_key(&private_key, group_entry) {
+                Ok(group_private_key) => group_private_key,
+                Err(e) => {
+                    log(&format!("[decrypt_file] {}", e));
+                    return DecryptedFileResult {
+                        success: false,
+                        decrypted_data: vec![],
+                        file_hash_hex: String::new(),
+                        error_message: e,
+                        cancelled: false,
+                    };
+                }
+            }
+        }
+        None => private_key,
+    };
 
     if let Err(e) = validate_inputs(&private_key_bytes, &context.ephemeral_public_key, &context.dek_nonce, &context.file_nonce) {
         log(&format!("[decrypt_file] Input validation failed: {}", e));
@@ -113,15 +270,27 @@ pub fn decrypt_file(val: JsValue) -> DecryptedFileResult {
             decrypted_data: vec![],
             file_hash_hex: String::new(),
             error_message: e,
+            cancelled: false,
         };
     }
 
+    let ephemeral_public = match canonical_public_key(&context.ephemeral_public_key, "Ephemeral Public Key") {
+        Ok(key) => key,
+        Err(e) => {
+            log(&format!("[decrypt_file] Input validation failed: {}", e));
+            return DecryptedFileResult {
+                success: false,
+                decrypted_data: vec![],
+                file_hash_hex: String::new(),
+                error_message: e,
+                cancelled: false,
+            };
+        }
+    };
+
     log("[decrypt_file] Performing ECDH to derive shared secret...");
     let private_key_array: [u8; 32] = private_key_bytes.try_into().unwrap();
-    let ephemeral_public_array: [u8; 32] = context.ephemeral_public_key.as_slice().try_into().unwrap();
-    
     let private_key = StaticSecret::from(private_key_array);
-    let ephemeral_public = PublicKey::from(ephemeral_public_array);
     
     let shared_secret = private_key.diffie_hellman(&ephemeral_public);
     log("[decrypt_file] Shared secret derived via ECDH");
@@ -132,7 +301,8 @@ pub fn decrypt_file(val: JsValue) -> DecryptedFileResult {
     let dek_cipher = Aes256Gcm::new(shared_key);
     let dek_nonce_ga = Nonce::from_slice(&context.dek_nonce);
 
-    let dek = match dek_cipher.decrypt(dek_nonce_ga, context.encrypted_dek.as_slice()) {
+    let dek_payload = Payload { msg: context.encrypted_dek.as_slice(), aad: &window.aad() };
+    let dek = match dek_cipher.decrypt(dek_nonce_ga, dek_payload) {
         Ok(decrypted) => {
             log(&format!("[decrypt_file] DEK decrypted! Size: {} bytes", decrypted.len()));
             decrypted
@@ -144,6 +314,7 @@ pub fn decrypt_file(val: JsValue) -> DecryptedFileResult {
                 decrypted_data: vec![],
                 file_hash_hex: String::new(),
                 error_message: "DEK decryption failed. Invalid private key or corrupted data.".to_string(),
+                cancelled: false,
             };
         }
     };
@@ -155,38 +326,83 @@ pub fn decrypt_file(val: JsValue) -> DecryptedFileResult {
             decrypted_data: vec![],
             file_hash_hex: String::new(),
             error_message: format!("Decrypted DEK must be 32 bytes, got {}", dek.len()),
+            cancelled: false,
         };
     }
 
-    // Step 4: Decrypt the file using the DEK
+    // Step 4: Decrypt the file using the DEK, stripping any padding frame
     log("[decrypt_file] Decrypting file data...");
-    let dek_key = GenericArray::from_slice(&dek);
-    let file_cipher = Aes256Gcm::new(dek_key);
-    let file_nonce_ga = Nonce::from_slice(&context.file_nonce);
-
-    match file_cipher.decrypt(file_nonce_ga, context.encrypted_data.as_slice()) {
-        Ok(decrypted) => {
+    let decrypted = open_payload_with_progress(&dek, &context.file_nonce, &context.encrypted_data, progress)
+        .and_then(|mut decrypted| {
             log(&format!("[decrypt_file] Decryption successful! Decrypted size: {} bytes", decrypted.len()));
-            
+
             // Compute hash of decrypted file for verification
-            let file_hash = hash_file(&decrypted);
+            match hash_file_with_progress(&decrypted, progress) {
+                Ok(file_hash) => Ok((decrypted, file_hash)),
+                Err(e) => {
+                    decrypted.zeroize();
+                    Err(e)
+                }
+            }
+        });
+    match decrypted {
+        Ok((decrypted, file_hash)) => {
             log(&format!("[decrypt_file] Decrypted file hash: {}", file_hash));
-            
+
+            if let Some(manifest) = &context.manifest
+                && !manifest.file_hash.eq_ignore_ascii_case(&file_hash)
+            {
+                log("[decrypt_file] Decrypted file does not match the manifest file hash");
+                return DecryptedFileResult {
+                    success: false,
+                    decrypted_data: vec![],
+                    file_hash_hex: String::new(),
+                    error_message: "Decrypted file does not match the manifest file hash".to_string(),
+                    cancelled: false,
+                };
+            }
+
             DecryptedFileResult {
                 success: true,
                 decrypted_data: decrypted,
                 file_hash_hex: file_hash,
                 error_message: String::new(),
+                cancelled: false,
             }
         }
         Err(e) => {
             log(&format!("[decrypt_file] File decryption failed: {}", e));
-            DecryptedFileResult {
-                success: false,
-                decrypted_data: vec![],
-                file_hash_hex: String::new(),
-                error_message: "File decryption failed. Invalid DEK or corrupted data.".to_string(),
+            DecryptedFileResult::from_error(e)
+        }
+    }
+}
+
+impl DecryptedFileResult {
+    /// Builds a result from decrypted bytes (hashing them) or an error message.
+    pub fn from_decryption(result: Result<Vec<u8>, String>) -> Self {
+        match result {
+            Ok(decrypted) => {
+                let file_hash = hash_file(&decrypted);
+                DecryptedFileResult {
+                    success: true,
+                    decrypted_data: decrypted,
+                    file_hash_hex: file_hash,
+                    error_message: String::new(),
+                    cancelled: false,
+                }
             }
+            Err(e) => DecryptedFileResult::from_error(e.into()),
+        }
+    }
+
+    /// A failed result, remembering whether the caller aborted.
+    pub fn from_error(error: ProgressError) -> Self {
+        DecryptedFileResult {
+            success: false,
+            decrypted_data: vec![],
+            file_hash_hex: String::new(),
+            error_message: error.to_string(),
+            cancelled: error.is_cancelled(),
         }
     }
 }
@@ -198,6 +414,8 @@ impl DecryptedFileResult {
         self.success
     }
 
+    /// The file bytes, with any frame padding stripped to the length recorded
+    /// in the frame, so their length is the original file size.
     #[wasm_bindgen(getter)]
     pub fn decrypted_data(&self) -> Vec<u8> {
         self.decrypted_data.clone()
@@ -212,4 +430,22 @@ impl DecryptedFileResult {
     pub fn error_message(&self) -> String {
         self.error_message.clone()
     }
+
+    /// "cancelled" if the caller aborted, "failed" for any other error, empty on success
+    #[wasm_bindgen(getter)]
+    pub fn error_code(&self) -> String {
+        if self.success {
+            return String::new();
+        }
+        let code = if self.cancelled { ERROR_CANCELLED } else { ERROR_FAILED };
+        code.to_string()
+    }
+}
+
+/// Decrypts file bytes with an already unwrapped DEK, stripping any payload frame.
+pub fn decrypt_with_dek(dek: &[u8], file_nonce: &[u8], encrypted_data: &[u8]) -> Result<Vec<u8>, String> {
+    check_len(32, dek, "DEK")?;
+    check_len(12, file_nonce, "File Nonce")?;
+
+    open_payload(dek, file_nonce, encrypted_data)
 }
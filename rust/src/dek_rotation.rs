@@ -0,0 +1,236 @@
+//! File DEK rotation for access revocation.
+//!
+//! Deleting a `file_dek` row does not revoke someone who cached the DEK, so
+//! revocation re-encrypts the file under a fresh DEK:
+//! 1. Unlock the caller's private key and unwrap the current DEK
+//! 2. Decrypt the file (and its metadata record, if any) with the current DEK
+//! 3. Re-encrypt both under a fresh DEK, keeping the file's padding,
+//!    compression and chunked layout, and wrap the DEK for the remaining
+//!    recipients only
+//! 4. Append a revocation block chained to the previous ledger block
+
+use wasm_bindgen::prelude::*;
+use aes_gcm::aead::{OsRng, rand_core::{CryptoRng, RngCore}};
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroize;
+
+pub use crate::{bytes_to_hex, generate_nonce_with_rng, hash_file, log};
+use crate::block_signature::LedgerEntry;
+use crate::encrypt_file::{
+    EncryptedDekEntry, RecipientInput, generate_dek_with_rng, unwrap_dek_with_private_key, wrap_dek_for_public_key_with_rng,
+};
+use crate::file_metadata::{decrypt_metadata, encrypt_metadata_with_rng};
+use crate::masterkey_decryptor::unlock_private_key;
+use crate::payload_frame::{open_payload_with_options, seal_payload};
+
+/// Input for rotating a file's DEK.
+/// Passed from JavaScript via serde.
+#[derive(Deserialize, Zeroize)]
+#[zeroize(drop)]
+pub struct RotateFileDekInput {
+    /// The current encrypted file blob
+    pub encrypted_data: Vec<u8>,
+    /// The current file nonce (bytes)
+    pub file_nonce: Vec<u8>,
+    /// The file's metadata record under the current DEK (empty without metadata)
+    #[serde(default)]
+    pub encrypted_metadata: Vec<u8>,
+    #[serde(default)]
+    pub metadata_nonce: Vec<u8>,
+    /// The caller's password
+    pub password: String,
+    /// The caller's salt for key derivation
+    pub pk_salt: String,
+    /// The caller's encrypted private key (bytes)
+    pub encrypted_private_key: Vec<u8>,
+    /// The caller's pk_nonce (bytes)
+    pub pk_nonce: Vec<u8>,
+    /// The caller's `file_dek` row for the current DEK (bytes)
+    pub encrypted_dek: Vec<u8>,
+    pub dek_nonce: Vec<u8>,
+    pub ephemeral_public_key: Vec<u8>,
+    /// Recipients who keep access; everyone else is revoked
+    #[zeroize(skip)]
+    pub recipients: Vec<RecipientInput>,
+    /// The user performing the revocation, recorded in the ledger
+    pub actor_id: String,
+    /// Unix timestamp in milliseconds (from Date.now())
+    pub timestamp_ms: f64,
+    /// The signature of the file's latest ledger block
+    pub previous_block_hash: String,
+}
+
+/// Result of a file DEK rotation.
+#[derive(Serialize)]
+pub struct RotateFileDekResult {
+    pub success: bool,
+    pub encrypted_data: Vec<u8>,
+    pub file_nonce_hex: String,
+    pub original_hash_hex: String,
+    /// SHA-256 of the new `encrypted_data`, covered by `ledger_entry`
+    pub ciphertext_hash_hex: String,
+    pub dek_entries: Vec<EncryptedDekEntry>,
+    /// The metadata record re-encrypted under the new DEK (empty without metadata)
+    pub encrypted_metadata: Vec<u8>,
+    pub metadata_nonce_hex: String,
+    pub ledger_entry: Option<LedgerEntry>,
+    pub error_message: String,
+}
+
+impl RotateFileDekResult {
+    fn error(msg: String) -> Self {
+        RotateFileDekResult {
+            success: false,
+            encrypted_data: vec![],
+            file_nonce_hex: String::new(),
+            original_hash_hex: String::new(),
+            ciphertext_hash_hex: String::new(),
+            dek_entries: vec![],
+            encrypted_metadata: vec![],
+            metadata_nonce_hex: String::new(),
+            ledger_entry: None,
+            error_message: msg,
+        }
+    }
+}
+
+/// Re-encrypts a file under a fresh DEK for the remaining recipients.
+///
+/// # Arguments
+/// * `input_js` - A JsValue representing a `RotateFileDekInput`
+///
+/// # Returns
+/// A JsValue containing `RotateFileDekResult`. The frontend replaces the stored
+/// blob, `file_nonce`, the metadata record and every `file_dek` row, then
+/// inserts `ledger_entry` (a `kind: "revocation"` block).
+#[wasm_bindgen]
+pub fn rotate_file_dek(input_js: JsValue) -> JsValue {
+    log("[rotate_file_dek] Starting file DEK rotation...");
+
+    let result = match serde_wasm_bindgen::from_value::<RotateFileDekInput>(input_js) {
+        Ok(input) => rotate_with_rng(&mut OsRng, &input).unwrap_or_else(RotateFileDekResult::error),
+        Err(e) => RotateFileDekResult::error(format!("Failed to parse input: {}", e)),
+    };
+
+    if !result.success {
+        log(&format!("[rotate_file_dek] Rotation failed: {}", result.error_message));
+    }
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// The core of [`rotate_file_dek`] for a parsed input.
+pub fn rotate(input: &RotateFileDekInput) -> Result<RotateFileDekResult, String> {
+    rotate_with_rng(&mut OsRng, input)
+}
+
+/// [`rotate`] with the new DEK, nonces and ephemeral keys drawn from `rng`.
+pub fn rotate_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    input: &RotateFileDekInput,
+) -> Result<RotateFileDekResult, String> {
+    if input.recipients.is_empty() {
+        return Err("At least one remaining recipient is required".to_string());
+    }
+    if input.actor_id.is_empty() {
+        return Err("Actor ID cannot be empty".to_string());
+    }
+
+    // Step 1: Unwrap the current DEK
+    let mut private_key = unlock_private_key(
+        &input.password,
+        &input.pk_salt,
+        &input.encrypted_private_key,
+        &input.pk_nonce,
+    ).map_err(|e| format!("Failed to decrypt private key: {}", e))?;
+
+    let old_dek = unwrap_dek_with_private_key(
+        &private_key,
+        &input.ephemeral_public_key,
+        &input.dek_nonce,
+        &input.encrypted_dek,
+    );
+    private_key.zeroize();
+    let mut old_dek = old_dek?;
+
+    // Step 2: Decrypt the file, noting its frame, and the metadata record
+    let opened = open_payload_with_options(&old_dek, &input.file_nonce, &input.encrypted_data);
+    let metadata = if input.encrypted_metadata.is_empty() {
+        Ok(None)
+    } else {
+        decrypt_metadata(&old_dek, &input.encrypted_metadata, &input.metadata_nonce).map(Some)
+    };
+    old_dek.zeroize();
+    let (mut plaintext, frame) = opened?;
+    let metadata = match metadata {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            plaintext.zeroize();
+            return Err(e);
+        }
+    };
+    let original_hash = hash_file(&plaintext);
+
+    // Step 3: Re-encrypt both under a fresh DEK and wrap it for the remaining recipients
+    let mut new_dek = generate_dek_with_rng(rng);
+    let file_nonce = generate_nonce_with_rng(rng);
+    let encrypted = seal_payload(&new_dek, file_nonce.as_slice(), &plaintext, &frame);
+    plaintext.zeroize();
+    let encrypted = encrypted.and_then(|encrypted_data| {
+        let (encrypted_metadata, metadata_nonce) = match &metadata {
+            Some(metadata) => encrypt_metadata_with_rng(rng, &new_dek, metadata)?,
+            None => (vec![], vec![]),
+        };
+        Ok((encrypted_data, encrypted_metadata, metadata_nonce))
+    });
+    let (encrypted_data, encrypted_metadata, metadata_nonce) = match encrypted {
+        Ok(encrypted) => encrypted,
+        Err(e) => {
+            new_dek.zeroize();
+            return Err(e);
+        }
+    };
+    let ciphertext_hash = hash_file(&encrypted_data);
+
+    let mut dek_entries = Vec::with_capacity(input.recipients.len());
+    for recipient in &input.recipients {
+        let wrapped = recipient
+            .validate()
+            .and_then(|_| wrap_dek_for_public_key_with_rng(rng, &new_dek, &recipient.public_key, recipient.window()));
+        match wrapped {
+            Ok(wrapped) => dek_entries.push(wrapped.into_entry(recipient)),
+            Err(e) => {
+                new_dek.zeroize();
+                return Err(format!("DEK encryption failed for user {}: {}", recipient.label(), e));
+            }
+        }
+    }
+    new_dek.zeroize();
+
+    // Step 4: Chain a ledger entry recording the revocation
+    let ledger_entry = LedgerEntry::revocation(
+        &input.actor_id,
+        input.timestamp_ms as u64,
+        &original_hash,
+        &ciphertext_hash,
+        &input.previous_block_hash,
+    )?;
+
+    log(&format!(
+        "[rotate_file_dek] Rotation complete! {} recipients keep access.",
+        dek_entries.len()
+    ));
+
+    Ok(RotateFileDekResult {
+        success: true,
+        encrypted_data,
+        file_nonce_hex: bytes_to_hex(&file_nonce),
+        original_hash_hex: original_hash,
+        ciphertext_hash_hex: ciphertext_hash,
+        dek_entries,
+        encrypted_metadata,
+        metadata_nonce_hex: bytes_to_hex(&metadata_nonce),
+        ledger_entry: Some(ledger_entry),
+        error_message: String::new(),
+    })
+}
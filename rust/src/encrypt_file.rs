@@ -8,12 +8,19 @@
 
 use wasm_bindgen::prelude::*;
 use aes_gcm::{
-    Aes256Gcm, Nonce, aead::{Aead, KeyInit, OsRng, generic_array::GenericArray}
+    Aes256Gcm, Nonce,
+    aead::{Aead, KeyInit, OsRng, Payload, generic_array::GenericArray, rand_core::{CryptoRng, RngCore}},
 };
 use x25519_dalek::{PublicKey, StaticSecret};
 use serde::{Serialize, Deserialize};
+use zeroize::Zeroize;
 
-pub use crate::{generate_nonce, bytes_to_hex, hash_file, log};
+pub use crate::{generate_nonce, generate_nonce_with_rng, bytes_to_hex, hash_file, hash_file_with_progress, log};
+use crate::file_metadata::{FileMetadata, encrypt_metadata_with_rng, generate_object_name_with_rng};
+use crate::grant_window::GrantWindow;
+use crate::merkle::MerkleTree;
+use crate::payload_frame::{CompressionScheme, DEFAULT_SEGMENT_SIZE, FrameOptions, PaddingScheme, seal_payload_with_progress};
+use crate::progress::{JsProgress, NoProgress, Progress, ProgressError, checkpoint};
 
 #[wasm_bindgen]
 pub struct EncryptedFileResult {
@@ -45,6 +52,15 @@ pub struct EncryptedFileResult {
 pub fn encrypt_file(
     file_data: &[u8], 
     recipient_public_key: &[u8],
+) -> EncryptedFileResult {
+    encrypt_file_with_rng(&mut OsRng, file_data, recipient_public_key)
+}
+
+/// [`encrypt_file`] with the DEK, nonces and ephemeral key drawn from `rng`.
+pub fn encrypt_file_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    file_data: &[u8],
+    recipient_public_key: &[u8],
 ) -> EncryptedFileResult {
     log("[encrypt_file] Starting file encryption...");
     log(&format!("[encrypt_file] File size: {} bytes", file_data.len()));
@@ -69,7 +85,7 @@ pub fn encrypt_file(
 
     // Generate a random DEK
     log("[encrypt_file] Generating random DEK...");
-    let dek_secret = StaticSecret::random_from_rng(OsRng);
+    let dek_secret = StaticSecret::random_from_rng(&mut *rng);
     let dek: [u8; 32] = dek_secret.to_bytes();
     log(&format!("[encrypt_file] DEK generated: {}", bytes_to_hex(&dek)));
 
@@ -78,7 +94,7 @@ pub fn encrypt_file(
 
     // Encrypt the file using the DEK
     log("[encrypt_file] Encrypting file with DEK...");
-    let file_nonce = generate_nonce();
+    let file_nonce = generate_nonce_with_rng(rng);
     let file_nonce_hex = bytes_to_hex(file_nonce.as_slice());
     log(&format!("[encrypt_file] File nonce: {}", file_nonce_hex));
 
@@ -108,7 +124,7 @@ pub fn encrypt_file(
 
     // Generate ephemeral key pair and perform ECDH
     log("[encrypt_file] Generating ephemeral key pair for ECDH...");
-    let ephemeral_secret = StaticSecret::random_from_rng(OsRng);
+    let ephemeral_secret = StaticSecret::random_from_rng(&mut *rng);
     let ephemeral_public = PublicKey::from(&ephemeral_secret);
     log(&format!("[encrypt_file] Ephemeral public key: {}", bytes_to_hex(ephemeral_public.as_bytes())));
 
@@ -118,7 +134,7 @@ pub fn encrypt_file(
 
     // Encrypt the DEK using the shared secret
     log("[encrypt_file] Encrypting DEK with shared secret...");
-    let dek_nonce = generate_nonce();
+    let dek_nonce = generate_nonce_with_rng(rng);
     let dek_nonce_hex = bytes_to_hex(dek_nonce.as_slice());
     log(&format!("[encrypt_file] DEK nonce: {}", dek_nonce_hex));
 
@@ -216,19 +232,66 @@ impl EncryptedFileResult {
 
 /// Input for a single recipient in multi-key encryption.
 /// Passed from JavaScript via serde.
+///
+/// A recipient is either a user (`user_id` + their public key) or a group
+/// (`group_id` + the group's public key, see `group_key`).
+/// `not_before` / `not_after` (Unix ms) make it a time-limited grant, see `grant_window`.
 #[derive(Deserialize)]
 pub struct RecipientInput {
+    #[serde(default)]
     pub user_id: String,
+    #[serde(default)]
+    pub group_id: Option<String>,
     pub public_key: Vec<u8>,
+    #[serde(default)]
+    pub not_before: Option<u64>,
+    #[serde(default)]
+    pub not_after: Option<u64>,
+}
+
+impl RecipientInput {
+    /// The id used in log and error messages.
+    pub fn label(&self) -> &str {
+        match &self.group_id {
+            Some(group_id) => group_id,
+            None => &self.user_id,
+        }
+    }
+
+    /// Checks that exactly one of `user_id` / `group_id` is set.
+    pub fn validate(&self) -> Result<(), String> {
+        match (&self.group_id, self.user_id.is_empty()) {
+            (Some(_), false) => Err(format!(
+                "Recipient {} cannot have both a user_id and a group_id",
+                self.user_id
+            )),
+            (Some(group_id), true) if group_id.is_empty() => Err("Recipient group_id cannot be empty".to_string()),
+            (None, true) => Err("Recipient must have a user_id or a group_id".to_string()),
+            _ => self.window().validate(),
+        }
+    }
+
+    /// The recipient's grant window.
+    pub fn window(&self) -> GrantWindow {
+        GrantWindow::new(self.not_before, self.not_after)
+    }
 }
 
 /// A single encrypted DEK entry for one recipient.
+///
+/// For group recipients `user_id` is empty and `group_id` is set.
 #[derive(Serialize)]
 pub struct EncryptedDekEntry {
     pub user_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<String>,
     pub encrypted_dek_hex: String,
     pub dek_nonce_hex: String,
     pub ephemeral_public_key_hex: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<u64>,
 }
 
 /// Result of multi-key file encryption.
@@ -238,15 +301,77 @@ pub struct MultiKeyEncryptResultData {
     pub encrypted_data: Vec<u8>,
     pub file_nonce_hex: String,
     pub original_hash_hex: String,
+    /// Merkle root over `MERKLE_CHUNK_SIZE` chunks of the original file
+    pub merkle_root_hex: String,
+    /// SHA-256 of `encrypted_data`, for the ledger block
+    pub ciphertext_hash_hex: String,
     pub dek_entries: Vec<EncryptedDekEntry>,
+    /// The metadata record encrypted under the file DEK (empty without metadata)
+    pub encrypted_metadata: Vec<u8>,
+    pub metadata_nonce_hex: String,
+    /// A random opaque name for the storage object
+    pub object_name: String,
     pub error_message: String,
+    /// "cancelled" if the caller aborted, "failed" for any other error, empty on success
+    pub error_code: String,
+}
+
+impl MultiKeyEncryptResultData {
+    /// A failed result; a `String` error is reported as "failed".
+    pub fn error(error: impl Into<ProgressError>) -> Self {
+        let error = error.into();
+        MultiKeyEncryptResultData {
+            success: false,
+            encrypted_data: vec![],
+            file_nonce_hex: String::new(),
+            original_hash_hex: String::new(),
+            merkle_root_hex: String::new(),
+            ciphertext_hash_hex: String::new(),
+            dek_entries: vec![],
+            encrypted_metadata: vec![],
+            metadata_nonce_hex: String::new(),
+            object_name: String::new(),
+            error_code: error.code().to_string(),
+            error_message: error.to_string(),
+        }
+    }
+}
+
+/// Optional settings for [`encrypt_file_multi_with_options`].
+#[derive(Deserialize, Default)]
+pub struct EncryptOptions {
+    /// Metadata record to encrypt under the file DEK; `size` is filled in
+    #[serde(default)]
+    pub metadata: Option<FileMetadata>,
+    /// Pads the ciphertext to hide the exact file size ("none", "padme" or "power_of_two")
+    #[serde(default)]
+    pub padding: PaddingScheme,
+    /// Compresses the file before encryption ("none" or "deflate"); skipped
+    /// for already-compressed formats. The hash still covers the original bytes.
+    #[serde(default)]
+    pub compression: CompressionScheme,
+    /// Encrypts the file in independently authenticated segments so it can be
+    /// audited and read by range without fetching the whole ciphertext
+    #[serde(default)]
+    pub chunked: bool,
+}
+
+impl EncryptOptions {
+    fn frame(&self) -> FrameOptions {
+        FrameOptions {
+            padding: self.padding,
+            compression: self.compression,
+            segment_size: self.chunked.then_some(DEFAULT_SEGMENT_SIZE),
+        }
+    }
 }
 
 /// Encrypts file data using hybrid encryption for multiple recipients.
 ///
 /// # Arguments
 /// * `file_data` - The raw file bytes to encrypt
-/// * `recipients_js` - A JsValue representing an array of { user_id: string, public_key: number[] }
+/// * `recipients_js` - A JsValue representing an array of { user_id: string, public_key: number[] },
+///   optionally with `not_before` / `not_after` (Unix ms) for a time-limited grant
 ///
 /// # Returns
 /// A JsValue containing MultiKeyEncryptResultData with encrypted file and per-recipient DEK entries.
@@ -254,147 +379,195 @@ pub struct MultiKeyEncryptResultData {
 pub fn encrypt_file_multi(
     file_data: &[u8],
     recipients_js: JsValue,
+) -> JsValue {
+    encrypt_file_multi_with_options(file_data, recipients_js, JsValue::UNDEFINED)
+}
+
+/// Like [`encrypt_file_multi`], with optional settings.
+///
+/// # Arguments
+/// * `file_data` - The raw file bytes to encrypt
+/// * `recipients_js` - As for `encrypt_file_multi`
+/// * `options_js` - A JsValue representing `EncryptOptions`, or undefined
+///
+/// # Returns
+/// A JsValue containing MultiKeyEncryptResultData. With `options.metadata`,
+/// `encrypted_metadata` / `metadata_nonce_hex` hold the encrypted record for
+/// `decrypt_file_metadata`.
+#[wasm_bindgen]
+pub fn encrypt_file_multi_with_options(
+    file_data: &[u8],
+    recipients_js: JsValue,
+    options_js: JsValue,
+) -> JsValue {
+    encrypt_file_multi_with_progress(file_data, recipients_js, options_js, JsValue::UNDEFINED)
+}
+
+/// Like [`encrypt_file_multi_with_options`], reporting progress and
+/// stopping when asked.
+///
+/// # Arguments
+/// * `file_data` - The raw file bytes to encrypt
+/// * `recipients_js` - As for `encrypt_file_multi`
+/// * `options_js` - A JsValue representing `EncryptOptions`, or undefined
+/// * `controls_js` - `{ on_progress, signal }` (see `progress`), or undefined
+///
+/// # Returns
+/// A JsValue containing MultiKeyEncryptResultData. An aborted call fails with
+/// `error_code` "cancelled".
+///
+/// Without `chunked: true` in the options the file is encrypted in one
+/// piece: `Encrypt` progress goes straight from 0 to the total and an abort
+/// only takes effect once that step is done. Pass `chunked` for large files
+/// that should be cancellable mid-encryption.
+#[wasm_bindgen]
+pub fn encrypt_file_multi_with_progress(
+    file_data: &[u8],
+    recipients_js: JsValue,
+    options_js: JsValue,
+    controls_js: JsValue,
 ) -> JsValue {
     log("[encrypt_file_multi] Starting multi-key file encryption...");
     log(&format!("[encrypt_file_multi] File size: {} bytes", file_data.len()));
 
-    // Deserialize recipients from JS
+    let mut progress = match JsProgress::from_controls(&controls_js) {
+        Ok(progress) => progress,
+        Err(e) => {
+            let result = MultiKeyEncryptResultData::error(format!("Failed to parse controls: {}", e));
+            return serde_wasm_bindgen::to_value(&result).unwrap();
+        }
+    };
+
+    // Deserialize recipients and options from JS
     let recipients: Vec<RecipientInput> = match serde_wasm_bindgen::from_value(recipients_js) {
         Ok(r) => r,
         Err(e) => {
-            let result = MultiKeyEncryptResultData {
-                success: false,
-                encrypted_data: vec![],
-                file_nonce_hex: String::new(),
-                original_hash_hex: String::new(),
-                dek_entries: vec![],
-                error_message: format!("Failed to parse recipients: {}", e),
-            };
+            let result = MultiKeyEncryptResultData::error(format!("Failed to parse recipients: {}", e));
             return serde_wasm_bindgen::to_value(&result).unwrap();
         }
     };
 
+    let options: EncryptOptions = if options_js.is_undefined() || options_js.is_null() {
+        EncryptOptions::default()
+    } else {
+        match serde_wasm_bindgen::from_value(options_js) {
+            Ok(o) => o,
+            Err(e) => {
+                let result = MultiKeyEncryptResultData::error(format!("Failed to parse options: {}", e));
+                return serde_wasm_bindgen::to_value(&result).unwrap();
+            }
+        }
+    };
+
+    let result = encrypt_file_multi_with_rng_and_progress(&mut OsRng, file_data, &recipients, &options, &mut progress);
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// The core of [`encrypt_file_multi_with_options`], drawing every key, nonce
+/// and object name from `rng`.
+pub fn encrypt_file_multi_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    file_data: &[u8],
+    recipients: &[RecipientInput],
+    options: &EncryptOptions,
+) -> MultiKeyEncryptResultData {
+    encrypt_file_multi_with_rng_and_progress(rng, file_data, recipients, options, &mut NoProgress)
+}
+
+/// [`encrypt_file_multi_with_rng`], reporting to `progress` and checking it
+/// for an abort between chunks and between recipients.
+pub fn encrypt_file_multi_with_rng_and_progress<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    file_data: &[u8],
+    recipients: &[RecipientInput],
+    options: &EncryptOptions,
+    progress: &mut dyn Progress,
+) -> MultiKeyEncryptResultData {
     log(&format!("[encrypt_file_multi] {} recipients", recipients.len()));
 
     if recipients.is_empty() {
-        let result = MultiKeyEncryptResultData {
-            success: false,
-            encrypted_data: vec![],
-            file_nonce_hex: String::new(),
-            original_hash_hex: String::new(),
-            dek_entries: vec![],
-            error_message: "At least one recipient is required".to_string(),
-        };
-        return serde_wasm_bindgen::to_value(&result).unwrap();
+        return MultiKeyEncryptResultData::error("At least one recipient is required".to_string());
     }
 
-    // Validate all public keys
+    // Validate all recipients and public keys
     for (i, recipient) in recipients.iter().enumerate() {
+        if let Err(e) = recipient.validate() {
+            return MultiKeyEncryptResultData::error(e);
+        }
+
         if recipient.public_key.len() != 32 {
             log(&format!(
                 "[encrypt_file_multi] Invalid public key length for recipient {}: {}",
                 i, recipient.public_key.len()
             ));
-            let result = MultiKeyEncryptResultData {
-                success: false,
-                encrypted_data: vec![],
-                file_nonce_hex: String::new(),
-                original_hash_hex: String::new(),
-                dek_entries: vec![],
-                error_message: format!(
-                    "Public key for user {} must be 32 bytes, got {}",
-                    recipient.user_id, recipient.public_key.len()
-                ),
-            };
-            return serde_wasm_bindgen::to_value(&result).unwrap();
+            return MultiKeyEncryptResultData::error(format!(
+                "Public key for user {} must be 32 bytes, got {}",
+                recipient.label(), recipient.public_key.len()
+            ));
         }
     }
 
     // Generate a random DEK
     log("[encrypt_file_multi] Generating random DEK...");
-    let dek_secret = StaticSecret::random_from_rng(OsRng);
+    let dek_secret = StaticSecret::random_from_rng(&mut *rng);
     let dek: [u8; 32] = dek_secret.to_bytes();
 
-    log("[encrypt_file_multi] Computing original file hash...");
-    let original_hash = hash_file(file_data);
-
-    // Encrypt the file using the DEK
-    log("[encrypt_file_multi] Encrypting file with DEK...");
-    let file_nonce = generate_nonce();
-    let file_nonce_hex = bytes_to_hex(file_nonce.as_slice());
-
-    let dek_key = GenericArray::from_slice(&dek);
-    let file_cipher = Aes256Gcm::new(dek_key);
-    let file_nonce_ga = Nonce::from_slice(file_nonce.as_slice());
-
-    let encrypted_file_data = match file_cipher.encrypt(file_nonce_ga, file_data) {
-        Ok(encrypted) => {
-            log(&format!("[encrypt_file_multi] File encrypted! Size: {} bytes", encrypted.len()));
-            encrypted
-        }
-        Err(e) => {
-            let result = MultiKeyEncryptResultData {
-                success: false,
-                encrypted_data: vec![],
-                file_nonce_hex: String::new(),
-                original_hash_hex: String::new(),
-                dek_entries: vec![],
-                error_message: format!("File encryption failed: {}", e),
-            };
-            return serde_wasm_bindgen::to_value(&result).unwrap();
-        }
+    // Encrypt the file and its metadata record using the DEK
+    let sealed = match seal_file_with_rng(rng, &dek, file_data, options, progress) {
+        Ok(sealed) => sealed,
+        Err(e) => return MultiKeyEncryptResultData::error(e),
     };
 
     // Encrypt the DEK for each recipient
     let mut dek_entries: Vec<EncryptedDekEntry> = Vec::with_capacity(recipients.len());
 
     for (i, recipient) in recipients.iter().enumerate() {
+        if let Err(e) = checkpoint(progress) {
+            return MultiKeyEncryptResultData::error(e);
+        }
         log(&format!(
             "[encrypt_file_multi] Encrypting DEK for recipient {} (user: {})...",
-            i, recipient.user_id
+            i, recipient.label()
         ));
 
         let pk_array: [u8; 32] = recipient.public_key.as_slice().try_into().unwrap();
         let recipient_public = PublicKey::from(pk_array);
 
         // Generate ephemeral key pair for this recipient
-        let ephemeral_secret = StaticSecret::random_from_rng(OsRng);
+        let ephemeral_secret = StaticSecret::random_from_rng(&mut *rng);
         let ephemeral_public = PublicKey::from(&ephemeral_secret);
 
         // Derive shared secret via ECDH
         let shared_secret = ephemeral_secret.diffie_hellman(&recipient_public);
 
         // Encrypt the DEK with the shared secret
-        let dek_nonce = generate_nonce();
+        let dek_nonce = generate_nonce_with_rng(rng);
         let dek_nonce_hex = bytes_to_hex(dek_nonce.as_slice());
 
         let shared_key = GenericArray::from_slice(shared_secret.as_bytes());
         let dek_cipher = Aes256Gcm::new(shared_key);
         let dek_nonce_ga = Nonce::from_slice(dek_nonce.as_slice());
 
-        let encrypted_dek = match dek_cipher.encrypt(dek_nonce_ga, dek.as_ref()) {
+        let window = recipient.window();
+        let payload = Payload { msg: dek.as_ref(), aad: &window.aad() };
+        let encrypted_dek = match dek_cipher.encrypt(dek_nonce_ga, payload) {
             Ok(encrypted) => encrypted,
             Err(e) => {
-                let result = MultiKeyEncryptResultData {
-                    success: false,
-                    encrypted_data: vec![],
-                    file_nonce_hex: String::new(),
-                    original_hash_hex: String::new(),
-                    dek_entries: vec![],
-                    error_message: format!(
-                        "DEK encryption failed for user {}: {}",
-                        recipient.user_id, e
-                    ),
-                };
-                return serde_wasm_bindgen::to_value(&result).unwrap();
+                return MultiKeyEncryptResultData::error(format!(
+                    "DEK encryption failed for user {}: {}",
+                    recipient.label(), e
+                ));
             }
         };
 
         dek_entries.push(EncryptedDekEntry {
             user_id: recipient.user_id.clone(),
+            group_id: recipient.group_id.clone(),
             encrypted_dek_hex: bytes_to_hex(&encrypted_dek),
             dek_nonce_hex,
             ephemeral_public_key_hex: bytes_to_hex(ephemeral_public.as_bytes()),
+            not_before: window.not_before,
+            not_after: window.not_after,
         });
     }
 
@@ -403,16 +576,73 @@ pub fn encrypt_file_multi(
         dek_entries.len()
     ));
 
-    let result = MultiKeyEncryptResultData {
+    MultiKeyEncryptResultData {
         success: true,
-        encrypted_data: encrypted_file_data,
-        file_nonce_hex,
-        original_hash_hex: original_hash,
+        encrypted_data: sealed.encrypted_data,
+        file_nonce_hex: sealed.file_nonce_hex,
+        original_hash_hex: sealed.original_hash_hex,
+        merkle_root_hex: sealed.merkle_root_hex,
+        ciphertext_hash_hex: sealed.ciphertext_hash_hex,
         dek_entries,
+        encrypted_metadata: sealed.encrypted_metadata,
+        metadata_nonce_hex: sealed.metadata_nonce_hex,
+        object_name: generate_object_name_with_rng(rng),
         error_message: String::new(),
+        error_code: String::new(),
+    }
+}
+
+/// A file encrypted under a DEK, before the DEK is wrapped for anyone.
+pub struct SealedFile {
+    pub encrypted_data: Vec<u8>,
+    pub file_nonce_hex: String,
+    pub original_hash_hex: String,
+    pub merkle_root_hex: String,
+    pub ciphertext_hash_hex: String,
+    pub encrypted_metadata: Vec<u8>,
+    pub metadata_nonce_hex: String,
+}
+
+/// Hashes `file_data`, encrypts it under `dek` framed as `options` ask, and
+/// encrypts the metadata record, if any, under the same DEK.
+///
+/// The file step shared by [`encrypt_file_multi_with_rng_and_progress`] and
+/// other callers that wrap the DEK themselves.
+pub fn seal_file_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    dek: &[u8],
+    file_data: &[u8],
+    options: &EncryptOptions,
+    progress: &mut dyn Progress,
+) -> Result<SealedFile, ProgressError> {
+    log("[encrypt_file_multi] Computing original file hash and Merkle root...");
+    let (original_hash_hex, tree) = MerkleTree::hash_with_progress(file_data, progress)?;
+
+    log("[encrypt_file_multi] Encrypting file with DEK...");
+    let file_nonce = generate_nonce_with_rng(rng);
+    let encrypted_data = seal_payload_with_progress(dek, file_nonce.as_slice(), file_data, &options.frame(), progress)?;
+    log(&format!("[encrypt_file_multi] File encrypted! Size: {} bytes", encrypted_data.len()));
+
+    log("[encrypt_file_multi] Computing ciphertext hash...");
+    let ciphertext_hash_hex = hash_file_with_progress(&encrypted_data, progress)?;
+
+    let (encrypted_metadata, metadata_nonce) = match options.metadata.clone() {
+        Some(mut metadata) => {
+            metadata.size = file_data.len() as u64;
+            encrypt_metadata_with_rng(rng, dek, &metadata)?
+        }
+        None => (vec![], vec![]),
     };
 
-    serde_wasm_bindgen::to_value(&result).unwrap()
+    Ok(SealedFile {
+        encrypted_data,
+        file_nonce_hex: bytes_to_hex(file_nonce.as_slice()),
+        original_hash_hex,
+        merkle_root_hex: tree.root_hex(),
+        ciphertext_hash_hex,
+        encrypted_metadata,
+        metadata_nonce_hex: bytes_to_hex(&metadata_nonce),
+    })
 }
 
 // --- DEK re-encryption for file re-sharing ---
@@ -437,6 +667,23 @@ pub struct ReShareDekInput {
     pub ephemeral_public_key: Vec<u8>,
     /// The target recipient's public key (bytes)
     pub target_public_key: Vec<u8>,
+    /// The sharer's group key entry, when their copy of the DEK was wrapped to a group
+    #[serde(default)]
+    pub group_entry: Option<crate::group_key::GroupKeyEntryInput>,
+    /// The grant window of the sharer's DEK entry, if it is time-limited
+    #[serde(default)]
+    pub not_before: Option<u64>,
+    #[serde(default)]
+    pub not_after: Option<u64>,
+    /// The caller's clock (Unix ms); required when the sharer's entry is time-limited
+    #[serde(default)]
+    pub now_ms: Option<f64>,
+    /// The grant window for the target recipient's new entry; must lie inside
+    /// the sharer's window
+    #[serde(default)]
+    pub target_not_before: Option<u64>,
+    #[serde(default)]
+    pub target_not_after: Option<u64>,
 }
 
 /// Result of re-encrypting a DEK for a new recipient
@@ -446,6 +693,10 @@ pub struct ReShareDekResult {
     pub encrypted_dek_hex: String,
     pub dek_nonce_hex: String,
     pub ephemeral_public_key_hex: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<u64>,
     pub error_message: String,
 }
 
@@ -462,31 +713,55 @@ pub struct ReShareDekResult {
 pub fn re_encrypt_dek_for_recipient(input_js: JsValue) -> JsValue {
     log("[re_encrypt_dek] Starting DEK re-encryption for new recipient...");
 
-    let input: ReShareDekInput = match serde_wasm_bindgen::from_value(input_js) {
-        Ok(i) => i,
-        Err(e) => {
-            let result = ReShareDekResult {
-                success: false,
-                encrypted_dek_hex: String::new(),
-                dek_nonce_hex: String::new(),
-                ephemeral_public_key_hex: String::new(),
-                error_message: format!("Failed to parse input: {}", e),
-            };
-            return serde_wasm_bindgen::to_value(&result).unwrap();
-        }
-    };
-
     let err_result = |msg: String| -> JsValue {
         let result = ReShareDekResult {
             success: false,
             encrypted_dek_hex: String::new(),
             dek_nonce_hex: String::new(),
             ephemeral_public_key_hex: String::new(),
+            not_before: None,
+            not_after: None,
             error_message: msg,
         };
         serde_wasm_bindgen::to_value(&result).unwrap()
     };
 
+    let input: ReShareDekInput = match serde_wasm_bindgen::from_value(input_js) {
+        Ok(i) => i,
+        Err(e) => return err_result(format!("Failed to parse input: {}", e)),
+    };
+
+    let wrapped = match re_share_dek_with_rng(&mut OsRng, &input) {
+        Ok(wrapped) => wrapped,
+        Err(e) => return err_result(e),
+    };
+
+    log("[re_encrypt_dek] DEK re-encrypted for new recipient successfully");
+
+    let result = ReShareDekResult {
+        success: true,
+        encrypted_dek_hex: bytes_to_hex(&wrapped.encrypted_dek),
+        dek_nonce_hex: bytes_to_hex(&wrapped.dek_nonce),
+        ephemeral_public_key_hex: bytes_to_hex(&wrapped.ephemeral_public_key),
+        not_before: wrapped.window.not_before,
+        not_after: wrapped.window.not_after,
+        error_message: String::new(),
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// The core of [`re_encrypt_dek_for_recipient`] for a parsed input.
+pub fn re_share_dek(input: &ReShareDekInput) -> Result<WrappedDek, String> {
+    re_share_dek_with_rng(&mut OsRng, input)
+}
+
+/// [`re_share_dek`] with the new ephemeral key and nonce drawn from `rng`.
+pub fn re_share_dek_with_rng<R: RngCore + CryptoRng>(rng: &mut R, input: &ReShareDekInput) -> Result<WrappedDek, String> {
+    // Refuse entries outside their grant window before touching any key
+    let source_window = GrantWindow::new(input.not_before, input.not_after);
+    source_window.check(input.now_ms.map(|ms| ms as u64))?;
+
     // Step 1: Decrypt sharer's private key
     let key_result = crate::masterkey_decryptor::decrypt_private_key(
         &input.password,
@@ -496,76 +771,409 @@ pub fn re_encrypt_dek_for_recipient(input_js: JsValue) -> JsValue {
     );
 
     if !key_result.success() {
-        return err_result(format!("Failed to decrypt private key: {}", key_result.error_message()));
+        return Err(format!("Failed to decrypt private key: {}", key_result.error_message()));
+    }
+
+    re_share_dek_with_private_key_and_rng(rng, input, key_result.private_key())
+}
+
+/// Steps 2-6 of [`re_encrypt_dek_for_recipient`], for a private key that is
+/// already unlocked. Does not check the source grant window against the clock.
+pub fn re_share_dek_with_private_key(input: &ReShareDekInput, private_key: Vec<u8>) -> Result<WrappedDek, String> {
+    re_share_dek_with_private_key_and_rng(&mut OsRng, input, private_key)
+}
+
+/// [`re_share_dek_with_private_key`] with the new ephemeral key and nonce drawn from `rng`.
+pub fn re_share_dek_with_private_key_and_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    input: &ReShareDekInput,
+    private_key: Vec<u8>,
+) -> Result<WrappedDek, String> {
+    let source_window = GrantWindow::new(input.not_before, input.not_after);
+    let target_window = GrantWindow::new(input.target_not_before, input.target_not_after);
+    target_window.validate()?;
+    target_window.check_within(&source_window)?;
+
+    let mut private_key = match &input.group_entry {
+        Some(group_entry) => {
+            let group_private_key = crate::group_key::unwrap_group_private_key(&private_key, group_entry);
+            let mut private_key = private_key;
+            private_key.zeroize();
+            group_private_key?
+        }
+        None => private_key,
+    };
+
+    // Steps 2-3: ECDH with the file's ephemeral key and decrypt the DEK
+    let dek = unwrap_dek_in_window(
+        &private_key,
+        &input.ephemeral_public_key,
+        &input.dek_nonce,
+        &input.encrypted_dek,
+        &source_window,
+    );
+    private_key.zeroize();
+    let mut dek = dek?;
+
+    log(&format!("[re_encrypt_dek] DEK decrypted, size: {} bytes", dek.len()));
+
+    // Steps 4-6: wrap the DEK under a new ephemeral key for the target recipient
+    let wrapped = wrap_dek_for_public_key_with_rng(rng, &dek, &input.target_public_key, target_window);
+    dek.zeroize();
+    wrapped
+}
+
+// --- Shared DEK wrapping helpers ---
+
+/// An existing `file_dek` row (bytes), identified by its file.
+#[derive(Deserialize)]
+pub struct FileDekInput {
+    pub file_id: String,
+    pub encrypted_dek: Vec<u8>,
+    pub dek_nonce: Vec<u8>,
+    pub ephemeral_public_key: Vec<u8>,
+    #[serde(default)]
+    pub not_before: Option<u64>,
+    #[serde(default)]
+    pub not_after: Option<u64>,
+}
+
+impl FileDekInput {
+    /// The row's grant window.
+    pub fn window(&self) -> GrantWindow {
+        GrantWindow::new(self.not_before, self.not_after)
+    }
+
+    /// Unwraps the row's DEK with the recipient's private key.
+    pub fn unwrap_dek(&self, private_key: &[u8]) -> Result<Vec<u8>, String> {
+        unwrap_dek_in_window(
+            private_key,
+            &self.ephemeral_public_key,
+            &self.dek_nonce,
+            &self.encrypted_dek,
+            &self.window(),
+        )
+    }
+}
+
+/// A replacement `file_dek` row for a specific file, ready for storage.
+#[derive(Serialize)]
+pub struct FileDekEntry {
+    pub file_id: String,
+    pub user_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<String>,
+    pub encrypted_dek_hex: String,
+    pub dek_nonce_hex: String,
+    pub ephemeral_public_key_hex: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<u64>,
+}
+
+/// A DEK wrapped for one recipient's X25519 public key.
+pub struct WrappedDek {
+    pub encrypted_dek: Vec<u8>,
+    pub dek_nonce: Vec<u8>,
+    pub ephemeral_public_key: Vec<u8>,
+    /// The grant window bound into the wrapping
+    pub window: GrantWindow,
+}
+
+impl WrappedDek {
+    /// Converts the wrapping into the hex-encoded `file_dek` row shape.
+    pub fn into_entry(self, recipient: &RecipientInput) -> EncryptedDekEntry {
+        EncryptedDekEntry {
+            user_id: recipient.user_id.clone(),
+            group_id: recipient.group_id.clone(),
+            encrypted_dek_hex: bytes_to_hex(&self.encrypted_dek),
+            dek_nonce_hex: bytes_to_hex(&self.dek_nonce),
+            ephemeral_public_key_hex: bytes_to_hex(&self.ephemeral_public_key),
+            not_before: self.window.not_before,
+            not_after: self.window.not_after,
+        }
+    }
+
+    /// Converts the wrapping into a `file_dek` row for `file_id`.
+    pub fn into_file_entry(self, file_id: &str, recipient: &RecipientInput) -> FileDekEntry {
+        let entry = self.into_entry(recipient);
+        FileDekEntry {
+            file_id: file_id.to_string(),
+            user_id: entry.user_id,
+            group_id: entry.group_id,
+            encrypted_dek_hex: entry.encrypted_dek_hex,
+            dek_nonce_hex: entry.dek_nonce_hex,
+            ephemeral_public_key_hex: entry.ephemeral_public_key_hex,
+            not_before: entry.not_before,
+            not_after: entry.not_after,
+        }
     }
+}
+
+/// Wraps a DEK for a recipient: fresh ephemeral X25519 key pair, ECDH with the
+/// recipient's public key, then AES-256-GCM under the shared secret.
+pub fn wrap_dek_for_public_key(dek: &[u8], recipient_public_key: &[u8]) -> Result<WrappedDek, String> {
+    wrap_dek_for_public_key_in_window(dek, recipient_public_key, GrantWindow::default())
+}
+
+/// Wraps a DEK for a recipient with `window` bound as AAD.
+pub fn wrap_dek_for_public_key_in_window(
+    dek: &[u8],
+    recipient_public_key: &[u8],
+    window: GrantWindow,
+) -> Result<WrappedDek, String> {
+    wrap_dek_for_public_key_with_rng(&mut OsRng, dek, recipient_public_key, window)
+}
+
+/// [`wrap_dek_for_public_key_in_window`] with the ephemeral key and nonce drawn from `rng`.
+pub fn wrap_dek_for_public_key_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    dek: &[u8],
+    recipient_public_key: &[u8],
+    window: GrantWindow,
+) -> Result<WrappedDek, String> {
+    let ephemeral_private_key = StaticSecret::random_from_rng(&mut *rng).to_bytes();
+    let dek_nonce = generate_nonce_with_rng(rng);
+    wrap_dek_with_ephemeral_key(dek, recipient_public_key, ephemeral_private_key, &dek_nonce, window)
+}
+
+/// Wraps a DEK with a given ephemeral private key and nonce.
+///
+/// The deterministic core of [`wrap_dek_for_public_key`]; callers must never
+/// reuse an ephemeral key.
+pub fn wrap_dek_with_ephemeral_key(
+    dek: &[u8],
+    recipient_public_key: &[u8],
+    ephemeral_private_key: [u8; 32],
+    dek_nonce: &[u8],
+    window: GrantWindow,
+) -> Result<WrappedDek, String> {
+    window.validate()?;
+    let mut wrapped = seal_for_public_key(dek, recipient_public_key, ephemeral_private_key, dek_nonce, &window.aad())?;
+    wrapped.window = window;
+    Ok(wrapped)
+}
+
+/// Wraps a 32-byte key that is not a file DEK (a group or application key)
+/// for a recipient's public key, binding `aad`.
+///
+/// `aad` must name what the key is, so the wrapping cannot be passed off as a
+/// `file_dek` row or another table's wrapping.
+pub fn wrap_key_for_public_key(key: &[u8], recipient_public_key: &[u8], aad: &[u8]) -> Result<WrappedDek, String> {
+    wrap_key_for_public_key_with_rng(&mut OsRng, key, recipient_public_key, aad)
+}
 
-    let private_key_bytes = key_result.private_key();
-    if private_key_bytes.len() != 32 {
-        return err_result(format!("Invalid private key length: {}", private_key_bytes.len()));
+/// [`wrap_key_for_public_key`] with the ephemeral key and nonce drawn from `rng`.
+pub fn wrap_key_for_public_key_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    key: &[u8],
+    recipient_public_key: &[u8],
+    aad: &[u8],
+) -> Result<WrappedDek, String> {
+    let ephemeral_private_key = StaticSecret::random_from_rng(&mut *rng).to_bytes();
+    let nonce = generate_nonce_with_rng(rng);
+    seal_for_public_key(key, recipient_public_key, ephemeral_private_key, &nonce, aad)
+}
+
+fn seal_for_public_key(
+    key: &[u8],
+    recipient_public_key: &[u8],
+    ephemeral_private_key: [u8; 32],
+    nonce: &[u8],
+    aad: &[u8],
+) -> Result<WrappedDek, String> {
+    let pk_array: [u8; 32] = recipient_public_key
+        .try_into()
+        .map_err(|_| format!("Public key must be 32 bytes, got {}", recipient_public_key.len()))?;
+    let recipient_public = PublicKey::from(pk_array);
+    if nonce.len() != 12 {
+        return Err(format!("DEK nonce must be 12 bytes, got {}", nonce.len()));
     }
 
-    // Step 2: ECDH to derive the shared secret used to encrypt this DEK
-    if input.ephemeral_public_key.len() != 32 {
-        return err_result(format!("Invalid ephemeral public key length: {}", input.ephemeral_public_key.len()));
+    let ephemeral_secret = StaticSecret::from(ephemeral_private_key);
+    let ephemeral_public = PublicKey::from(&ephemeral_secret);
+    let shared_secret = ephemeral_secret.diffie_hellman(&recipient_public);
+
+    let nonce = Nonce::from_slice(nonce);
+    let shared_key = GenericArray::from_slice(shared_secret.as_bytes());
+    let cipher = Aes256Gcm::new(shared_key);
+
+    let encrypted_dek = cipher
+        .encrypt(nonce, Payload { msg: key, aad })
+        .map_err(|e| format!("DEK encryption failed: {}", e))?;
+
+    Ok(WrappedDek {
+        encrypted_dek,
+        dek_nonce: nonce.to_vec(),
+        ephemeral_public_key: ephemeral_public.as_bytes().to_vec(),
+        window: GrantWindow::default(),
+    })
+}
+
+/// Parses an X25519 public key, rejecting non-canonical encodings.
+///
+/// X25519 ignores the top bit and reduces the coordinate mod 2^255 - 19, so
+/// several encodings give the same shared secret. Keys generated here are
+/// always canonical; accepting only those means any change to a stored
+/// ephemeral key makes the unwrap fail.
+pub fn canonical_public_key(bytes: &[u8], name: &str) -> Result<PublicKey, String> {
+    let array: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| format!("{} must be 32 bytes, got {}", name, bytes.len()))?;
+    let non_canonical = array[31] & 0x80 != 0
+        || (array[31] == 0x7f && array[1..31].iter().all(|&b| b == 0xff) && array[0] >= 0xed);
+    if non_canonical {
+        return Err(format!("{} is not a canonical X25519 key", name));
     }
+    Ok(PublicKey::from(array))
+}
 
-    let pk_array: [u8; 32] = private_key_bytes.as_slice().try_into().unwrap();
-    let eph_array: [u8; 32] = input.ephemeral_public_key.as_slice().try_into().unwrap();
+/// Unwraps a DEK with the recipient's X25519 private key.
+///
+/// Returns the 32-byte DEK.
+pub fn unwrap_dek_with_private_key(
+    private_key: &[u8],
+    ephemeral_public_key: &[u8],
+    dek_nonce: &[u8],
+    encrypted_dek: &[u8],
+) -> Result<Vec<u8>, String> {
+    unwrap_dek_in_window(private_key, ephemeral_public_key, dek_nonce, encrypted_dek, &GrantWindow::default())
+}
 
-    let sharer_private = StaticSecret::from(pk_array);
-    let file_ephemeral_public = PublicKey::from(eph_array);
-    let shared_secret = sharer_private.diffie_hellman(&file_ephemeral_public);
+/// Unwraps a DEK whose wrapping binds `window` as AAD.
+///
+/// Only authenticates the window; callers check it against their clock.
+pub fn unwrap_dek_in_window(
+    private_key: &[u8],
+    ephemeral_public_key: &[u8],
+    dek_nonce: &[u8],
+    encrypted_dek: &[u8],
+    window: &GrantWindow,
+) -> Result<Vec<u8>, String> {
+    unwrap_key_with_private_key(private_key, ephemeral_public_key, dek_nonce, encrypted_dek, &window.aad())
+}
 
-    // Step 3: Decrypt the DEK
-    if input.dek_nonce.len() != 12 {
-        return err_result(format!("Invalid DEK nonce length: {}", input.dek_nonce.len()));
+/// Unwraps a key wrapped by [`wrap_key_for_public_key`] with the same `aad`.
+///
+/// Returns the 32-byte key.
+pub fn unwrap_key_with_private_key(
+    private_key: &[u8],
+    ephemeral_public_key: &[u8],
+    dek_nonce: &[u8],
+    encrypted_dek: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, String> {
+    let private_key_array: [u8; 32] = private_key
+        .try_into()
+        .map_err(|_| format!("Private key must be 32 bytes, got {}", private_key.len()))?;
+    let ephemeral_public = canonical_public_key(ephemeral_public_key, "Ephemeral public key")?;
+    if dek_nonce.len() != 12 {
+        return Err(format!("DEK nonce must be 12 bytes, got {}", dek_nonce.len()));
     }
 
+    let private_key = StaticSecret::from(private_key_array);
+    let shared_secret = private_key.diffie_hellman(&ephemeral_public);
+
     let shared_key = GenericArray::from_slice(shared_secret.as_bytes());
     let dek_cipher = Aes256Gcm::new(shared_key);
-    let dek_nonce_ga = Nonce::from_slice(&input.dek_nonce);
 
-    let dek = match dek_cipher.decrypt(dek_nonce_ga, input.encrypted_dek.as_slice()) {
-        Ok(d) => d,
-        Err(_) => return err_result("Failed to decrypt DEK. Data may be corrupted.".to_string()),
-    };
+    let dek = dek_cipher
+        .decrypt(Nonce::from_slice(dek_nonce), Payload { msg: encrypted_dek, aad })
+        .map_err(|_| "DEK decryption failed. Invalid private key or corrupted data.".to_string())?;
 
-    log(&format!("[re_encrypt_dek] DEK decrypted, size: {} bytes", dek.len()));
+    if dek.len() != 32 {
+        return Err(format!("Decrypted DEK must be 32 bytes, got {}", dek.len()));
+    }
+
+    Ok(dek)
+}
+
+/// Generates a fresh random 32-byte DEK.
+pub fn generate_dek() -> [u8; 32] {
+    generate_dek_with_rng(&mut OsRng)
+}
+
+/// Generates a 32-byte DEK from `rng`.
+pub fn generate_dek_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> [u8; 32] {
+    StaticSecret::random_from_rng(rng).to_bytes()
+}
+
+/// Encrypts file bytes under a DEK with a fresh nonce.
+///
+/// Returns `(ciphertext, nonce)`.
+pub fn encrypt_with_dek(dek: &[u8], data: &[u8]) -> Result<(Vec<u8>, Vec<u8>), String> {
+    encrypt_with_dek_with_rng(&mut OsRng, dek, data)
+}
 
-    // Step 4: Generate new ephemeral key pair for the target recipient
-    if input.target_public_key.len() != 32 {
-        return err_result(format!("Invalid target public key length: {}", input.target_public_key.len()));
+/// [`encrypt_with_dek`] with the nonce drawn from `rng`.
+pub fn encrypt_with_dek_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    dek: &[u8],
+    data: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), String> {
+    if dek.len() != 32 {
+        return Err(format!("DEK must be 32 bytes, got {}", dek.len()));
     }
+    let file_nonce = generate_nonce_with_rng(rng);
+    let file_cipher = Aes256Gcm::new(GenericArray::from_slice(dek));
 
-    let target_pk_array: [u8; 32] = input.target_public_key.as_slice().try_into().unwrap();
-    let target_public = PublicKey::from(target_pk_array);
+    let ciphertext = file_cipher
+        .encrypt(&file_nonce, data)
+        .map_err(|e| format!("File encryption failed: {}", e))?;
 
-    let new_ephemeral_secret = StaticSecret::random_from_rng(OsRng);
-    let new_ephemeral_public = PublicKey::from(&new_ephemeral_secret);
+    Ok((ciphertext, file_nonce.to_vec()))
+}
 
-    // Step 5: ECDH with target's public key
-    let new_shared_secret = new_ephemeral_secret.diffie_hellman(&target_public);
+/// Wraps a key under a symmetric wrapping key (AES-256-GCM), binding `aad`.
+///
+/// Returns `(encrypted_key, nonce)`.
+pub fn wrap_key_with_key(wrapping_key: &[u8], key: &[u8], aad: &[u8]) -> Result<(Vec<u8>, Vec<u8>), String> {
+    wrap_key_with_key_with_rng(&mut OsRng, wrapping_key, key, aad)
+}
 
-    // Step 6: Encrypt the DEK for the target recipient
-    let new_shared_key = GenericArray::from_slice(new_shared_secret.as_bytes());
-    let new_dek_cipher = Aes256Gcm::new(new_shared_key);
-    let new_dek_nonce = generate_nonce();
-    let new_dek_nonce_ga = Nonce::from_slice(new_dek_nonce.as_slice());
+/// [`wrap_key_with_key`] with the nonce drawn from `rng`.
+pub fn wrap_key_with_key_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    wrapping_key: &[u8],
+    key: &[u8],
+    aad: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), String> {
+    if wrapping_key.len() != 32 {
+        return Err(format!("Wrapping key must be 32 bytes, got {}", wrapping_key.len()));
+    }
+    let nonce = generate_nonce_with_rng(rng);
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(wrapping_key));
 
-    let new_encrypted_dek = match new_dek_cipher.encrypt(new_dek_nonce_ga, dek.as_ref()) {
-        Ok(ct) => ct,
-        Err(_) => return err_result("Failed to encrypt DEK for target recipient.".to_string()),
-    };
+    let encrypted_key = cipher
+        .encrypt(&nonce, Payload { msg: key, aad })
+        .map_err(|e| format!("Key wrapping failed: {}", e))?;
 
-    log("[re_encrypt_dek] DEK re-encrypted for new recipient successfully");
+    Ok((encrypted_key, nonce.to_vec()))
+}
 
-    let result = ReShareDekResult {
-        success: true,
-        encrypted_dek_hex: bytes_to_hex(&new_encrypted_dek),
-        dek_nonce_hex: bytes_to_hex(new_dek_nonce.as_slice()),
-        ephemeral_public_key_hex: bytes_to_hex(new_ephemeral_public.as_bytes()),
-        error_message: String::new(),
-    };
+/// Unwraps a key wrapped by [`wrap_key_with_key`] and checks it is 32 bytes.
+pub fn unwrap_key_with_key(
+    wrapping_key: &[u8],
+    encrypted_key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, String> {
+    if wrapping_key.len() != 32 {
+        return Err(format!("Wrapping key must be 32 bytes, got {}", wrapping_key.len()));
+    }
+    if nonce.len() != 12 {
+        return Err(format!("Nonce must be 12 bytes, got {}", nonce.len()));
+    }
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(wrapping_key));
 
-    serde_wasm_bindgen::to_value(&result).unwrap()
+    let key = cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: encrypted_key, aad })
+        .map_err(|_| "Key unwrapping failed. Invalid key or corrupted data.".to_string())?;
+
+    if key.len() != 32 {
+        return Err(format!("Unwrapped key must be 32 bytes, got {}", key.len()));
+    }
+
+    Ok(key)
 }
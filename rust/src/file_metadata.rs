@@ -0,0 +1,194 @@
+//! Encrypted file names and metadata.
+//!
+//! The original name, MIME type, size, category and tags are serialised to
+//! JSON and encrypted under the file DEK into a small separate ciphertext, so
+//! `file_metadata` rows and storage paths carry nothing readable. Storage gets
+//! a random opaque object name instead of `${file.name}.enc`.
+//!
+//! The metadata ciphertext uses its own nonce and binds a label as AAD, so it
+//! can never be mistaken for (or swapped with) the file ciphertext.
+
+use wasm_bindgen::prelude::*;
+use aes_gcm::{
+    Aes256Gcm, Nonce,
+    aead::{Aead, KeyInit, OsRng, Payload, generic_array::GenericArray, rand_core::{CryptoRng, RngCore}},
+};
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroize;
+
+pub use crate::{bytes_to_hex, generate_nonce_with_rng, log};
+use crate::encrypt_file::unwrap_dek_in_window;
+use crate::grant_window::GrantWindow;
+use crate::group_key::{GroupKeyEntryInput, unwrap_group_private_key};
+use crate::masterkey_decryptor::unlock_private_key;
+
+const METADATA_LABEL: &[u8] = b"filechain-file-metadata-v1";
+
+/// The metadata record encrypted alongside a file.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct FileMetadata {
+    pub name: String,
+    #[serde(default)]
+    pub mime_type: String,
+    /// Plaintext size in bytes; filled in by the encoder
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Encrypts a metadata record under the file DEK.
+///
+/// Returns `(encrypted_metadata, metadata_nonce)`.
+pub fn encrypt_metadata(dek: &[u8], metadata: &FileMetadata) -> Result<(Vec<u8>, Vec<u8>), String> {
+    encrypt_metadata_with_rng(&mut OsRng, dek, metadata)
+}
+
+/// [`encrypt_metadata`] with the nonce drawn from `rng`.
+pub fn encrypt_metadata_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    dek: &[u8],
+    metadata: &FileMetadata,
+) -> Result<(Vec<u8>, Vec<u8>), String> {
+    if dek.len() != 32 {
+        return Err(format!("DEK must be 32 bytes, got {}", dek.len()));
+    }
+    let mut plaintext = serde_json::to_vec(metadata).map_err(|e| format!("Failed to encode metadata: {}", e))?;
+
+    let nonce = generate_nonce_with_rng(rng);
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(dek));
+    let encrypted = cipher.encrypt(&nonce, Payload { msg: &plaintext, aad: METADATA_LABEL });
+    plaintext.zeroize();
+
+    let encrypted = encrypted.map_err(|e| format!("Metadata encryption failed: {}", e))?;
+    Ok((encrypted, nonce.to_vec()))
+}
+
+/// Decrypts a metadata record encrypted by [`encrypt_metadata`].
+pub fn decrypt_metadata(dek: &[u8], encrypted_metadata: &[u8], metadata_nonce: &[u8]) -> Result<FileMetadata, String> {
+    if dek.len() != 32 {
+        return Err(format!("DEK must be 32 bytes, got {}", dek.len()));
+    }
+    if metadata_nonce.len() != 12 {
+        return Err(format!("Metadata nonce must be 12 bytes, got {}", metadata_nonce.len()));
+    }
+
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(dek));
+    let mut plaintext = cipher
+        .decrypt(Nonce::from_slice(metadata_nonce), Payload { msg: encrypted_metadata, aad: METADATA_LABEL })
+        .map_err(|_| "Metadata decryption failed. Invalid DEK or corrupted data.".to_string())?;
+
+    let metadata = serde_json::from_slice(&plaintext).map_err(|e| format!("Failed to decode metadata: {}", e));
+    plaintext.zeroize();
+    metadata
+}
+
+/// Generates a random opaque storage object name (32 hex characters).
+pub fn generate_object_name() -> String {
+    generate_object_name_with_rng(&mut OsRng)
+}
+
+/// [`generate_object_name`] drawing from `rng`.
+pub fn generate_object_name_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> String {
+    let mut bytes = [0u8; 16];
+    rng.fill_bytes(&mut bytes);
+    bytes_to_hex(&bytes)
+}
+
+/// Input for decrypting a file's metadata record.
+///
+/// Has the same key fields as `DecryptionContext`, with the metadata
+/// ciphertext in place of the file.
+#[derive(Deserialize, Zeroize)]
+#[zeroize(drop)]
+pub struct MetadataDecryptionContext {
+    pub password: String,
+    pub pk_salt: String,
+    pub encrypted_private_key: Vec<u8>,
+    pub pk_nonce: Vec<u8>,
+    pub ephemeral_public_key: Vec<u8>,
+    pub encrypted_dek: Vec<u8>,
+    pub dek_nonce: Vec<u8>,
+    pub encrypted_metadata: Vec<u8>,
+    pub metadata_nonce: Vec<u8>,
+    /// The user's group key entry, when the DEK was wrapped to a group
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub group_entry: Option<GroupKeyEntryInput>,
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub not_before: Option<u64>,
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub not_after: Option<u64>,
+    /// The caller's clock (Unix ms); required for time-limited entries
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub now_ms: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct FileMetadataResult {
+    pub success: bool,
+    pub metadata: Option<FileMetadata>,
+    pub error_message: String,
+}
+
+/// Decrypts the metadata record produced by `encrypt_file_multi_with_options`.
+///
+/// # Arguments
+/// * `context_js` - A JsValue representing a `MetadataDecryptionContext`
+///
+/// # Returns
+/// A JsValue containing `FileMetadataResult`
+#[wasm_bindgen]
+pub fn decrypt_file_metadata(context_js: JsValue) -> JsValue {
+    log("[decrypt_file_metadata] Decrypting file metadata...");
+
+    let result = match serde_wasm_bindgen::from_value::<MetadataDecryptionContext>(context_js)
+        .map_err(|e| format!("Failed to parse input: {}", e))
+        .and_then(|context| open_metadata(&context))
+    {
+        Ok(metadata) => FileMetadataResult { success: true, metadata: Some(metadata), error_message: String::new() },
+        Err(e) => {
+            log(&format!("[decrypt_file_metadata] Failed: {}", e));
+            FileMetadataResult { success: false, metadata: None, error_message: e }
+        }
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+fn open_metadata(context: &MetadataDecryptionContext) -> Result<FileMetadata, String> {
+    let window = GrantWindow::new(context.not_before, context.not_after);
+    window.check(context.now_ms.map(|ms| ms as u64))?;
+
+    let mut private_key = unlock_private_key(
+        &context.password,
+        &context.pk_salt,
+        &context.encrypted_private_key,
+        &context.pk_nonce,
+    ).map_err(|e| format!("Failed to decrypt private key: {}", e))?;
+
+    if let Some(group_entry) = &context.group_entry {
+        let group_private_key = unwrap_group_private_key(&private_key, group_entry);
+        private_key.zeroize();
+        private_key = group_private_key?;
+    }
+
+    let dek = unwrap_dek_in_window(
+        &private_key,
+        &context.ephemeral_public_key,
+        &context.dek_nonce,
+        &context.encrypted_dek,
+        &window,
+    );
+    private_key.zeroize();
+    let mut dek = dek?;
+
+    let metadata = decrypt_metadata(&dek, &context.encrypted_metadata, &context.metadata_nonce);
+    dek.zeroize();
+    metadata
+}
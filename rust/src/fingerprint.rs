@@ -0,0 +1,321 @@
+//! Public key fingerprints, safety numbers and key pinning.
+//!
+//! The fingerprint of a user's key is
+//!   SHA-256("filechain-fingerprint-v1" || len(user_id) || user_id || public_key)
+//! truncated to 30 bytes, so the same key presented for a different user gives a
+//! different fingerprint. It is rendered either as 6 groups of 5 digits or as
+//! hex groups of 4 characters.
+//!
+//! A safety number combines the fingerprints of two users in a fixed order, so
+//! both sides see the same 60 digits and can compare them out of band.
+
+use wasm_bindgen::prelude::*;
+use sha2::{Sha256, Digest};
+use serde::{Serialize, Deserialize};
+
+pub use crate::{bytes_to_hex, log};
+
+const FINGERPRINT_LABEL: &[u8] = b"filechain-fingerprint-v1";
+const FINGERPRINT_LEN: usize = 30;
+const PIN_STORE_VERSION: u32 = 1;
+
+/// Computes the truncated fingerprint of a user's X25519 public key.
+pub fn fingerprint_bytes(user_id: &str, public_key: &[u8]) -> Result<[u8; FINGERPRINT_LEN], String> {
+    if public_key.len() != 32 {
+        return Err(format!("Public key must be 32 bytes, got {}", public_key.len()));
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(FINGERPRINT_LABEL);
+    hasher.update((user_id.len() as u32).to_be_bytes());
+    hasher.update(user_id.as_bytes());
+    hasher.update(public_key);
+    let digest = hasher.finalize();
+
+    let mut fingerprint = [0u8; FINGERPRINT_LEN];
+    fingerprint.copy_from_slice(&digest[..FINGERPRINT_LEN]);
+    Ok(fingerprint)
+}
+
+/// Renders a fingerprint as 5-digit groups (each group from 5 bytes, mod 100000).
+pub fn fingerprint_digits(fingerprint: &[u8]) -> Vec<String> {
+    fingerprint
+        .chunks(5)
+        .map(|chunk| {
+            let value = chunk.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+            format!("{:05}", value % 100_000)
+        })
+        .collect()
+}
+
+/// Renders a fingerprint as uppercase hex groups of 4 characters.
+pub fn fingerprint_hex_groups(fingerprint: &[u8]) -> Vec<String> {
+    fingerprint
+        .chunks(2)
+        .map(|chunk| bytes_to_hex(chunk).to_uppercase())
+        .collect()
+}
+
+#[derive(Serialize)]
+pub struct FingerprintResult {
+    pub success: bool,
+    pub fingerprint_hex: String,
+    /// e.g. "12345 67890 ..." (6 groups)
+    pub digits: String,
+    /// e.g. "A1B2 C3D4 ..." (15 groups)
+    pub hex_groups: String,
+    pub error_message: String,
+}
+
+/// Computes the fingerprint of a user's public key for display.
+///
+/// # Arguments
+/// * `user_id` - The UUID of the key owner
+/// * `public_key` - The owner's X25519 public key (32 bytes)
+///
+/// # Returns
+/// A JsValue containing `FingerprintResult`
+#[wasm_bindgen]
+pub fn public_key_fingerprint(user_id: &str, public_key: &[u8]) -> JsValue {
+    let result = match fingerprint_bytes(user_id, public_key) {
+        Ok(fingerprint) => FingerprintResult {
+            success: true,
+            fingerprint_hex: bytes_to_hex(&fingerprint),
+            digits: fingerprint_digits(&fingerprint).join(" "),
+            hex_groups: fingerprint_hex_groups(&fingerprint).join(" "),
+            error_message: String::new(),
+        },
+        Err(e) => FingerprintResult {
+            success: false,
+            fingerprint_hex: String::new(),
+            digits: String::new(),
+            hex_groups: String::new(),
+            error_message: e,
+        },
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// Computes the 60-digit safety number between two users.
+///
+/// The two 30-digit halves are ordered numerically, so the result does not
+/// depend on which side computes it.
+pub fn compute_safety_number(
+    user_a_id: &str,
+    public_key_a: &[u8],
+    user_b_id: &str,
+    public_key_b: &[u8],
+) -> Result<Vec<String>, String> {
+    let a = fingerprint_digits(&fingerprint_bytes(user_a_id, public_key_a)?);
+    let b = fingerprint_digits(&fingerprint_bytes(user_b_id, public_key_b)?);
+
+    let (first, second) = if a.concat() <= b.concat() { (a, b) } else { (b, a) };
+    Ok(first.into_iter().chain(second).collect())
+}
+
+#[derive(Serialize)]
+pub struct SafetyNumberResult {
+    pub success: bool,
+    /// 12 groups of 5 digits separated by spaces
+    pub safety_number: String,
+    pub error_message: String,
+}
+
+/// Computes the pairwise safety number between the current user and a colleague.
+#[wasm_bindgen]
+pub fn safety_number(
+    user_a_id: &str,
+    public_key_a: &[u8],
+    user_b_id: &str,
+    public_key_b: &[u8],
+) -> JsValue {
+    let result = match compute_safety_number(user_a_id, public_key_a, user_b_id, public_key_b) {
+        Ok(groups) => SafetyNumberResult {
+            success: true,
+            safety_number: groups.join(" "),
+            error_message: String::new(),
+        },
+        Err(e) => SafetyNumberResult {
+            success: false,
+            safety_number: String::new(),
+            error_message: e,
+        },
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+// --- Key pinning ---
+
+/// One pinned key, as persisted by the client (e.g. in IndexedDB).
+#[derive(Serialize, Deserialize, Clone)]
+pub struct KeyPin {
+    pub user_id: String,
+    pub public_key_hex: String,
+    pub fingerprint_hex: String,
+    /// Unix timestamp in milliseconds when the key was first pinned
+    pub pinned_at_ms: u64,
+    /// Whether the user compared the safety number out of band
+    pub verified: bool,
+}
+
+/// The client-side pin store. Serialised as-is to JSON by the frontend.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct KeyPinStore {
+    pub version: u32,
+    pub pins: Vec<KeyPin>,
+}
+
+impl Default for KeyPinStore {
+    fn default() -> Self {
+        KeyPinStore { version: PIN_STORE_VERSION, pins: vec![] }
+    }
+}
+
+/// Outcome of checking a key against the pin store.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PinStatus {
+    /// No pin existed; the key was pinned on first use
+    New,
+    /// The key matches the pin
+    Match,
+    /// The key differs from the pin; the store was not changed
+    Changed,
+}
+
+impl KeyPinStore {
+    /// Checks `public_key` against the pin for `user_id`, pinning it if unknown.
+    pub fn check(&mut self, user_id: &str, public_key: &[u8], timestamp_ms: u64) -> Result<PinStatus, String> {
+        let fingerprint_hex = bytes_to_hex(&fingerprint_bytes(user_id, public_key)?);
+
+        match self.pins.iter().find(|pin| pin.user_id == user_id) {
+            Some(pin) if pin.fingerprint_hex == fingerprint_hex => Ok(PinStatus::Match),
+            Some(_) => Ok(PinStatus::Changed),
+            None => {
+                self.pin(user_id, public_key, timestamp_ms, false)?;
+                Ok(PinStatus::New)
+            }
+        }
+    }
+
+    /// Pins `public_key` for `user_id`, replacing any existing pin.
+    pub fn pin(&mut self, user_id: &str, public_key: &[u8], timestamp_ms: u64, verified: bool) -> Result<(), String> {
+        let fingerprint_hex = bytes_to_hex(&fingerprint_bytes(user_id, public_key)?);
+
+        self.pins.retain(|pin| pin.user_id != user_id);
+        self.pins.push(KeyPin {
+            user_id: user_id.to_string(),
+            public_key_hex: bytes_to_hex(public_key),
+            fingerprint_hex,
+            pinned_at_ms: timestamp_ms,
+            verified,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+pub struct PinCheckResult {
+    pub success: bool,
+    pub status: Option<PinStatus>,
+    /// The fingerprint currently pinned for the user, if any
+    pub pinned_fingerprint_hex: String,
+    pub current_fingerprint_hex: String,
+    /// The store to persist (unchanged unless the key was new or re-pinned)
+    pub store: Option<KeyPinStore>,
+    pub error_message: String,
+}
+
+impl PinCheckResult {
+    fn error(msg: String) -> Self {
+        PinCheckResult {
+            success: false,
+            status: None,
+            pinned_fingerprint_hex: String::new(),
+            current_fingerprint_hex: String::new(),
+            store: None,
+            error_message: msg,
+        }
+    }
+}
+
+fn parse_store(store_js: JsValue) -> Result<KeyPinStore, String> {
+    if store_js.is_undefined() || store_js.is_null() {
+        return Ok(KeyPinStore::default());
+    }
+    let store: KeyPinStore = serde_wasm_bindgen::from_value(store_js)
+        .map_err(|e| format!("Failed to parse pin store: {}", e))?;
+    if store.version != PIN_STORE_VERSION {
+        return Err(format!("Unsupported pin store version {}", store.version));
+    }
+    Ok(store)
+}
+
+/// Checks a recipient's key against the pin store before encrypting to it.
+///
+/// # Arguments
+/// * `store_js` - The persisted `KeyPinStore`, or null/undefined for an empty store
+/// * `user_id` - The UUID of the key owner
+/// * `public_key` - The key the server returned (32 bytes)
+/// * `timestamp_ms` - Unix timestamp in milliseconds (from Date.now())
+///
+/// # Returns
+/// A JsValue containing `PinCheckResult`. A `changed` status means the server
+/// returned a different key than before and the user should re-verify.
+#[wasm_bindgen]
+pub fn check_pinned_key(store_js: JsValue, user_id: &str, public_key: &[u8], timestamp_ms: f64) -> JsValue {
+    let result = parse_store(store_js)
+        .and_then(|mut store| {
+            let pinned_fingerprint_hex = store
+                .pins
+                .iter()
+                .find(|pin| pin.user_id == user_id)
+                .map(|pin| pin.fingerprint_hex.clone())
+                .unwrap_or_default();
+            let status = store.check(user_id, public_key, timestamp_ms as u64)?;
+            if status == PinStatus::Changed {
+                log(&format!("[check_pinned_key] Key for user {} changed since it was pinned", user_id));
+            }
+            Ok(PinCheckResult {
+                success: true,
+                status: Some(status),
+                pinned_fingerprint_hex,
+                current_fingerprint_hex: bytes_to_hex(&fingerprint_bytes(user_id, public_key)?),
+                store: Some(store),
+                error_message: String::new(),
+            })
+        })
+        .unwrap_or_else(PinCheckResult::error);
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// Pins a key explicitly, e.g. after the user compared safety numbers or
+/// accepted a changed key.
+#[wasm_bindgen]
+pub fn pin_public_key(
+    store_js: JsValue,
+    user_id: &str,
+    public_key: &[u8],
+    timestamp_ms: f64,
+    verified: bool,
+) -> JsValue {
+    let result = parse_store(store_js)
+        .and_then(|mut store| {
+            store.pin(user_id, public_key, timestamp_ms as u64, verified)?;
+            let fingerprint_hex = bytes_to_hex(&fingerprint_bytes(user_id, public_key)?);
+            Ok(PinCheckResult {
+                success: true,
+                status: Some(PinStatus::Match),
+                pinned_fingerprint_hex: fingerprint_hex.clone(),
+                current_fingerprint_hex: fingerprint_hex,
+                store: Some(store),
+                error_message: String::new(),
+            })
+        })
+        .unwrap_or_else(PinCheckResult::error);
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
@@ -0,0 +1,99 @@
+//! Time-limited access grants on DEK entries.
+//!
+//! A DEK entry may carry `not_before` / `not_after` (Unix milliseconds). The
+//! window is bound into the AES-GCM AAD of the DEK wrapping, so editing it in
+//! the database breaks the unwrap. Clients refuse to unwrap outside the window
+//! against a caller-supplied clock; the server only has to delete expired rows.
+//!
+//! An unbounded window encodes to empty AAD, so entries created before grants
+//! existed unwrap exactly as before. Only `decrypt_file` and
+//! `re_encrypt_dek_for_recipient` accept a clock; other operations that unwrap
+//! a single caller-supplied entry treat windowed entries as unreadable.
+//!
+//! A re-shared entry's window must lie inside the sharer's, so a time-limited
+//! grant cannot be laundered into a longer one.
+
+use serde::{Serialize, Deserialize};
+
+const GRANT_LABEL: &[u8] = b"filechain-grant-v1";
+
+/// The validity window of a DEK entry.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct GrantWindow {
+    pub not_before: Option<u64>,
+    pub not_after: Option<u64>,
+}
+
+impl GrantWindow {
+    pub fn new(not_before: Option<u64>, not_after: Option<u64>) -> Self {
+        GrantWindow { not_before, not_after }
+    }
+
+    pub fn is_unbounded(&self) -> bool {
+        self.not_before.is_none() && self.not_after.is_none()
+    }
+
+    /// The AAD bound into the DEK wrapping (empty when unbounded).
+    pub fn aad(&self) -> Vec<u8> {
+        if self.is_unbounded() {
+            return vec![];
+        }
+        let mut out = GRANT_LABEL.to_vec();
+        for bound in [self.not_before, self.not_after] {
+            match bound {
+                Some(ms) => {
+                    out.push(1);
+                    out.extend_from_slice(&ms.to_be_bytes());
+                }
+                None => out.push(0),
+            }
+        }
+        out
+    }
+
+    /// Checks the window is well formed.
+    pub fn validate(&self) -> Result<(), String> {
+        match (self.not_before, self.not_after) {
+            (Some(start), Some(end)) if start > end => {
+                Err("Grant not_before must not be later than not_after".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the window lies inside `outer`, so a re-share cannot outlast the
+    /// sharer's own grant. An open bound is only allowed where `outer` is open too.
+    pub fn check_within(&self, outer: &GrantWindow) -> Result<(), String> {
+        if let Some(start) = outer.not_before
+            && self.not_before.is_none_or(|not_before| not_before < start)
+        {
+            return Err("Grant must not start before the sharer's own grant".to_string());
+        }
+        if let Some(end) = outer.not_after
+            && self.not_after.is_none_or(|not_after| not_after > end)
+        {
+            return Err("Grant must not end after the sharer's own grant".to_string());
+        }
+        Ok(())
+    }
+
+    /// Checks `now_ms` falls inside the window. A bounded window requires a clock.
+    pub fn check(&self, now_ms: Option<u64>) -> Result<(), String> {
+        if self.is_unbounded() {
+            return Ok(());
+        }
+        let now = now_ms.ok_or_else(|| "A current time is required for a time-limited grant".to_string())?;
+
+        if let Some(start) = self.not_before
+            && now < start
+        {
+            return Err("Access grant is not valid yet".to_string());
+        }
+        if let Some(end) = self.not_after
+            && now > end
+        {
+            return Err("Access grant has expired".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,293 @@
+//! Group recipients.
+//!
+//! A group (e.g. the team assigned to an application) has its own X25519 key
+//! pair. The group private key is wrapped to each member's public key, and
+//! files are encrypted to the group public key like any other recipient
+//! (`RecipientInput` with a `group_id`). Member wrappings bind the group ID as
+//! AAD, so they only open as that group's key.
+//!
+//!   member private key → group private key → file DEK → file
+//!
+//! Adding a member only adds one group key wrapping. Removing a member
+//! replaces the group key pair, since the removed member may have kept the old
+//! private key: every `file_dek` row addressed to the group is re-wrapped to
+//! the new public key, one row per file. File contents are not re-encrypted
+//! (use `rotate_file_dek` for that).
+
+use wasm_bindgen::prelude::*;
+use aes_gcm::aead::{OsRng, rand_core::{CryptoRng, RngCore}};
+use x25519_dalek::{PublicKey, StaticSecret};
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroize;
+
+pub use crate::{bytes_to_hex, log};
+use crate::encrypt_file::{
+    FileDekEntry, FileDekInput, RecipientInput, unwrap_key_with_private_key, wrap_dek_for_public_key_with_rng,
+    wrap_key_for_public_key_with_rng,
+};
+use crate::masterkey_decryptor::PrivateKeyCredentials;
+
+/// A member's wrapping of the group private key (bytes), as stored.
+#[derive(Deserialize)]
+pub struct GroupKeyEntryInput {
+    /// The group the key belongs to, bound into the wrapping
+    pub group_id: String,
+    pub encrypted_group_key: Vec<u8>,
+    pub group_key_nonce: Vec<u8>,
+    pub ephemeral_public_key: Vec<u8>,
+}
+
+/// A member's wrapping of the group private key, ready for storage.
+#[derive(Serialize)]
+pub struct GroupKeyEntry {
+    pub user_id: String,
+    pub encrypted_group_key_hex: String,
+    pub group_key_nonce_hex: String,
+    pub ephemeral_public_key_hex: String,
+}
+
+/// Result of a group key operation.
+#[derive(Serialize)]
+pub struct GroupKeyResult {
+    pub success: bool,
+    pub group_public_key_hex: String,
+    pub member_entries: Vec<GroupKeyEntry>,
+    pub dek_entries: Vec<FileDekEntry>,
+    pub error_message: String,
+}
+
+impl GroupKeyResult {
+    fn error(msg: String) -> Self {
+        GroupKeyResult {
+            success: false,
+            group_public_key_hex: String::new(),
+            member_entries: vec![],
+            dek_entries: vec![],
+            error_message: msg,
+        }
+    }
+}
+
+/// AAD binding a member's wrapping of the group private key to its group.
+fn member_key_aad(group_id: &str) -> Vec<u8> {
+    format!("filechain-group-key-v1|{}", group_id).into_bytes()
+}
+
+fn wrap_for_members<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    group_private_key: &[u8],
+    group_id: &str,
+    members: &[RecipientInput],
+) -> Result<Vec<GroupKeyEntry>, String> {
+    if group_id.is_empty() {
+        return Err("Group ID cannot be empty".to_string());
+    }
+    let aad = member_key_aad(group_id);
+    members
+        .iter()
+        .map(|member| {
+            if member.user_id.is_empty() {
+                return Err("Group members must have a user_id".to_string());
+            }
+            let wrapped = wrap_key_for_public_key_with_rng(rng, group_private_key, &member.public_key, &aad)
+                .map_err(|e| format!("Failed to wrap group key for user {}: {}", member.user_id, e))?;
+            Ok(GroupKeyEntry {
+                user_id: member.user_id.clone(),
+                encrypted_group_key_hex: bytes_to_hex(&wrapped.encrypted_dek),
+                group_key_nonce_hex: bytes_to_hex(&wrapped.dek_nonce),
+                ephemeral_public_key_hex: bytes_to_hex(&wrapped.ephemeral_public_key),
+            })
+        })
+        .collect()
+}
+
+/// Unwraps a group private key with a member's private key.
+pub fn unwrap_group_private_key(member_private_key: &[u8], entry: &GroupKeyEntryInput) -> Result<Vec<u8>, String> {
+    unwrap_key_with_private_key(
+        member_private_key,
+        &entry.ephemeral_public_key,
+        &entry.group_key_nonce,
+        &entry.encrypted_group_key,
+        &member_key_aad(&entry.group_id),
+    )
+    .map_err(|e| format!("Failed to unwrap group key: {}", e))
+}
+
+/// Creates a new group key pair and wraps the private half for the initial members.
+///
+/// # Arguments
+/// * `group_id` - The group the key belongs to
+/// * `members_js` - A JsValue representing an array of { user_id: string, public_key: number[] }
+///
+/// # Returns
+/// A JsValue containing `GroupKeyResult`. `group_public_key_hex` is what files
+/// are encrypted to.
+#[wasm_bindgen]
+pub fn create_group_key(group_id: &str, members_js: JsValue) -> JsValue {
+    log("[create_group_key] Creating group key pair...");
+
+    let result = match serde_wasm_bindgen::from_value::<Vec<RecipientInput>>(members_js) {
+        Ok(members) if members.is_empty() => GroupKeyResult::error("At least one member is required".to_string()),
+        Ok(members) => new_group_with_rng(&mut OsRng, group_id, &members).unwrap_or_else(GroupKeyResult::error),
+        Err(e) => GroupKeyResult::error(format!("Failed to parse members: {}", e)),
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// The core of [`create_group_key`], with the key pair and wrappings drawn from `rng`.
+pub fn new_group_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    group_id: &str,
+    members: &[RecipientInput],
+) -> Result<GroupKeyResult, String> {
+    let secret = StaticSecret::random_from_rng(&mut *rng);
+    let public = PublicKey::from(&secret);
+    let mut private_key = secret.to_bytes();
+
+    let member_entries = wrap_for_members(rng, &private_key, group_id, members);
+    private_key.zeroize();
+
+    Ok(GroupKeyResult {
+        success: true,
+        group_public_key_hex: bytes_to_hex(public.as_bytes()),
+        member_entries: member_entries?,
+        dek_entries: vec![],
+        error_message: String::new(),
+    })
+}
+
+/// Input for adding members to a group.
+#[derive(Deserialize)]
+pub struct AddGroupMembersInput {
+    pub credentials: PrivateKeyCredentials,
+    /// The caller's own group key entry
+    pub caller_entry: GroupKeyEntryInput,
+    pub new_members: Vec<RecipientInput>,
+}
+
+/// Wraps the group private key for new members using the caller's copy.
+#[wasm_bindgen]
+pub fn add_group_members(input_js: JsValue) -> JsValue {
+    log("[add_group_members] Adding group members...");
+
+    let result = match serde_wasm_bindgen::from_value::<AddGroupMembersInput>(input_js) {
+        Ok(input) => add_members_with_rng(&mut OsRng, &input).unwrap_or_else(GroupKeyResult::error),
+        Err(e) => GroupKeyResult::error(format!("Failed to parse input: {}", e)),
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// The core of [`add_group_members`], with the new wrappings drawn from `rng`.
+pub fn add_members_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    input: &AddGroupMembersInput,
+) -> Result<GroupKeyResult, String> {
+    let mut private_key = input.credentials.unlock()?;
+    let group_private_key = unwrap_group_private_key(&private_key, &input.caller_entry);
+    private_key.zeroize();
+    let mut group_private_key = group_private_key?;
+
+    let group_array: [u8; 32] = group_private_key.as_slice().try_into().unwrap();
+    let group_public = PublicKey::from(&StaticSecret::from(group_array));
+    let member_entries = wrap_for_members(rng, &group_private_key, &input.caller_entry.group_id, &input.new_members);
+    group_private_key.zeroize();
+
+    Ok(GroupKeyResult {
+        success: true,
+        group_public_key_hex: bytes_to_hex(group_public.as_bytes()),
+        member_entries: member_entries?,
+        dek_entries: vec![],
+        error_message: String::new(),
+    })
+}
+
+/// Input for removing members from a group.
+#[derive(Deserialize)]
+pub struct RemoveGroupMembersInput {
+    pub credentials: PrivateKeyCredentials,
+    pub group_id: String,
+    /// The caller's own group key entry
+    pub caller_entry: GroupKeyEntryInput,
+    /// Members who stay in the group; everyone else is removed
+    pub remaining_members: Vec<RecipientInput>,
+    /// Every `file_dek` row addressed to the group
+    pub dek_entries: Vec<FileDekInput>,
+}
+
+/// Replaces the group key pair, leaving out removed members.
+///
+/// # Returns
+/// A JsValue containing `GroupKeyResult` with the new group public key,
+/// replacement member entries and the group's `file_dek` rows re-wrapped to the
+/// new public key. File contents are not re-encrypted (use `rotate_file_dek`).
+#[wasm_bindgen]
+pub fn remove_group_members(input_js: JsValue) -> JsValue {
+    log("[remove_group_members] Rotating group key pair...");
+
+    let result = match serde_wasm_bindgen::from_value::<RemoveGroupMembersInput>(input_js) {
+        Ok(input) => remove_members_with_rng(&mut OsRng, &input).unwrap_or_else(GroupKeyResult::error),
+        Err(e) => GroupKeyResult::error(format!("Failed to parse input: {}", e)),
+    };
+
+    if !result.success {
+        log(&format!("[remove_group_members] Failed: {}", result.error_message));
+    }
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// The core of [`remove_group_members`], with the new key pair and wrappings drawn from `rng`.
+pub fn remove_members_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    input: &RemoveGroupMembersInput,
+) -> Result<GroupKeyResult, String> {
+    if input.remaining_members.is_empty() {
+        return Err("At least one remaining member is required".to_string());
+    }
+    if input.caller_entry.group_id != input.group_id {
+        return Err("Caller's group key entry belongs to another group".to_string());
+    }
+
+    let mut private_key = input.credentials.unlock()?;
+    let old_group_private_key = unwrap_group_private_key(&private_key, &input.caller_entry);
+    private_key.zeroize();
+    let mut old_group_private_key = old_group_private_key?;
+
+    let new_secret = StaticSecret::random_from_rng(&mut *rng);
+    let new_public = PublicKey::from(&new_secret);
+    let mut new_group_private_key = new_secret.to_bytes();
+
+    let recipient = RecipientInput {
+        user_id: String::new(),
+        group_id: Some(input.group_id.clone()),
+        public_key: new_public.as_bytes().to_vec(),
+        not_before: None,
+        not_after: None,
+    };
+    let dek_entries: Result<Vec<FileDekEntry>, String> = input
+        .dek_entries
+        .iter()
+        .map(|row| {
+            let mut dek = row
+                .unwrap_dek(&old_group_private_key)
+                .map_err(|e| format!("File {}: {}", row.file_id, e))?;
+            let wrapped = wrap_dek_for_public_key_with_rng(rng, &dek, &recipient.public_key, row.window());
+            dek.zeroize();
+            Ok(wrapped?.into_file_entry(&row.file_id, &recipient))
+        })
+        .collect();
+    old_group_private_key.zeroize();
+
+    let member_entries = wrap_for_members(rng, &new_group_private_key, &input.group_id, &input.remaining_members);
+    new_group_private_key.zeroize();
+
+    Ok(GroupKeyResult {
+        success: true,
+        group_public_key_hex: bytes_to_hex(new_public.as_bytes()),
+        member_entries: member_entries?,
+        dek_entries: dek_entries?,
+        error_message: String::new(),
+    })
+}
@@ -0,0 +1,126 @@
+//! Signatures made with a user's X25519 identity key.
+//!
+//! Users only have one key pair (the X25519 key stored in `user_profiles`), so
+//! statements are signed XEdDSA-style: the clamped X25519 scalar is used as an
+//! Ed25519 signing scalar, with its sign normalised so the matching Ed25519
+//! public key can be recovered from the Montgomery `public_key` alone. Anyone who
+//! knows a user's `public_key` can therefore verify what they signed.
+
+use wasm_bindgen::prelude::*;
+use curve25519_dalek::{
+    constants::ED25519_BASEPOINT_TABLE, montgomery::MontgomeryPoint,
+    scalar::{Scalar, clamp_integer},
+};
+use ed25519_dalek::{Signature, VerifyingKey, hazmat::{ExpandedSecretKey, raw_sign}};
+use sha2::{Sha512, Digest};
+
+pub use crate::{bytes_to_hex, hex_to_bytes, log};
+
+// Domain label for deriving the deterministic signing nonce prefix
+const HASH_PREFIX_LABEL: &[u8] = b"filechain-identity-signature-v1";
+
+/// Signs `message` with an X25519 private key (32 bytes).
+///
+/// Returns the 64-byte Ed25519 signature.
+pub fn sign_with_identity_key(private_key: &[u8], message: &[u8]) -> Result<[u8; 64], String> {
+    let private_key_array: [u8; 32] = private_key
+        .try_into()
+        .map_err(|_| format!("Private key must be 32 bytes, got {}", private_key.len()))?;
+
+    let mut scalar = Scalar::from_bytes_mod_order(clamp_integer(private_key_array));
+    let mut public_point = &scalar * ED25519_BASEPOINT_TABLE;
+
+    // Normalise to the Edwards point with sign bit 0, which is what
+    // `MontgomeryPoint::to_edwards(0)` recovers on the verifying side
+    if public_point.compress().as_bytes()[31] & 0x80 != 0 {
+        scalar = -scalar;
+        public_point = -public_point;
+    }
+
+    let mut hasher = Sha512::new();
+    hasher.update(HASH_PREFIX_LABEL);
+    hasher.update(private_key_array);
+    let digest = hasher.finalize();
+    let mut hash_prefix = [0u8; 32];
+    hash_prefix.copy_from_slice(&digest[32..]);
+
+    let expanded = ExpandedSecretKey { scalar, hash_prefix };
+    let verifying_key = VerifyingKey::from(public_point);
+    let signature = raw_sign::<Sha512>(&expanded, message, &verifying_key);
+
+    Ok(signature.to_bytes())
+}
+
+/// Verifies a signature made by [`sign_with_identity_key`] against the
+/// signer's X25519 public key (32 bytes).
+pub fn verify_identity_signature(public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<(), String> {
+    let public_key_array: [u8; 32] = public_key
+        .try_into()
+        .map_err(|_| format!("Public key must be 32 bytes, got {}", public_key.len()))?;
+    let signature_array: [u8; 64] = signature
+        .try_into()
+        .map_err(|_| format!("Signature must be 64 bytes, got {}", signature.len()))?;
+
+    let edwards = MontgomeryPoint(public_key_array)
+        .to_edwards(0)
+        .ok_or_else(|| "Public key is not a valid curve point".to_string())?;
+    let verifying_key = VerifyingKey::from(edwards);
+    let signature = Signature::from_bytes(&signature_array);
+
+    verifying_key
+        .verify_strict(message, &signature)
+        .map_err(|_| "Signature verification failed".to_string())
+}
+
+#[wasm_bindgen]
+pub struct SignatureVerificationResult {
+    valid: bool,
+    error_message: String,
+}
+
+#[wasm_bindgen]
+impl SignatureVerificationResult {
+    #[wasm_bindgen(getter)]
+    pub fn valid(&self) -> bool {
+        self.valid
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn error_message(&self) -> String {
+        self.error_message.clone()
+    }
+}
+
+impl From<Result<(), String>> for SignatureVerificationResult {
+    fn from(result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => SignatureVerificationResult { valid: true, error_message: String::new() },
+            Err(e) => SignatureVerificationResult { valid: false, error_message: e },
+        }
+    }
+}
+
+/// Verifies a hex-encoded identity signature over a UTF-8 message.
+///
+/// # Arguments
+/// * `public_key_hex` - The signer's X25519 public key (hex)
+/// * `message` - The exact message that was signed
+/// * `signature_hex` - The 64-byte signature (hex)
+#[wasm_bindgen]
+pub fn verify_identity_signature_hex(
+    public_key_hex: &str,
+    message: &str,
+    signature_hex: &str,
+) -> SignatureVerificationResult {
+    log("[identity_signature] Verifying signature...");
+
+    let result = hex_to_bytes(public_key_hex)
+        .and_then(|pk| hex_to_bytes(signature_hex).map(|sig| (pk, sig)))
+        .and_then(|(pk, sig)| verify_identity_signature(&pk, message.as_bytes(), &sig));
+
+    if let Err(e) = &result {
+        log(&format!("[identity_signature] Verification failed: {}", e));
+    }
+
+    result.into()
+}
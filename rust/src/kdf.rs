@@ -0,0 +1,202 @@
+//! Argon2id cost parameters and on-device calibration.
+//!
+//! Every KEK is derived with `KdfParams::default()` (64 MB, 3 passes): the
+//! key wrapping and unlocking exports take no parameters, and stored keys do
+//! not record any. `calibrate_kdf` times Argon2id on the current device and
+//! reports parameters that take about a target time, never weaker than the
+//! defaults. It measures what a device can afford, e.g. before raising the
+//! defaults; it does not change how keys are wrapped. Rust callers can derive
+//! with other parameters through `derive_key_encryption_key`, but a key
+//! wrapped that way only unlocks with the same parameters.
+
+use wasm_bindgen::prelude::*;
+use argon2::{Argon2, Algorithm, Version, Params};
+
+pub use crate::log;
+use crate::clock::{Clock, SystemClock};
+
+const CALIBRATION_INPUT: &str = "filechain-kdf-calibration";
+const CALIBRATION_SALT: &str = "filechain-kdf-calibration-salt";
+const MAX_REFINEMENTS: usize = 4;
+
+/// Argon2id cost parameters.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KdfParams {
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of passes over the memory
+    pub iterations: u32,
+    /// Degree of parallelism (lanes)
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams {
+            memory_kib: 65536, // 64 MB memory cost
+            iterations: 3,     // 3 iterations
+            parallelism: 1,    // 1 degree of parallelism
+        }
+    }
+}
+
+impl KdfParams {
+    /// An Argon2id instance producing 32-byte keys with these parameters.
+    pub fn argon2(&self) -> Result<Argon2<'static>, String> {
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(32))
+            .map_err(|e| format!("Invalid Argon2 parameters: {}", e))?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+}
+
+/// Suggested parameters and how long one derivation with them took.
+#[derive(Clone, Copy, Debug)]
+pub struct KdfCalibration {
+    pub params: KdfParams,
+    pub measured_ms: u64,
+}
+
+#[wasm_bindgen]
+pub struct KdfCalibrationResult {
+    success: bool,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    measured_ms: u64,
+    error_message: String,
+}
+
+#[wasm_bindgen]
+impl KdfCalibrationResult {
+    #[wasm_bindgen(getter)]
+    pub fn success(&self) -> bool {
+        self.success
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn memory_kib(&self) -> u32 {
+        self.memory_kib
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn parallelism(&self) -> u32 {
+        self.parallelism
+    }
+
+    /// How long one derivation with the suggested parameters took (ms)
+    #[wasm_bindgen(getter)]
+    pub fn measured_ms(&self) -> f64 {
+        self.measured_ms as f64
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn error_message(&self) -> String {
+        self.error_message.clone()
+    }
+}
+
+/// Suggests Argon2id parameters that take about `target_ms` to derive a key
+/// on this device. Keys are still wrapped with the defaults.
+///
+/// Memory grows first (up to `max_memory_mib`), then the number of passes.
+/// The result is never weaker than the defaults, so on slow devices it can
+/// take longer than the target. Runs Argon2 up to five times; call it from a
+/// worker, not during an unlock.
+///
+/// # Arguments
+/// * `target_ms` - The desired unlock time in milliseconds
+/// * `max_memory_mib` - The most memory the derivation may use, in MiB
+///
+/// # Returns
+/// KdfCalibrationResult with the suggested parameters and the measured time
+#[wasm_bindgen]
+pub fn calibrate_kdf(target_ms: f64, max_memory_mib: f64) -> KdfCalibrationResult {
+    log(&format!("[calibrate_kdf] Calibrating for {} ms...", target_ms));
+
+    if !target_ms.is_finite() || target_ms < 1.0 || !max_memory_mib.is_finite() || max_memory_mib < 0.0 {
+        return KdfCalibrationResult {
+            success: false,
+            memory_kib: 0,
+            iterations: 0,
+            parallelism: 0,
+            measured_ms: 0,
+            error_message: "Target time and memory limit must be positive numbers".to_string(),
+        };
+    }
+    let max_memory_kib = (max_memory_mib * 1024.0).min(u32::MAX as f64) as u32;
+
+    match calibrate_kdf_with_clock(&SystemClock, target_ms as u64, max_memory_kib) {
+        Ok(calibration) => {
+            log(&format!(
+                "[calibrate_kdf] {} KiB, {} passes: {} ms",
+                calibration.params.memory_kib, calibration.params.iterations, calibration.measured_ms
+            ));
+            KdfCalibrationResult {
+                success: true,
+                memory_kib: calibration.params.memory_kib,
+                iterations: calibration.params.iterations,
+                parallelism: calibration.params.parallelism,
+                measured_ms: calibration.measured_ms,
+                error_message: String::new(),
+            }
+        }
+        Err(e) => {
+            log(&format!("[calibrate_kdf] Failed: {}", e));
+            KdfCalibrationResult {
+                success: false,
+                memory_kib: 0,
+                iterations: 0,
+                parallelism: 0,
+                measured_ms: 0,
+                error_message: e,
+            }
+        }
+    }
+}
+
+/// The core of [`calibrate_kdf`], timed with `clock`.
+pub fn calibrate_kdf_with_clock(clock: &impl Clock, target_ms: u64, max_memory_kib: u32) -> Result<KdfCalibration, String> {
+    let floor = KdfParams::default();
+    let max_memory_kib = max_memory_kib.max(floor.memory_kib);
+
+    // Cost is only roughly proportional to memory x passes, so rescale from
+    // each measurement until it lands within 10% of the target
+    let mut params = floor;
+    let mut measured_ms = time_derivation(clock, &params)?;
+    for _ in 0..MAX_REFINEMENTS {
+        if measured_ms.abs_diff(target_ms) * 10 <= target_ms {
+            break;
+        }
+        let next = scale_params(&params, target_ms as f64 / measured_ms.max(1) as f64, max_memory_kib);
+        if next == params {
+            break;
+        }
+        params = next;
+        measured_ms = time_derivation(clock, &params)?;
+    }
+
+    Ok(KdfCalibration { params, measured_ms })
+}
+
+/// Scales the cost of `params` by `factor`: memory first (in whole MiB, up to
+/// `max_memory_kib`), then passes. Never goes below the defaults.
+pub fn scale_params(params: &KdfParams, factor: f64, max_memory_kib: u32) -> KdfParams {
+    let floor = KdfParams::default();
+    let memory_kib = ((params.memory_kib as f64 * factor).min(max_memory_kib as f64) as u32 / 1024 * 1024)
+        .max(floor.memory_kib);
+    let remaining = factor * params.memory_kib as f64 / memory_kib as f64;
+    let iterations = ((params.iterations as f64 * remaining).round() as u32).max(floor.iterations);
+
+    KdfParams { memory_kib, iterations, parallelism: params.parallelism }
+}
+
+fn time_derivation(clock: &impl Clock, params: &KdfParams) -> Result<u64, String> {
+    let start = clock.now_ms();
+    crate::derive_key_encryption_key(CALIBRATION_INPUT, CALIBRATION_SALT, params)?;
+    Ok(clock.now_ms().saturating_sub(start))
+}
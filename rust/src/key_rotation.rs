@@ -0,0 +1,258 @@
+//! Identity key rotation.
+//!
+//! Unlike `re_encrypt_private_key`, which only changes the password wrapping,
+//! rotation replaces the X25519 key pair itself:
+//! 1. Unlock the old private key with the user's password
+//! 2. Generate a new X25519 key pair, wrapped under the same password and a fresh recovery key
+//! 3. Unwrap every `file_dek` entry with the old key and re-wrap it to the new public key
+//! 4. Sign a "key superseded" statement with both the old and the new key
+
+use wasm_bindgen::prelude::*;
+use aes_gcm::aead::{OsRng, rand_core::{CryptoRng, RngCore}};
+use x25519_dalek::{PublicKey, StaticSecret};
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroize;
+
+pub use crate::{bytes_to_hex, hex_to_bytes, log};
+use crate::encrypt_file::{FileDekInput, wrap_dek_for_public_key_with_rng};
+use crate::identity_signature::{sign_with_identity_key, verify_identity_signature, SignatureVerificationResult};
+use crate::masterkey_decryptor::unlock_private_key;
+use crate::masterkey_generator::wrap_private_key_with_rng;
+
+/// Input for rotating a user's identity key pair.
+/// Passed from JavaScript via serde.
+#[derive(Deserialize, Zeroize)]
+#[zeroize(drop)]
+pub struct KeyRotationInput {
+    /// The user's password (used to unlock the old key and wrap the new one)
+    pub password: String,
+    /// The user's current salt for key derivation
+    pub pk_salt: String,
+    /// The user's current encrypted private key (bytes)
+    pub encrypted_private_key: Vec<u8>,
+    /// The user's current pk_nonce (bytes)
+    pub pk_nonce: Vec<u8>,
+    /// Unix timestamp in milliseconds recorded in the superseded statement
+    pub timestamp_ms: f64,
+    /// Every `file_dek` row owned by the user
+    #[zeroize(skip)]
+    pub dek_entries: Vec<FileDekInput>,
+}
+
+/// A replacement `file_dek` row wrapped to the new public key.
+/// Time-limited rows keep their grant window.
+#[derive(Serialize)]
+pub struct RotatedDekEntry {
+    pub file_id: String,
+    pub encrypted_dek_hex: String,
+    pub dek_nonce_hex: String,
+    pub ephemeral_public_key_hex: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<u64>,
+}
+
+/// Statement linking an old identity key to its replacement.
+///
+/// Signed by the old key (the holder authorised the change) and by the new key
+/// (the holder possesses it).
+#[derive(Serialize, Deserialize, Clone)]
+pub struct KeySupersededStatement {
+    pub old_public_key_hex: String,
+    pub new_public_key_hex: String,
+    pub timestamp_ms: u64,
+    pub old_key_signature_hex: String,
+    pub new_key_signature_hex: String,
+}
+
+/// Result of an identity key rotation.
+#[derive(Serialize)]
+pub struct KeyRotationResult {
+    pub success: bool,
+    pub public_key_hex: String,
+    pub encrypted_private_key_hex: String,
+    pub salt: String,
+    pub nonce_hex: String,
+    pub recovery_key_hex: String,
+    pub recovery_encrypted_private_key_hex: String,
+    pub recovery_salt: String,
+    pub recovery_nonce_hex: String,
+    pub dek_entries: Vec<RotatedDekEntry>,
+    pub superseded_statement: Option<KeySupersededStatement>,
+    pub error_message: String,
+}
+
+impl KeyRotationResult {
+    fn error(msg: String) -> Self {
+        KeyRotationResult {
+            success: false,
+            public_key_hex: String::new(),
+            encrypted_private_key_hex: String::new(),
+            salt: String::new(),
+            nonce_hex: String::new(),
+            recovery_key_hex: String::new(),
+            recovery_encrypted_private_key_hex: String::new(),
+            recovery_salt: String::new(),
+            recovery_nonce_hex: String::new(),
+            dek_entries: vec![],
+            superseded_statement: None,
+            error_message: msg,
+        }
+    }
+}
+
+/// Canonical bytes signed for a key superseded statement.
+pub fn superseded_statement_message(old_public_key_hex: &str, new_public_key_hex: &str, timestamp_ms: u64) -> String {
+    format!(
+        "filechain-key-superseded-v1\n{}\n{}\n{}",
+        old_public_key_hex, new_public_key_hex, timestamp_ms
+    )
+}
+
+/// Rotates the user's identity key pair and re-wraps every DEK they own.
+///
+/// # Arguments
+/// * `input_js` - A JsValue representing a `KeyRotationInput`
+///
+/// # Returns
+/// A JsValue containing `KeyRotationResult`. The rotation fails as a whole if
+/// any DEK cannot be unwrapped, so no file is left unreadable.
+#[wasm_bindgen]
+pub fn rotate_identity_key(input_js: JsValue) -> JsValue {
+    log("[rotate_identity_key] Starting identity key rotation...");
+
+    let result = match serde_wasm_bindgen::from_value::<KeyRotationInput>(input_js) {
+        Ok(input) => rotate(&input).unwrap_or_else(KeyRotationResult::error),
+        Err(e) => KeyRotationResult::error(format!("Failed to parse input: {}", e)),
+    };
+
+    if !result.success {
+        log(&format!("[rotate_identity_key] Rotation failed: {}", result.error_message));
+    }
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// The core of [`rotate_identity_key`] for a parsed input.
+pub fn rotate(input: &KeyRotationInput) -> Result<KeyRotationResult, String> {
+    rotate_with_rng(&mut OsRng, input)
+}
+
+/// [`rotate`] with the new key pair, recovery key, salts and nonces drawn from `rng`.
+pub fn rotate_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    input: &KeyRotationInput,
+) -> Result<KeyRotationResult, String> {
+    // Step 1: Unlock the old key
+    let mut old_private_key = unlock_private_key(
+        &input.password,
+        &input.pk_salt,
+        &input.encrypted_private_key,
+        &input.pk_nonce,
+    ).map_err(|e| format!("Failed to decrypt private key: {}", e))?;
+
+    let old_array: [u8; 32] = old_private_key.as_slice().try_into().unwrap();
+    let old_public = PublicKey::from(&StaticSecret::from(old_array));
+
+    // Step 2: Generate and wrap the new key pair
+    let new_secret = StaticSecret::random_from_rng(&mut *rng);
+    let new_public = PublicKey::from(&new_secret);
+    let mut new_private_key = new_secret.to_bytes();
+    log("[rotate_identity_key] Generated new X25519 key pair");
+
+    let password_wrapped = wrap_private_key_with_rng(rng, &input.password, &new_private_key)?;
+
+    let recovery_key_hex = bytes_to_hex(&StaticSecret::random_from_rng(&mut *rng).to_bytes());
+    let recovery_wrapped = wrap_private_key_with_rng(rng, &recovery_key_hex, &new_private_key)?;
+
+    // Step 3: Re-wrap every DEK to the new public key
+    let mut dek_entries = Vec::with_capacity(input.dek_entries.len());
+    for entry in &input.dek_entries {
+        let mut dek = entry.unwrap_dek(&old_private_key)
+            .map_err(|e| format!("Failed to unwrap DEK for file {}: {}", entry.file_id, e))?;
+
+        let wrapped = wrap_dek_for_public_key_with_rng(rng, &dek, new_public.as_bytes(), entry.window());
+        dek.zeroize();
+        let wrapped = wrapped.map_err(|e| format!("Failed to re-wrap DEK for file {}: {}", entry.file_id, e))?;
+
+        dek_entries.push(RotatedDekEntry {
+            file_id: entry.file_id.clone(),
+            encrypted_dek_hex: bytes_to_hex(&wrapped.encrypted_dek),
+            dek_nonce_hex: bytes_to_hex(&wrapped.dek_nonce),
+            ephemeral_public_key_hex: bytes_to_hex(&wrapped.ephemeral_public_key),
+            not_before: wrapped.window.not_before,
+            not_after: wrapped.window.not_after,
+        });
+    }
+    log(&format!("[rotate_identity_key] Re-wrapped {} DEK entries", dek_entries.len()));
+
+    // Step 4: Sign the superseded statement with both keys
+    let old_public_key_hex = bytes_to_hex(old_public.as_bytes());
+    let new_public_key_hex = bytes_to_hex(new_public.as_bytes());
+    let timestamp_ms = input.timestamp_ms as u64;
+    let message = superseded_statement_message(&old_public_key_hex, &new_public_key_hex, timestamp_ms);
+
+    let old_signature = sign_with_identity_key(&old_private_key, message.as_bytes());
+    let new_signature = sign_with_identity_key(&new_private_key, message.as_bytes());
+    old_private_key.zeroize();
+    new_private_key.zeroize();
+
+    let statement = KeySupersededStatement {
+        old_public_key_hex,
+        new_public_key_hex: new_public_key_hex.clone(),
+        timestamp_ms,
+        old_key_signature_hex: bytes_to_hex(&old_signature?),
+        new_key_signature_hex: bytes_to_hex(&new_signature?),
+    };
+
+    log("[rotate_identity_key] Rotation complete");
+
+    Ok(KeyRotationResult {
+        success: true,
+        public_key_hex: new_public_key_hex,
+        encrypted_private_key_hex: bytes_to_hex(&password_wrapped.encrypted_private_key),
+        salt: password_wrapped.salt,
+        nonce_hex: bytes_to_hex(&password_wrapped.nonce),
+        recovery_key_hex,
+        recovery_encrypted_private_key_hex: bytes_to_hex(&recovery_wrapped.encrypted_private_key),
+        recovery_salt: recovery_wrapped.salt,
+        recovery_nonce_hex: bytes_to_hex(&recovery_wrapped.nonce),
+        dek_entries,
+        superseded_statement: Some(statement),
+        error_message: String::new(),
+    })
+}
+
+/// Checks both signatures on a key superseded statement.
+pub fn verify_superseded_statement(statement: &KeySupersededStatement) -> Result<(), String> {
+    let message = superseded_statement_message(
+        &statement.old_public_key_hex,
+        &statement.new_public_key_hex,
+        statement.timestamp_ms,
+    );
+
+    let old_public_key = hex_to_bytes(&statement.old_public_key_hex)?;
+    let new_public_key = hex_to_bytes(&statement.new_public_key_hex)?;
+
+    verify_identity_signature(&old_public_key, message.as_bytes(), &hex_to_bytes(&statement.old_key_signature_hex)?)
+        .map_err(|e| format!("Old key signature: {}", e))?;
+    verify_identity_signature(&new_public_key, message.as_bytes(), &hex_to_bytes(&statement.new_key_signature_hex)?)
+        .map_err(|e| format!("New key signature: {}", e))?;
+
+    Ok(())
+}
+
+/// Verifies a key superseded statement produced by [`rotate_identity_key`].
+///
+/// # Arguments
+/// * `statement_js` - A JsValue representing a `KeySupersededStatement`
+#[wasm_bindgen]
+pub fn verify_key_superseded_statement(statement_js: JsValue) -> SignatureVerificationResult {
+    log("[verify_key_superseded_statement] Verifying statement...");
+
+    match serde_wasm_bindgen::from_value::<KeySupersededStatement>(statement_js) {
+        Ok(statement) => verify_superseded_statement(&statement).into(),
+        Err(e) => Err(format!("Failed to parse statement: {}", e)).into(),
+    }
+}
@@ -1,18 +1,47 @@
 use wasm_bindgen::prelude::*;
-use argon2::{Argon2, Algorithm, Version, Params};
 use aes_gcm::{
-    Aes256Gcm, aead::{AeadCore, OsRng, generic_array::GenericArray, consts::U12}
+    Aes256Gcm, aead::{AeadCore, OsRng, generic_array::GenericArray, consts::U12, rand_core::{CryptoRng, RngCore}}
 };
 use sha2::{Sha256, Digest};
 
+use crate::kdf::KdfParams;
+use crate::progress::{ByteCounter, PROGRESS_CHUNK_SIZE, Progress, ProgressError, Stage};
+
 pub mod masterkey_generator;
 pub mod masterkey_decryptor;
+// encrypt_file and block_signature open with `///` headers rather than `//!`
+#[allow(clippy::empty_line_after_doc_comments)]
 pub mod encrypt_file;
 pub mod decrypt_file;
+#[allow(clippy::empty_line_after_doc_comments)]
 pub mod block_signature;
+pub mod identity_signature;
+pub mod key_rotation;
+pub mod dek_rotation;
+pub mod application_key;
+pub mod group_key;
+pub mod fingerprint;
+pub mod recipient_manifest;
+pub mod share_package;
+pub mod share_link;
+pub mod pending_share;
+pub mod grant_window;
+pub mod crypto_shred;
+pub mod file_metadata;
+pub mod payload_frame;
+pub mod clock;
+pub mod kdf;
+pub mod file_hasher;
+pub mod merkle;
+pub mod storage_audit;
+pub mod range_decrypt;
+pub mod progress;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 // Nonce type alias for AES-256-GCM (12 bytes)
 pub type Nonce = GenericArray<u8, U12>;
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern "C" {
     pub fn alert(s: &str);
@@ -20,35 +49,49 @@ extern "C" {
     pub fn log(s: &str);
 }
 
+// Native builds (tests, benches, fuzzing) have no JS console to log to
+#[cfg(not(target_arch = "wasm32"))]
+pub fn alert(_s: &str) {}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn log(_s: &str) {}
+
 #[wasm_bindgen]
 pub fn greet() {
     alert("Hello, wasm-game-of-life!");
 }
 
 /// Derives a 32-byte key with pepper
+///
+/// Panics if Argon2 rejects the salt; use [`try_key_encryption_key`] for salts
+/// that did not come from `SaltString::generate`.
 pub fn get_key_encryption_key(input: &str, salt: &str) -> Vec<u8> {
+    try_key_encryption_key(input, salt).expect("Failed to hash password")
+}
+
+/// [`get_key_encryption_key`] for stored or user-supplied salts.
+///
+/// Fails instead of panicking when the salt is too short or too long.
+pub fn try_key_encryption_key(input: &str, salt: &str) -> Result<Vec<u8>, String> {
+    derive_key_encryption_key(input, salt, &KdfParams::default())
+}
+
+/// [`try_key_encryption_key`] with explicit Argon2id cost parameters.
+pub fn derive_key_encryption_key(input: &str, salt: &str, params: &KdfParams) -> Result<Vec<u8>, String> {
     let paminta = get_paminta();
  
     // combine input with pepper
     let mut input_with_pepper = input.as_bytes().to_vec();
     input_with_pepper.extend_from_slice(&paminta);
 
-    // argon2id config
-    let params = Params::new(
-        65536,  // 64 MB memory cost
-        3,      // 3 iterations
-        1,      // 1 degree of parallelism
-        Some(32) // 32 bytes output
-    ).unwrap();
-
-    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+    let argon2 = params.argon2()?;
 
     let mut derived_key = vec![0u8; 32];
     argon2
         .hash_password_into(&input_with_pepper, salt.as_bytes(), &mut derived_key)
-        .expect("Failed to hash password");
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
 
-    derived_key 
+    Ok(derived_key)
 }
 
 // - 4rD^grSXyRwJ~Wuc5vcHL5
@@ -71,34 +114,65 @@ pub fn hash_file(data: &[u8]) -> String {
     hash_hex
 }
 
+/// [`hash_file`] in `PROGRESS_CHUNK_SIZE` steps, reporting `Stage::Hash`
+/// progress and stopping if `progress` aborts.
+pub fn hash_file_with_progress(data: &[u8], progress: &mut dyn Progress) -> Result<String, ProgressError> {
+    let mut counter = ByteCounter::start(progress, Stage::Hash, data.len() as u64)?;
+    let mut hasher = Sha256::new();
+    for chunk in data.chunks(PROGRESS_CHUNK_SIZE) {
+        hasher.update(chunk);
+        counter.advance(progress, chunk.len() as u64)?;
+    }
+    Ok(bytes_to_hex(&hasher.finalize()))
+}
 
 pub fn bytes_to_hex(data: &[u8]) -> String {
     data.iter().map(|b| format!("{:02x}", b)).collect()
 }
 
+/// Appends a 4-byte big-endian length followed by `field`.
+///
+/// Used to build unambiguous byte encodings for signatures and AAD.
+pub fn push_length_prefixed(out: &mut Vec<u8>, field: &[u8]) {
+    out.extend_from_slice(&(field.len() as u32).to_be_bytes());
+    out.extend_from_slice(field);
+}
+
+/// Decodes a hex string (either case). Never panics on malformed input.
 pub fn hex_to_bytes(hex: &str) -> Result<Vec<u8>, String> {
-    if hex.len() % 2 != 0 {
+    if !hex.len().is_multiple_of(2) {
         return Err("Invalid hex string length".to_string());
     }
 
-    (0..hex.len())
-        .step_by(2)
-        .map(|i| {
-            u8::from_str_radix(&hex[i..i + 2], 16)
-                .map_err(|_| format!("Invalid hex character at position {}", i))
+    // Work on bytes: slicing the str could split a multi-byte character
+    hex.as_bytes()
+        .chunks_exact(2)
+        .enumerate()
+        .map(|(i, pair)| match (hex_digit(pair[0]), hex_digit(pair[1])) {
+            (Some(high), Some(low)) => Ok(high << 4 | low),
+            _ => Err(format!("Invalid hex character at position {}", i * 2)),
         })
         .collect()
 }
 
+fn hex_digit(c: u8) -> Option<u8> {
+    (c as char).to_digit(16).map(|d| d as u8)
+}
+
 /// Generates a cryptographically secure 12-byte nonce
 pub fn generate_nonce() -> Nonce {
-    Aes256Gcm::generate_nonce(&mut OsRng)
+    generate_nonce_with_rng(&mut OsRng)
+}
+
+/// Generates a 12-byte nonce from `rng`
+pub fn generate_nonce_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Nonce {
+    Aes256Gcm::generate_nonce(rng)
 }
 
 #[wasm_bindgen]
 pub fn master_key_bytes_to_hex(input: &str, salt: &str) -> String {
-    let key = get_key_encryption_key(input, salt);
-    bytes_to_hex(&key)
+    // Empty on an unusable salt rather than a panic across the wasm boundary
+    try_key_encryption_key(input, salt).map(|key| bytes_to_hex(&key)).unwrap_or_default()
 }
 
 #[wasm_bindgen]
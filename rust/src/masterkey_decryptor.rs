@@ -1,10 +1,12 @@
 use wasm_bindgen::prelude::*;
 use aes_gcm::{
-    Aes256Gcm, Nonce, aead::{Aead, KeyInit, OsRng, generic_array::GenericArray}
+    Aes256Gcm, Nonce, aead::{Aead, KeyInit, OsRng, generic_array::GenericArray, rand_core::{CryptoRng, RngCore}}
 };
 use argon2::password_hash::SaltString;
+use serde::Deserialize;
+use zeroize::Zeroize;
 
-pub use crate::{get_key_encryption_key, generate_nonce, bytes_to_hex, log};
+pub use crate::{get_key_encryption_key, try_key_encryption_key, generate_nonce, generate_nonce_with_rng, bytes_to_hex, log};
 
 #[wasm_bindgen]
 pub struct DecryptedPrivateKey {
@@ -63,7 +65,17 @@ pub fn decrypt_private_key(
 
     // Derive the encryption key from password and salt (includes paminta internally)
     log("Deriving encryption key from password...");
-    let encryption_key = get_key_encryption_key(password, salt);
+    let encryption_key = match try_key_encryption_key(password, salt) {
+        Ok(key) => key,
+        Err(e) => {
+            log(&format!("Invalid salt: {}", e));
+            return DecryptedPrivateKey {
+                success: false,
+                private_key: vec![],
+                error_message: e,
+            };
+        }
+    };
 
     // Create the cipher
     let key = GenericArray::from_slice(&encryption_key);
@@ -100,6 +112,18 @@ pub fn re_encrypt_private_key(
     encrypted_key: &[u8],
     old_nonce: &[u8],
     new_password: &str,
+) -> ReEncryptedPrivateKey {
+    re_encrypt_private_key_with_rng(&mut OsRng, old_password, old_salt, encrypted_key, old_nonce, new_password)
+}
+
+/// [`re_encrypt_private_key`] with the new salt and nonce drawn from `rng`.
+pub fn re_encrypt_private_key_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    old_password: &str,
+    old_salt: &str,
+    encrypted_key: &[u8],
+    old_nonce: &[u8],
+    new_password: &str,
 ) -> ReEncryptedPrivateKey {
     if old_nonce.len() != 12 {
         return ReEncryptedPrivateKey {
@@ -121,7 +145,18 @@ pub fn re_encrypt_private_key(
         };
     }
 
-    let old_encryption_key = get_key_encryption_key(old_password, old_salt);
+    let old_encryption_key = match try_key_encryption_key(old_password, old_salt) {
+        Ok(key) => key,
+        Err(e) => {
+            return ReEncryptedPrivateKey {
+                success: false,
+                encrypted_private_key: vec![],
+                salt: String::new(),
+                nonce: vec![],
+                error_message: e,
+            };
+        }
+    };
     let old_key = GenericArray::from_slice(&old_encryption_key);
     let old_cipher = Aes256Gcm::new(old_key);
     let old_nonce = Nonce::from_slice(old_nonce);
@@ -139,11 +174,11 @@ pub fn re_encrypt_private_key(
         }
     };
 
-    let new_salt = SaltString::generate(&mut OsRng);
+    let new_salt = SaltString::generate(&mut *rng);
     let new_encryption_key = get_key_encryption_key(new_password, new_salt.as_str());
     let new_key = GenericArray::from_slice(&new_encryption_key);
     let new_cipher = Aes256Gcm::new(new_key);
-    let new_nonce = generate_nonce();
+    let new_nonce = generate_nonce_with_rng(rng);
 
     let encrypted_private_key = match new_cipher.encrypt(&new_nonce, decrypted_private_key.as_ref()) {
         Ok(ciphertext) => ciphertext,
@@ -268,6 +303,25 @@ pub fn recover_and_reencrypt_private_key(
     recovery_encrypted_key: &[u8],
     recovery_nonce_bytes: &[u8],
     new_password: &str,
+) -> RecoveredPrivateKey {
+    recover_and_reencrypt_private_key_with_rng(
+        &mut OsRng,
+        recovery_key,
+        recovery_salt_str,
+        recovery_encrypted_key,
+        recovery_nonce_bytes,
+        new_password,
+    )
+}
+
+/// [`recover_and_reencrypt_private_key`] with every new salt, key and nonce drawn from `rng`.
+pub fn recover_and_reencrypt_private_key_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    recovery_key: &str,
+    recovery_salt_str: &str,
+    recovery_encrypted_key: &[u8],
+    recovery_nonce_bytes: &[u8],
+    new_password: &str,
 ) -> RecoveredPrivateKey {
     log("Starting recovery key decryption...");
 
@@ -291,7 +345,10 @@ pub fn recover_and_reencrypt_private_key(
     }
 
     // Step 1: Decrypt private key using recovery key
-    let recovery_encryption_key = get_key_encryption_key(recovery_key, recovery_salt_str);
+    let recovery_encryption_key = match try_key_encryption_key(recovery_key, recovery_salt_str) {
+        Ok(key) => key,
+        Err(e) => return err(e),
+    };
     let rk = GenericArray::from_slice(&recovery_encryption_key);
     let recovery_cipher = Aes256Gcm::new(rk);
     let rn = Nonce::from_slice(recovery_nonce_bytes);
@@ -308,11 +365,11 @@ pub fn recover_and_reencrypt_private_key(
     };
 
     // Step 2: Re-encrypt with new password
-    let new_salt = SaltString::generate(&mut OsRng);
+    let new_salt = SaltString::generate(&mut *rng);
     let new_encryption_key = get_key_encryption_key(new_password, new_salt.as_str());
     let new_key = GenericArray::from_slice(&new_encryption_key);
     let new_cipher = Aes256Gcm::new(new_key);
-    let new_nonce = generate_nonce();
+    let new_nonce = generate_nonce_with_rng(rng);
 
     let new_encrypted_pk = match new_cipher.encrypt(&new_nonce, private_key_bytes.as_ref()) {
         Ok(ct) => ct,
@@ -321,14 +378,14 @@ pub fn recover_and_reencrypt_private_key(
 
     // Step 3: Generate a fresh recovery key and re-encrypt
     use x25519_dalek::StaticSecret;
-    let fresh_recovery_secret = StaticSecret::random_from_rng(OsRng);
+    let fresh_recovery_secret = StaticSecret::random_from_rng(&mut *rng);
     let fresh_recovery_hex = bytes_to_hex(&fresh_recovery_secret.to_bytes());
 
-    let fresh_recovery_salt = SaltString::generate(&mut OsRng);
+    let fresh_recovery_salt = SaltString::generate(&mut *rng);
     let fresh_recovery_kek = get_key_encryption_key(&fresh_recovery_hex, fresh_recovery_salt.as_str());
     let fresh_rk = GenericArray::from_slice(&fresh_recovery_kek);
     let fresh_cipher = Aes256Gcm::new(fresh_rk);
-    let fresh_nonce = generate_nonce();
+    let fresh_nonce = generate_nonce_with_rng(rng);
 
     let fresh_recovery_ct = match fresh_cipher.encrypt(&fresh_nonce, private_key_bytes.as_ref()) {
         Ok(ct) => ct,
@@ -408,6 +465,17 @@ pub fn generate_recovery_key_for_existing(
     salt: &str,
     encrypted_key: &[u8],
     nonce_bytes: &[u8],
+) -> GeneratedRecoveryKey {
+    generate_recovery_key_for_existing_with_rng(&mut OsRng, password, salt, encrypted_key, nonce_bytes)
+}
+
+/// [`generate_recovery_key_for_existing`] with the recovery key, salt and nonce drawn from `rng`.
+pub fn generate_recovery_key_for_existing_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    password: &str,
+    salt: &str,
+    encrypted_key: &[u8],
+    nonce_bytes: &[u8],
 ) -> GeneratedRecoveryKey {
     log("Generating recovery key for existing user...");
 
@@ -428,7 +496,10 @@ pub fn generate_recovery_key_for_existing(
     }
 
     // Decrypt with current password
-    let encryption_key = get_key_encryption_key(password, salt);
+    let encryption_key = match try_key_encryption_key(password, salt) {
+        Ok(key) => key,
+        Err(e) => return err(e),
+    };
     let key = GenericArray::from_slice(&encryption_key);
     let cipher = Aes256Gcm::new(key);
     let nonce = Nonce::from_slice(nonce_bytes);
@@ -440,14 +511,14 @@ pub fn generate_recovery_key_for_existing(
 
     // Generate recovery key and encrypt
     use x25519_dalek::StaticSecret;
-    let recovery_secret = StaticSecret::random_from_rng(OsRng);
+    let recovery_secret = StaticSecret::random_from_rng(&mut *rng);
     let recovery_key_hex = bytes_to_hex(&recovery_secret.to_bytes());
 
-    let recovery_salt = SaltString::generate(&mut OsRng);
+    let recovery_salt = SaltString::generate(&mut *rng);
     let recovery_kek = get_key_encryption_key(&recovery_key_hex, recovery_salt.as_str());
     let rk = GenericArray::from_slice(&recovery_kek);
     let recovery_cipher = Aes256Gcm::new(rk);
-    let recovery_nonce = generate_nonce();
+    let recovery_nonce = generate_nonce_with_rng(rng);
 
     let recovery_ct = match recovery_cipher.encrypt(&recovery_nonce, private_key_bytes.as_ref()) {
         Ok(ct) => ct,
@@ -489,4 +560,42 @@ impl GeneratedRecoveryKey {
 
     #[wasm_bindgen(getter)]
     pub fn error_message(&self) -> String { self.error_message.clone() }
-}
\ No newline at end of file
+}
+
+/// Decrypts the user's private key and returns the raw 32 bytes.
+///
+/// Thin wrapper over [`decrypt_private_key`] for Rust callers that need a `Result`.
+pub fn unlock_private_key(
+    password: &str,
+    salt: &str,
+    encrypted_key: &[u8],
+    nonce: &[u8],
+) -> Result<Vec<u8>, String> {
+    let key_result = decrypt_private_key(password, salt, encrypted_key, nonce);
+    if !key_result.success {
+        return Err(key_result.error_message);
+    }
+    if key_result.private_key.len() != 32 {
+        return Err(format!("Invalid private key length: {}", key_result.private_key.len()));
+    }
+    Ok(key_result.private_key)
+}
+
+/// The password-protected private key fields, as stored on the user profile.
+/// Passed from JavaScript via serde as a nested object.
+#[derive(Deserialize, Zeroize)]
+#[zeroize(drop)]
+pub struct PrivateKeyCredentials {
+    pub password: String,
+    pub pk_salt: String,
+    pub encrypted_private_key: Vec<u8>,
+    pub pk_nonce: Vec<u8>,
+}
+
+impl PrivateKeyCredentials {
+    /// Decrypts the private key. See [`unlock_private_key`].
+    pub fn unlock(&self) -> Result<Vec<u8>, String> {
+        unlock_private_key(&self.password, &self.pk_salt, &self.encrypted_private_key, &self.pk_nonce)
+            .map_err(|e| format!("Failed to decrypt private key: {}", e))
+    }
+}
@@ -1,10 +1,10 @@
 use wasm_bindgen::prelude::*;
 use aes_gcm::{
-    Aes256Gcm, aead::{Aead, KeyInit, OsRng, generic_array::GenericArray}
+    Aes256Gcm, aead::{Aead, KeyInit, OsRng, generic_array::GenericArray, rand_core::{CryptoRng, RngCore}}
 };
 use x25519_dalek::{PublicKey, StaticSecret};
 use argon2::{password_hash::SaltString};
-pub use crate::{generate_nonce, get_key_encryption_key, bytes_to_hex, alert, log};
+pub use crate::{generate_nonce, generate_nonce_with_rng, get_key_encryption_key, try_key_encryption_key, bytes_to_hex, alert, log};
 
 
 /// Encrypts a master key using AES-256-GCM
@@ -26,18 +26,23 @@ pub struct EncryptedMasterKey {
 
 #[wasm_bindgen]
 pub fn encrypt_master_key(input: &str) -> EncryptedMasterKey {
-    let salt = SaltString::generate(&mut OsRng);
+    encrypt_master_key_with_rng(&mut OsRng, input)
+}
+
+/// [`encrypt_master_key`] with the salt, key pair and nonce drawn from `rng`.
+pub fn encrypt_master_key_with_rng<R: RngCore + CryptoRng>(rng: &mut R, input: &str) -> EncryptedMasterKey {
+    let salt = SaltString::generate(&mut *rng);
     // Generate the data encryption key from input
     let encryption_key = get_key_encryption_key(input, salt.as_str());
     log(&bytes_to_hex(&encryption_key));
 
-    let secret = StaticSecret::random_from_rng(OsRng);
+    let secret = StaticSecret::random_from_rng(&mut *rng);
     let public = PublicKey::from(&secret);
     log("Generated X25519 key pair");
 
     let key = GenericArray::from_slice(&encryption_key);
     let cipher = Aes256Gcm::new(key);
-    let nonce = generate_nonce();
+    let nonce = generate_nonce_with_rng(rng);
 
     log(&bytes_to_hex(&nonce));
 
@@ -115,13 +120,21 @@ pub struct EncryptedMasterKeyWithRecovery {
 /// and must be saved by the user offline.
 #[wasm_bindgen]
 pub fn encrypt_master_key_with_recovery(input: &str) -> EncryptedMasterKeyWithRecovery {
+    encrypt_master_key_with_recovery_with_rng(&mut OsRng, input)
+}
+
+/// [`encrypt_master_key_with_recovery`] with every salt, key and nonce drawn from `rng`.
+pub fn encrypt_master_key_with_recovery_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    input: &str,
+) -> EncryptedMasterKeyWithRecovery {
     log("Generating master key with recovery key...");
 
-    let salt = SaltString::generate(&mut OsRng);
+    let salt = SaltString::generate(&mut *rng);
     let encryption_key = get_key_encryption_key(input, salt.as_str());
 
     // Generate X25519 key pair
-    let secret = StaticSecret::random_from_rng(OsRng);
+    let secret = StaticSecret::random_from_rng(&mut *rng);
     let public = PublicKey::from(&secret);
     let private_key_bytes = secret.to_bytes();
     log("Generated X25519 key pair");
@@ -129,24 +142,24 @@ pub fn encrypt_master_key_with_recovery(input: &str) -> EncryptedMasterKeyWithRe
     // Encrypt private key with password-derived KEK
     let key = GenericArray::from_slice(&encryption_key);
     let cipher = Aes256Gcm::new(key);
-    let nonce = generate_nonce();
+    let nonce = generate_nonce_with_rng(rng);
 
     let ciphertext = cipher
         .encrypt(&nonce, private_key_bytes.as_ref())
         .expect("Failed to encrypt master key with password");
 
     // Generate a random 256-bit recovery key
-    let recovery_secret = StaticSecret::random_from_rng(OsRng);
+    let recovery_secret = StaticSecret::random_from_rng(&mut *rng);
     let recovery_key_bytes = recovery_secret.to_bytes();
     let recovery_key_hex = bytes_to_hex(&recovery_key_bytes);
     log("Generated recovery key");
 
     // Encrypt private key with recovery-derived KEK
-    let recovery_salt = SaltString::generate(&mut OsRng);
+    let recovery_salt = SaltString::generate(&mut *rng);
     let recovery_encryption_key = get_key_encryption_key(&recovery_key_hex, recovery_salt.as_str());
     let recovery_key_ga = GenericArray::from_slice(&recovery_encryption_key);
     let recovery_cipher = Aes256Gcm::new(recovery_key_ga);
-    let recovery_nonce = generate_nonce();
+    let recovery_nonce = generate_nonce_with_rng(rng);
 
     let recovery_ciphertext = recovery_cipher
         .encrypt(&recovery_nonce, private_key_bytes.as_ref())
@@ -202,4 +215,51 @@ impl EncryptedMasterKeyWithRecovery {
 
     #[wasm_bindgen(getter)]
     pub fn recovery_nonce_hex(&self) -> String { bytes_to_hex(&self.recovery_nonce) }
-}
\ No newline at end of file
+}
+
+// --- Shared private key wrapping ---
+
+/// A private key encrypted under a KEK derived from a password or recovery key.
+pub struct WrappedPrivateKey {
+    pub encrypted_private_key: Vec<u8>,
+    pub salt: String,
+    pub nonce: Vec<u8>,
+}
+
+/// Encrypts an X25519 private key under a KEK derived from `input` with a fresh salt.
+pub fn wrap_private_key(input: &str, private_key: &[u8]) -> Result<WrappedPrivateKey, String> {
+    wrap_private_key_with_rng(&mut OsRng, input, private_key)
+}
+
+/// [`wrap_private_key`] with the salt and nonce drawn from `rng`.
+pub fn wrap_private_key_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    input: &str,
+    private_key: &[u8],
+) -> Result<WrappedPrivateKey, String> {
+    let salt = SaltString::generate(&mut *rng);
+    let nonce = generate_nonce_with_rng(rng);
+    let encrypted_private_key = seal_private_key(input, salt.as_str(), &nonce, private_key)?;
+
+    Ok(WrappedPrivateKey {
+        encrypted_private_key,
+        salt: salt.as_str().to_string(),
+        nonce: nonce.to_vec(),
+    })
+}
+
+/// Encrypts a private key under the KEK for `input` and `salt` with a given nonce.
+///
+/// The deterministic core of [`wrap_private_key`]; callers must never reuse a nonce.
+pub fn seal_private_key(input: &str, salt: &str, nonce: &[u8], private_key: &[u8]) -> Result<Vec<u8>, String> {
+    if nonce.len() != 12 {
+        return Err(format!("Nonce must be 12 bytes, got {}", nonce.len()));
+    }
+    let encryption_key = try_key_encryption_key(input, salt)?;
+    let key = GenericArray::from_slice(&encryption_key);
+    let cipher = Aes256Gcm::new(key);
+
+    cipher
+        .encrypt(GenericArray::from_slice(nonce), private_key)
+        .map_err(|_| "Failed to encrypt private key.".to_string())
+}
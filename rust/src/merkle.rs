@@ -0,0 +1,269 @@
+//! Merkle trees over fixed-size plaintext chunks.
+//!
+//! A file is split into `MERKLE_CHUNK_SIZE` chunks (the last one may be
+//! shorter) and hashed into a tree as in RFC 6962:
+//!   leaf = SHA-256(0x00 || chunk)
+//!   node = SHA-256(0x01 || left || right)
+//! where a range of n > 1 leaves splits at the largest power of two below n.
+//! The root of an empty file is SHA-256 of the empty string.
+//!
+//! The root is returned by `encrypt_file_multi` next to the flat file hash
+//! and can be anchored in the ledger like any other hash. An inclusion proof
+//! then lets a verifier check a single chunk against the root, so a damaged
+//! download can be pinned to the chunk that is wrong.
+
+use wasm_bindgen::prelude::*;
+use sha2::{Sha256, Digest};
+use serde::{Serialize, Deserialize};
+
+pub use crate::{bytes_to_hex, hex_to_bytes, log};
+use crate::identity_signature::SignatureVerificationResult;
+use crate::progress::{ByteCounter, Progress, ProgressError, Stage};
+
+/// Plaintext bytes per Merkle leaf (1 MiB)
+pub const MERKLE_CHUNK_SIZE: usize = 1024 * 1024;
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Hashes one chunk into a leaf.
+pub fn leaf_hash(chunk: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(chunk);
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// The largest power of two strictly below `n` (n > 1).
+fn split_point(n: usize) -> usize {
+    1 << (usize::BITS - (n - 1).leading_zeros() - 1)
+}
+
+fn subtree_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    match leaves.len() {
+        0 => Sha256::digest([]).into(),
+        1 => leaves[0],
+        n => {
+            let k = split_point(n);
+            node_hash(&subtree_root(&leaves[..k]), &subtree_root(&leaves[k..]))
+        }
+    }
+}
+
+/// Appends the audit path for leaf `index`, deepest sibling first.
+fn audit_path(index: usize, leaves: &[[u8; 32]], out: &mut Vec<[u8; 32]>) {
+    let n = leaves.len();
+    if n <= 1 {
+        return;
+    }
+    let k = split_point(n);
+    if index < k {
+        audit_path(index, &leaves[..k], out);
+        out.push(subtree_root(&leaves[k..]));
+    } else {
+        audit_path(index - k, &leaves[k..], out);
+        out.push(subtree_root(&leaves[..k]));
+    }
+}
+
+/// The leaf hashes of a file, from which roots and proofs are computed.
+pub struct MerkleTree {
+    leaves: Vec<[u8; 32]>,
+}
+
+impl MerkleTree {
+    /// Splits `data` into `MERKLE_CHUNK_SIZE` chunks and hashes each one.
+    pub fn from_data(data: &[u8]) -> Self {
+        MerkleTree { leaves: data.chunks(MERKLE_CHUNK_SIZE).map(leaf_hash).collect() }
+    }
+
+    /// Hashes `data` whole (SHA-256, hex) and as a tree in one pass,
+    /// reporting `Stage::Hash` progress per chunk.
+    pub fn hash_with_progress(data: &[u8], progress: &mut dyn Progress) -> Result<(String, Self), ProgressError> {
+        let mut counter = ByteCounter::start(progress, Stage::Hash, data.len() as u64)?;
+        let mut hasher = Sha256::new();
+        let mut leaves = Vec::with_capacity(data.len().div_ceil(MERKLE_CHUNK_SIZE));
+        for chunk in data.chunks(MERKLE_CHUNK_SIZE) {
+            hasher.update(chunk);
+            leaves.push(leaf_hash(chunk));
+            counter.advance(progress, chunk.len() as u64)?;
+        }
+        Ok((bytes_to_hex(&hasher.finalize()), MerkleTree { leaves }))
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        subtree_root(&self.leaves)
+    }
+
+    pub fn root_hex(&self) -> String {
+        bytes_to_hex(&self.root())
+    }
+
+    /// Produces the inclusion proof for chunk `index`.
+    pub fn proof(&self, index: usize) -> Result<MerkleProof, String> {
+        if index >= self.leaves.len() {
+            return Err(format!(
+                "Chunk index {} out of range for {} chunks",
+                index,
+                self.leaves.len()
+            ));
+        }
+
+        let mut siblings = Vec::new();
+        audit_path(index, &self.leaves, &mut siblings);
+
+        Ok(MerkleProof {
+            chunk_index: index as u64,
+            chunk_count: self.leaves.len() as u64,
+            siblings: siblings.iter().map(|hash| bytes_to_hex(hash)).collect(),
+        })
+    }
+}
+
+/// An inclusion proof for one chunk of a file.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MerkleProof {
+    pub chunk_index: u64,
+    /// Number of chunks in the file
+    pub chunk_count: u64,
+    /// Sibling hashes (hex) from the leaf up to the root
+    pub siblings: Vec<String>,
+}
+
+impl MerkleProof {
+    /// Checks that `chunk` is chunk `chunk_index` of the file with `root`.
+    pub fn verify(&self, chunk: &[u8], root: &[u8]) -> Result<(), String> {
+        if self.chunk_index >= self.chunk_count {
+            return Err(format!(
+                "Chunk index {} out of range for {} chunks",
+                self.chunk_index, self.chunk_count
+            ));
+        }
+
+        // Every chunk but the last is full, and none is empty
+        let is_last = self.chunk_index == self.chunk_count - 1;
+        if chunk.is_empty() || chunk.len() > MERKLE_CHUNK_SIZE || (!is_last && chunk.len() != MERKLE_CHUNK_SIZE) {
+            return Err(format!(
+                "Chunk {} has an invalid length of {} bytes",
+                self.chunk_index,
+                chunk.len()
+            ));
+        }
+
+        // RFC 9162, section 2.1.3.2
+        let mut f_n = self.chunk_index;
+        let mut s_n = self.chunk_count - 1;
+        let mut hash = leaf_hash(chunk);
+
+        for sibling_hex in &self.siblings {
+            let sibling: [u8; 32] = hex_to_bytes(sibling_hex)?
+                .try_into()
+                .map_err(|_| "Proof hashes must be 32 bytes".to_string())?;
+
+            if s_n == 0 {
+                return Err("Proof is too long".to_string());
+            }
+
+            if f_n & 1 == 1 || f_n == s_n {
+                hash = node_hash(&sibling, &hash);
+                while f_n & 1 == 0 && f_n != 0 {
+                    f_n >>= 1;
+                    s_n >>= 1;
+                }
+            } else {
+                hash = node_hash(&hash, &sibling);
+            }
+
+            f_n >>= 1;
+            s_n >>= 1;
+        }
+
+        if s_n != 0 {
+            return Err("Proof is too short".to_string());
+        }
+        if hash.as_slice() != root {
+            return Err(format!("Chunk {} does not match the Merkle root", self.chunk_index));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+pub struct ChunkProofResult {
+    pub success: bool,
+    pub merkle_root_hex: String,
+    pub proof: Option<MerkleProof>,
+    pub error_message: String,
+}
+
+/// Computes the Merkle root (hex) of a file's plaintext.
+#[wasm_bindgen]
+pub fn compute_merkle_root(file_data: &[u8]) -> String {
+    log(&format!("[compute_merkle_root] Hashing {} bytes...", file_data.len()));
+    let root = MerkleTree::from_data(file_data).root_hex();
+    log(&format!("[compute_merkle_root] Root: {}", root));
+    root
+}
+
+/// Produces the inclusion proof for one chunk of a file.
+///
+/// # Arguments
+/// * `file_data` - The full plaintext
+/// * `chunk_index` - The chunk to prove (0-based, `MERKLE_CHUNK_SIZE` bytes each)
+///
+/// # Returns
+/// A JsValue containing `ChunkProofResult`; store `proof` for `verify_chunk_proof`.
+#[wasm_bindgen]
+pub fn generate_chunk_proof(file_data: &[u8], chunk_index: u32) -> JsValue {
+    log(&format!("[generate_chunk_proof] Proving chunk {}...", chunk_index));
+
+    let tree = MerkleTree::from_data(file_data);
+    let result = match tree.proof(chunk_index as usize) {
+        Ok(proof) => ChunkProofResult {
+            success: true,
+            merkle_root_hex: tree.root_hex(),
+            proof: Some(proof),
+            error_message: String::new(),
+        },
+        Err(e) => {
+            log(&format!("[generate_chunk_proof] Failed: {}", e));
+            ChunkProofResult { success: false, merkle_root_hex: String::new(), proof: None, error_message: e }
+        }
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// Verifies one chunk of a file against its Merkle root.
+///
+/// # Arguments
+/// * `merkle_root_hex` - The root recorded at upload (or anchored in the ledger)
+/// * `chunk_data` - The chunk's plaintext bytes
+/// * `proof_js` - A JsValue representing a `MerkleProof`
+#[wasm_bindgen]
+pub fn verify_chunk_proof(merkle_root_hex: &str, chunk_data: &[u8], proof_js: JsValue) -> SignatureVerificationResult {
+    log("[verify_chunk_proof] Verifying chunk...");
+
+    let proof = match serde_wasm_bindgen::from_value::<MerkleProof>(proof_js) {
+        Ok(proof) => proof,
+        Err(e) => return Err(format!("Failed to parse proof: {}", e)).into(),
+    };
+
+    let result = hex_to_bytes(merkle_root_hex).and_then(|root| proof.verify(chunk_data, &root));
+    if let Err(e) = &result {
+        log(&format!("[verify_chunk_proof] Verification failed: {}", e));
+    }
+    result.into()
+}
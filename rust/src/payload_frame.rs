@@ -0,0 +1,561 @@
+//! Framed file payloads.
+//!
+//! By default a file ciphertext is plain AES-256-GCM over the file bytes, so
+//! its length is the plaintext length plus 16. When padding or compression is
+//! requested the ciphertext is framed instead:
+//!
+//!   header = MAGIC || flags
+//!   blob   = header || AES-256-GCM(body, aad = header)
+//!   body   = data_len (u64 BE) || data || zero padding
+//!
+//! With the deflate flag, `data` is original_len (u64 BE) || raw deflate stream.
+//! The header is authenticated but readable, so a reader knows how to decode
+//! the body; the true length lives only inside the encrypted body. Blobs that
+//! do not start with MAGIC (or do not authenticate as an unchunked frame) are
+//! decrypted as legacy unframed ciphertexts.
+//!
+//! With the chunked flag the body is encrypted in independent segments, so
+//! any segment can be located, fetched and checked on its own:
+//!
+//!   header    = MAGIC || flags || segment_size (u32 BE)
+//!   blob      = header || segment_0 || segment_1 || ...
+//!   segment_i = AES-256-GCM(body[i * segment_size..][..segment_size],
+//!                           key = HKDF(dek, salt = file_nonce),
+//!                           nonce = 0u32 || i (u64 BE),
+//!                           aad = header || i (u64 BE) || is_last (u8))
+//!
+//! Every segment but the last holds exactly `segment_size` bytes of body, and
+//! `segment_size` is at least 8 so that segment 0 holds the data length. A
+//! chunked blob whose segments fail to authenticate is rejected rather than
+//! retried as a legacy ciphertext.
+
+use std::ops::Range;
+
+use miniz_oxide::{deflate::compress_to_vec, inflate::decompress_to_vec_with_limit};
+use aes_gcm::{
+    Aes256Gcm, Nonce, aead::{Aead, KeyInit, Payload, generic_array::GenericArray}
+};
+use hkdf::Hkdf;
+use serde::Deserialize;
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use crate::progress::{ByteCounter, NoProgress, Progress, ProgressError, Stage};
+
+const MAGIC: &[u8; 8] = b"FCFRAME\x01";
+const HEADER_LEN: usize = MAGIC.len() + 1;
+const LENGTH_PREFIX_LEN: usize = 8;
+const CHUNKED_HEADER_LEN: usize = HEADER_LEN + 4;
+const TAG_LEN: usize = 16;
+
+const FLAG_PADDED: u8 = 0b0000_0001;
+const FLAG_DEFLATE: u8 = 0b0000_0010;
+const FLAG_CHUNKED: u8 = 0b0000_0100;
+
+const SEGMENT_KEY_LABEL: &[u8] = b"filechain-payload-segment-key-v1";
+
+/// Body bytes per segment of a chunked payload (64 KiB)
+pub const DEFAULT_SEGMENT_SIZE: usize = 64 * 1024;
+
+const DEFLATE_LEVEL: u8 = 6;
+
+/// Leading bytes of formats that are already compressed.
+const COMPRESSED_SIGNATURES: &[&[u8]] = &[
+    b"PK\x03\x04", // ZIP, DOCX, XLSX, ODT
+    b"\x1f\x8b", // gzip
+    b"\x28\xb5\x2f\xfd", // zstd
+    b"\xfd7zXZ\x00", // xz
+    b"BZh", // bzip2
+    b"7z\xbc\xaf\x27\x1c", // 7-Zip
+    b"Rar!\x1a\x07", // RAR
+    b"\x89PNG", // PNG
+    b"\xff\xd8\xff", // JPEG
+    b"GIF8", // GIF
+    b"\x1a\x45\xdf\xa3", // WebM, Matroska
+    b"OggS", // Ogg
+    b"fLaC", // FLAC
+    b"ID3", // MP3
+];
+
+/// How a framed body is padded before encryption.
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PaddingScheme {
+    /// No padding (legacy unframed ciphertext)
+    #[default]
+    None,
+    /// Padmé: at most ~12% overhead, leaks O(log log n) bits of the length
+    Padme,
+    /// Round up to the next power of two
+    PowerOfTwo,
+}
+
+impl PaddingScheme {
+    /// The padded size for a body of `len` bytes, or an error if it would
+    /// not fit in memory (e.g. a power of two above 2 GiB on wasm32).
+    pub fn padded_len(&self, len: usize) -> Result<usize, String> {
+        let padded = match self {
+            _ if len > isize::MAX as usize => None,
+            PaddingScheme::None => Some(len),
+            PaddingScheme::Padme => usize::try_from(padme(len as u64)).ok(),
+            PaddingScheme::PowerOfTwo => len.checked_next_power_of_two(),
+        };
+        padded
+            .filter(|&padded| padded <= isize::MAX as usize)
+            .ok_or_else(|| format!("Payload of {} bytes is too large to pad", len))
+    }
+}
+
+/// The Padmé padded length of `len` (Nikitin et al., PURBs, 2019).
+pub fn padme(len: u64) -> u64 {
+    if len < 2 {
+        return len;
+    }
+    let exponent = 63 - len.leading_zeros() as u64;
+    let size_bits = 64 - exponent.leading_zeros() as u64;
+    let last_bits = exponent - size_bits;
+    let mask = (1u64 << last_bits) - 1;
+    (len + mask) & !mask
+}
+
+/// Compression applied inside the encrypted body.
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionScheme {
+    #[default]
+    None,
+    /// Raw deflate; skipped for already-compressed formats
+    Deflate,
+}
+
+/// Whether `data` starts with the signature of an already-compressed format
+/// (or sits in an ISO BMFF container such as MP4, MOV or HEIC).
+pub fn is_already_compressed(data: &[u8]) -> bool {
+    COMPRESSED_SIGNATURES.iter().any(|signature| data.starts_with(signature))
+        || data.get(4..8) == Some(b"ftyp")
+        || (data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP"))
+}
+
+/// How a file payload is framed before encryption.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct FrameOptions {
+    pub padding: PaddingScheme,
+    pub compression: CompressionScheme,
+    /// Encrypts the body in segments of this many bytes (chunked layout)
+    pub segment_size: Option<usize>,
+}
+
+impl FrameOptions {
+    fn flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.padding != PaddingScheme::None {
+            flags |= FLAG_PADDED;
+        }
+        if self.compression == CompressionScheme::Deflate {
+            flags |= FLAG_DEFLATE;
+        }
+        if self.segment_size.is_some() {
+            flags |= FLAG_CHUNKED;
+        }
+        flags
+    }
+
+    /// Whether these options produce a legacy unframed ciphertext.
+    pub fn is_plain(&self) -> bool {
+        self.flags() == 0
+    }
+}
+
+/// Encrypts file bytes under `dek`, framing them when `options` ask for it.
+pub fn seal_payload(dek: &[u8], file_nonce: &[u8], data: &[u8], options: &FrameOptions) -> Result<Vec<u8>, String> {
+    seal_payload_with_progress(dek, file_nonce, data, options, &mut NoProgress).map_err(String::from)
+}
+
+/// [`seal_payload`], reporting `Stage::Encrypt` progress.
+///
+/// Only the chunked layout can be cancelled part-way; other payloads are
+/// encrypted in one piece, reporting 0 and then the total.
+pub fn seal_payload_with_progress(
+    dek: &[u8],
+    file_nonce: &[u8],
+    data: &[u8],
+    options: &FrameOptions,
+    progress: &mut dyn Progress,
+) -> Result<Vec<u8>, ProgressError> {
+    if dek.len() != 32 {
+        return Err(format!("DEK must be 32 bytes, got {}", dek.len()).into());
+    }
+    if file_nonce.len() != 12 {
+        return Err(format!("File nonce must be 12 bytes, got {}", file_nonce.len()).into());
+    }
+    if let Some(segment_size) = options.segment_size
+        && (segment_size < LENGTH_PREFIX_LEN || segment_size > u32::MAX as usize)
+    {
+        return Err(format!("Invalid segment size {}", segment_size).into());
+    }
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(dek));
+    let nonce = Nonce::from_slice(file_nonce);
+
+    // Only keep compression when it applies and actually saves space
+    let mut options = *options;
+    let mut compressed = None;
+    if options.compression == CompressionScheme::Deflate {
+        if !is_already_compressed(data) {
+            let mut stream = (data.len() as u64).to_be_bytes().to_vec();
+            stream.extend_from_slice(&compress_to_vec(data, DEFLATE_LEVEL));
+            if stream.len() < data.len() {
+                compressed = Some(stream);
+            }
+        }
+        if compressed.is_none() {
+            options.compression = CompressionScheme::None;
+        }
+    }
+    let data = compressed.as_deref().unwrap_or(data);
+
+    if options.is_plain() {
+        let mut counter = ByteCounter::start(progress, Stage::Encrypt, data.len() as u64)?;
+        let encrypted = cipher.encrypt(nonce, data).map_err(|e| format!("File encryption failed: {}", e))?;
+        counter.advance(progress, data.len() as u64)?;
+        return Ok(encrypted);
+    }
+
+    let mut header = MAGIC.to_vec();
+    header.push(options.flags());
+    if let Some(segment_size) = options.segment_size {
+        header.extend_from_slice(&(segment_size as u32).to_be_bytes());
+    }
+
+    let padded_len = match options.padding.padded_len(LENGTH_PREFIX_LEN + data.len()) {
+        Ok(padded_len) => padded_len,
+        Err(e) => {
+            if let Some(mut stream) = compressed {
+                stream.zeroize();
+            }
+            return Err(e.into());
+        }
+    };
+    let mut body = Vec::with_capacity(padded_len);
+    body.extend_from_slice(&(data.len() as u64).to_be_bytes());
+    body.extend_from_slice(data);
+    body.resize(padded_len, 0);
+
+    let encrypted = match options.segment_size {
+        Some(segment_size) => seal_segments(dek, file_nonce, &header, &body, segment_size, progress),
+        None => ByteCounter::start(progress, Stage::Encrypt, body.len() as u64).and_then(|mut counter| {
+            let encrypted = cipher
+                .encrypt(nonce, Payload { msg: &body, aad: &header })
+                .map_err(|e| format!("File encryption failed: {}", e))?;
+            counter.advance(progress, body.len() as u64)?;
+            Ok(encrypted)
+        }),
+    };
+    body.zeroize();
+    if let Some(mut stream) = compressed {
+        stream.zeroize();
+    }
+
+    let mut blob = header;
+    blob.extend_from_slice(&encrypted?);
+    Ok(blob)
+}
+
+fn segment_cipher(dek: &[u8], file_nonce: &[u8]) -> Result<Aes256Gcm, String> {
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(file_nonce), dek)
+        .expand(SEGMENT_KEY_LABEL, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(&key));
+    key.zeroize();
+    Ok(cipher)
+}
+
+fn segment_nonce(index: u64) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[4..].copy_from_slice(&index.to_be_bytes());
+    nonce
+}
+
+fn segment_aad(header: &[u8], index: u64, is_last: bool) -> Vec<u8> {
+    let mut aad = header.to_vec();
+    aad.extend_from_slice(&index.to_be_bytes());
+    aad.push(is_last as u8);
+    aad
+}
+
+fn seal_segments(
+    dek: &[u8],
+    file_nonce: &[u8],
+    header: &[u8],
+    body: &[u8],
+    segment_size: usize,
+    progress: &mut dyn Progress,
+) -> Result<Vec<u8>, ProgressError> {
+    let cipher = segment_cipher(dek, file_nonce)?;
+    let segment_count = body.len().div_ceil(segment_size);
+    let mut counter = ByteCounter::start(progress, Stage::Encrypt, body.len() as u64)?;
+
+    let mut out = Vec::with_capacity(body.len() + segment_count * TAG_LEN);
+    for (i, segment) in body.chunks(segment_size).enumerate() {
+        let index = i as u64;
+        let aad = segment_aad(header, index, i == segment_count - 1);
+        let encrypted = cipher
+            .encrypt(Nonce::from_slice(&segment_nonce(index)), Payload { msg: segment, aad: &aad })
+            .map_err(|e| format!("File encryption failed: {}", e))?;
+        out.extend_from_slice(&encrypted);
+        counter.advance(progress, segment.len() as u64)?;
+    }
+    Ok(out)
+}
+
+/// Where the segments of a chunked payload sit in its blob.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkedLayout {
+    /// Body bytes per segment; each stored segment is 16 bytes longer
+    pub segment_size: u64,
+    pub segment_count: u64,
+    pub blob_len: u64,
+}
+
+impl ChunkedLayout {
+    /// Bytes of header before the first segment.
+    pub const HEADER_LEN: usize = CHUNKED_HEADER_LEN;
+
+    /// Reads the layout from the first `HEADER_LEN` bytes of a blob and the
+    /// blob's total length, so the rest of the blob need not be fetched.
+    pub fn parse(header: &[u8], blob_len: u64) -> Result<Self, String> {
+        if header.len() < CHUNKED_HEADER_LEN
+            || !header.starts_with(MAGIC)
+            || header[MAGIC.len()] & FLAG_CHUNKED == 0
+        {
+            return Err("Payload does not use the chunked layout".to_string());
+        }
+        let segment_size = u32::from_be_bytes(header[HEADER_LEN..CHUNKED_HEADER_LEN].try_into().unwrap()) as u64;
+        if segment_size == 0 {
+            return Err("Chunked payload has a zero segment size".to_string());
+        }
+
+        let stride = segment_size + TAG_LEN as u64;
+        let segments_len = blob_len
+            .checked_sub(CHUNKED_HEADER_LEN as u64)
+            .ok_or_else(|| "Chunked payload is truncated".to_string())?;
+        let segment_count = segments_len.div_ceil(stride);
+        // The last segment must hold at least one body byte
+        if segment_count == 0 || segments_len - (segment_count - 1) * stride <= TAG_LEN as u64 {
+            return Err("Chunked payload is truncated".to_string());
+        }
+
+        Ok(ChunkedLayout { segment_size, segment_count, blob_len })
+    }
+
+    /// Total body bytes across all segments.
+    pub fn body_len(&self) -> u64 {
+        self.blob_len - CHUNKED_HEADER_LEN as u64 - self.segment_count * TAG_LEN as u64
+    }
+
+    /// The byte range of stored segment `index` within the blob.
+    pub fn segment_range(&self, index: u64) -> Range<u64> {
+        let start = CHUNKED_HEADER_LEN as u64 + index * (self.segment_size + TAG_LEN as u64);
+        start..(start + self.segment_size + TAG_LEN as u64).min(self.blob_len)
+    }
+}
+
+/// Decrypts stored segment `index` of a chunked payload into body bytes.
+///
+/// `header` is the blob's first `ChunkedLayout::HEADER_LEN` bytes.
+pub fn open_segment(
+    dek: &[u8],
+    file_nonce: &[u8],
+    header: &[u8],
+    layout: &ChunkedLayout,
+    index: u64,
+    segment: &[u8],
+) -> Result<Vec<u8>, String> {
+    if index >= layout.segment_count {
+        return Err(format!("Segment {} out of range for {} segments", index, layout.segment_count));
+    }
+    let header = header
+        .get(..CHUNKED_HEADER_LEN)
+        .ok_or_else(|| "Chunked payload header is truncated".to_string())?;
+    let aad = segment_aad(header, index, index == layout.segment_count - 1);
+    segment_cipher(dek, file_nonce)?
+        .decrypt(Nonce::from_slice(&segment_nonce(index)), Payload { msg: segment, aad: &aad })
+        .map_err(|_| format!("Segment {} failed to authenticate. Invalid DEK or corrupted data.", index))
+}
+
+/// Opens segment 0 of a chunked payload for range reads.
+///
+/// `head` is the start of the blob, through the end of segment 0. Returns the
+/// layout and the data length.
+pub fn open_chunked_head(dek: &[u8], file_nonce: &[u8], head: &[u8], blob_len: u64) -> Result<(ChunkedLayout, u64), String> {
+    let layout = ChunkedLayout::parse(head, blob_len)?;
+    if head[MAGIC.len()] & FLAG_DEFLATE != 0 {
+        return Err("Compressed payloads cannot be read by range".to_string());
+    }
+    if layout.segment_size < LENGTH_PREFIX_LEN as u64 {
+        return Err(format!("Segment size {} is too small for range reads", layout.segment_size));
+    }
+
+    let first = layout.segment_range(0);
+    let segment = head
+        .get(first.start as usize..first.end as usize)
+        .ok_or_else(|| format!("Range reads need the first {} bytes of the payload", first.end))?;
+    let mut body = open_segment(dek, file_nonce, head, &layout, 0, segment)?;
+    if body.len() < LENGTH_PREFIX_LEN {
+        body.zeroize();
+        return Err("Framed payload is truncated".to_string());
+    }
+    let data_len = u64::from_be_bytes(body[..LENGTH_PREFIX_LEN].try_into().unwrap());
+    body.zeroize();
+
+    if data_len > layout.body_len() - LENGTH_PREFIX_LEN as u64 {
+        return Err("Framed payload length exceeds its body".to_string());
+    }
+    Ok((layout, data_len))
+}
+
+fn open_segments(
+    dek: &[u8],
+    file_nonce: &[u8],
+    blob: &[u8],
+    progress: &mut dyn Progress,
+) -> Result<Vec<u8>, ProgressError> {
+    let layout = ChunkedLayout::parse(blob, blob.len() as u64)?;
+    let header = &blob[..CHUNKED_HEADER_LEN];
+    let mut counter = ByteCounter::start(progress, Stage::Decrypt, blob.len() as u64)?;
+    counter.advance(progress, CHUNKED_HEADER_LEN as u64)?;
+
+    let mut body = Vec::with_capacity(blob.len());
+    for index in 0..layout.segment_count {
+        let range = layout.segment_range(index);
+        let segment = &blob[range.start as usize..range.end as usize];
+        let opened = open_segment(dek, file_nonce, header, &layout, index, segment).map_err(ProgressError::from).and_then(|mut plaintext| {
+            body.extend_from_slice(&plaintext);
+            plaintext.zeroize();
+            counter.advance(progress, segment.len() as u64)
+        });
+        if let Err(e) = opened {
+            body.zeroize();
+            return Err(e);
+        }
+    }
+    Ok(body)
+}
+
+/// Decrypts a payload produced by [`seal_payload`] (framed or legacy).
+pub fn open_payload(dek: &[u8], file_nonce: &[u8], blob: &[u8]) -> Result<Vec<u8>, String> {
+    open_payload_with_progress(dek, file_nonce, blob, &mut NoProgress).map_err(String::from)
+}
+
+/// [`open_payload`], reporting `Stage::Decrypt` progress over the blob.
+///
+/// As with sealing, only chunked payloads report and check for an abort
+/// between segments; others are decrypted in one piece.
+pub fn open_payload_with_progress(
+    dek: &[u8],
+    file_nonce: &[u8],
+    blob: &[u8],
+    progress: &mut dyn Progress,
+) -> Result<Vec<u8>, ProgressError> {
+    open_framed(dek, file_nonce, blob, progress).map(|(data, _)| data)
+}
+
+/// [`open_payload`], also returning the options that seal data back into
+/// the same layout (padding, compression and segment size).
+pub fn open_payload_with_options(dek: &[u8], file_nonce: &[u8], blob: &[u8]) -> Result<(Vec<u8>, FrameOptions), String> {
+    open_framed(dek, file_nonce, blob, &mut NoProgress).map_err(String::from)
+}
+
+fn open_framed(
+    dek: &[u8],
+    file_nonce: &[u8],
+    blob: &[u8],
+    progress: &mut dyn Progress,
+) -> Result<(Vec<u8>, FrameOptions), ProgressError> {
+    if dek.len() != 32 {
+        return Err(format!("DEK must be 32 bytes, got {}", dek.len()).into());
+    }
+    if file_nonce.len() != 12 {
+        return Err(format!("File nonce must be 12 bytes, got {}", file_nonce.len()).into());
+    }
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(dek));
+    let nonce = Nonce::from_slice(file_nonce);
+
+    if blob.len() >= HEADER_LEN && blob.starts_with(MAGIC) && blob[MAGIC.len()] & FLAG_CHUNKED != 0 {
+        let segment_size = ChunkedLayout::parse(blob, blob.len() as u64)?.segment_size as usize;
+        let mut body = open_segments(dek, file_nonce, blob, progress)?;
+        let decoded = decode_body(blob[MAGIC.len()], &body, Some(segment_size));
+        body.zeroize();
+        return Ok(decoded?);
+    }
+
+    let mut counter = ByteCounter::start(progress, Stage::Decrypt, blob.len() as u64)?;
+    if blob.len() >= HEADER_LEN && blob.starts_with(MAGIC) && blob[MAGIC.len()] & FLAG_CHUNKED == 0 {
+        let (header, ciphertext) = blob.split_at(HEADER_LEN);
+        if let Ok(mut body) = cipher.decrypt(nonce, Payload { msg: ciphertext, aad: header }) {
+            let decoded = counter
+                .advance(progress, blob.len() as u64)
+                .and_then(|_| Ok(decode_body(header[MAGIC.len()], &body, None)?));
+            body.zeroize();
+            return decoded;
+        }
+    }
+
+    let mut decrypted = cipher
+        .decrypt(nonce, blob)
+        .map_err(|_| "File decryption failed. Invalid DEK or corrupted data.".to_string())?;
+    if let Err(e) = counter.advance(progress, blob.len() as u64) {
+        decrypted.zeroize();
+        return Err(e);
+    }
+    Ok((decrypted, FrameOptions::default()))
+}
+
+/// Strips the frame from a decrypted body, returning the data and the options
+/// it was sealed with. The padding scheme is the one that gives this body's
+/// length, which re-seals the same data identically.
+fn decode_body(flags: u8, body: &[u8], segment_size: Option<usize>) -> Result<(Vec<u8>, FrameOptions), String> {
+    if flags & !(FLAG_PADDED | FLAG_DEFLATE | FLAG_CHUNKED) != 0 {
+        return Err(format!("Unsupported payload flags {:#04x}", flags));
+    }
+    if body.len() < LENGTH_PREFIX_LEN {
+        return Err("Framed payload is truncated".to_string());
+    }
+    let (prefix, rest) = body.split_at(LENGTH_PREFIX_LEN);
+    let data_len = u64::from_be_bytes(prefix.try_into().unwrap());
+    if data_len > rest.len() as u64 {
+        return Err("Framed payload length exceeds its body".to_string());
+    }
+    let (data, padding) = rest.split_at(data_len as usize);
+    if padding.iter().any(|&b| b != 0) {
+        return Err("Framed payload padding is not zero".to_string());
+    }
+
+    let unpadded_len = LENGTH_PREFIX_LEN + data.len();
+    let padding = if flags & FLAG_PADDED == 0 {
+        PaddingScheme::None
+    } else if PaddingScheme::Padme.padded_len(unpadded_len) == Ok(body.len()) {
+        PaddingScheme::Padme
+    } else {
+        PaddingScheme::PowerOfTwo
+    };
+    let mut options = FrameOptions { padding, compression: CompressionScheme::None, segment_size };
+
+    if flags & FLAG_DEFLATE == 0 {
+        return Ok((data.to_vec(), options));
+    }
+    options.compression = CompressionScheme::Deflate;
+    if data.len() < LENGTH_PREFIX_LEN {
+        return Err("Compressed payload is truncated".to_string());
+    }
+    let (prefix, stream) = data.split_at(LENGTH_PREFIX_LEN);
+    let original_len = u64::from_be_bytes(prefix.try_into().unwrap());
+    let original_len = usize::try_from(original_len).map_err(|_| "Compressed payload is too large".to_string())?;
+
+    let decompressed = decompress_to_vec_with_limit(stream, original_len)
+        .map_err(|e| format!("Decompression failed: {}", e))?;
+    if decompressed.len() != original_len {
+        return Err("Decompressed payload length does not match its header".to_string());
+    }
+    Ok((decompressed, options))
+}
@@ -0,0 +1,206 @@
+//! Pending shares to users who have not set up their master key yet.
+//!
+//! An invited user has no `public_key` until they complete account setup. The
+//! inviter instead derives a one-time invite key pair from a random invite
+//! code (sent to the invitee out of band) and wraps DEKs to its public half,
+//! like any other recipient. After setup, the invitee re-derives the invite
+//! key from the code and re-wraps every pending DEK to their real public key.
+//!
+//! The invite private key is Argon2id(invite_code, "filechain-invite:" + invitee_id),
+//! using the same KDF as the account KEK.
+
+use wasm_bindgen::prelude::*;
+use aes_gcm::aead::{OsRng, rand_core::{CryptoRng, RngCore}};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use x25519_dalek::{PublicKey, StaticSecret};
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroize;
+
+pub use crate::{bytes_to_hex, get_key_encryption_key, try_key_encryption_key, log};
+use crate::encrypt_file::{
+    FileDekEntry, FileDekInput, RecipientInput, wrap_dek_for_public_key_with_rng,
+};
+use crate::masterkey_decryptor::PrivateKeyCredentials;
+
+/// Derives the invite key pair for an invitee from their invite code.
+pub fn derive_invite_secret(invite_code: &str, invitee_id: &str) -> Result<StaticSecret, String> {
+    if invite_code.is_empty() {
+        return Err("Invite code cannot be empty".to_string());
+    }
+    if invitee_id.is_empty() {
+        return Err("Invitee ID cannot be empty".to_string());
+    }
+
+    let mut seed = try_key_encryption_key(invite_code, &format!("filechain-invite:{}", invitee_id))?;
+    let seed_array: [u8; 32] = seed.as_slice().try_into().unwrap();
+    seed.zeroize();
+    Ok(StaticSecret::from(seed_array))
+}
+
+#[derive(Serialize)]
+pub struct InviteResult {
+    pub success: bool,
+    /// Send to the invitee out of band; never store it server-side
+    pub invite_code: String,
+    /// Store on the pending invitation; encrypt to it like a `public_key`
+    pub invite_public_key_hex: String,
+    pub error_message: String,
+}
+
+/// Creates a one-time invite code and its invite public key.
+///
+/// # Arguments
+/// * `invitee_id` - The UUID of the invited user
+///
+/// # Returns
+/// A JsValue containing `InviteResult`. New uploads can include the invitee in
+/// `encrypt_file_multi` as `{ user_id: invitee_id, public_key: invite_public_key }`.
+#[wasm_bindgen]
+pub fn create_invite(invitee_id: &str) -> JsValue {
+    log("[create_invite] Creating invite key...");
+
+    serde_wasm_bindgen::to_value(&create_invite_with_rng(&mut OsRng, invitee_id)).unwrap()
+}
+
+/// The core of [`create_invite`], with the invite code drawn from `rng`.
+pub fn create_invite_with_rng<R: RngCore + CryptoRng>(rng: &mut R, invitee_id: &str) -> InviteResult {
+    let mut code_bytes = [0u8; 18];
+    rng.fill_bytes(&mut code_bytes);
+    let invite_code = URL_SAFE_NO_PAD.encode(code_bytes);
+    code_bytes.zeroize();
+
+    match derive_invite_secret(&invite_code, invitee_id) {
+        Ok(secret) => InviteResult {
+            success: true,
+            invite_public_key_hex: bytes_to_hex(PublicKey::from(&secret).as_bytes()),
+            invite_code,
+            error_message: String::new(),
+        },
+        Err(e) => InviteResult {
+            success: false,
+            invite_code: String::new(),
+            invite_public_key_hex: String::new(),
+            error_message: e,
+        },
+    }
+}
+
+#[derive(Serialize)]
+pub struct PendingShareResult {
+    pub success: bool,
+    pub dek_entries: Vec<FileDekEntry>,
+    pub error_message: String,
+}
+
+impl PendingShareResult {
+    fn from_result(result: Result<Vec<FileDekEntry>, String>) -> Self {
+        match result {
+            Ok(dek_entries) => PendingShareResult { success: true, dek_entries, error_message: String::new() },
+            Err(e) => PendingShareResult { success: false, dek_entries: vec![], error_message: e },
+        }
+    }
+}
+
+/// Unwraps each row with `private_key` and re-wraps it to `recipient`,
+/// keeping the row's grant window.
+fn rewrap_rows<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    private_key: &[u8],
+    rows: &[FileDekInput],
+    recipient: &RecipientInput,
+) -> Result<Vec<FileDekEntry>, String> {
+    rows.iter()
+        .map(|row| {
+            let mut dek = row.unwrap_dek(private_key).map_err(|e| format!("File {}: {}", row.file_id, e))?;
+            let wrapped = wrap_dek_for_public_key_with_rng(rng, &dek, &recipient.public_key, row.window());
+            dek.zeroize();
+            Ok(wrapped?.into_file_entry(&row.file_id, recipient))
+        })
+        .collect()
+}
+
+/// Input for sharing existing files with an invitee.
+#[derive(Deserialize)]
+pub struct CreateInviteWrappingsInput {
+    /// The sharer's own credentials
+    pub credentials: PrivateKeyCredentials,
+    pub invitee_id: String,
+    pub invite_public_key: Vec<u8>,
+    /// The sharer's `file_dek` rows for the files to share
+    pub dek_entries: Vec<FileDekInput>,
+}
+
+/// Wraps the DEKs of existing files to an invitee's invite public key.
+///
+/// # Returns
+/// A JsValue containing `PendingShareResult` with one pending `file_dek` row per file.
+#[wasm_bindgen]
+pub fn create_invite_wrappings(input_js: JsValue) -> JsValue {
+    log("[create_invite_wrappings] Wrapping DEKs for invitee...");
+
+    let result = serde_wasm_bindgen::from_value::<CreateInviteWrappingsInput>(input_js)
+        .map_err(|e| format!("Failed to parse input: {}", e))
+        .and_then(|input| {
+            let recipient = RecipientInput {
+                user_id: input.invitee_id.clone(),
+                group_id: None,
+                public_key: input.invite_public_key.clone(),
+                not_before: None,
+                not_after: None,
+            };
+            recipient.validate()?;
+            let mut private_key = input.credentials.unlock()?;
+            let entries = rewrap_rows(&mut OsRng, &private_key, &input.dek_entries, &recipient);
+            private_key.zeroize();
+            entries
+        });
+
+    serde_wasm_bindgen::to_value(&PendingShareResult::from_result(result)).unwrap()
+}
+
+/// Input for claiming pending shares after account setup.
+#[derive(Deserialize, Zeroize)]
+#[zeroize(drop)]
+pub struct ClaimInviteInput {
+    pub invite_code: String,
+    pub invitee_id: String,
+    /// The invitee's new public key from `encrypt_master_key_with_recovery`
+    pub public_key: Vec<u8>,
+    /// The pending `file_dek` rows wrapped to the invite key
+    #[zeroize(skip)]
+    pub dek_entries: Vec<FileDekInput>,
+}
+
+/// Re-wraps pending DEKs from the invite key to the invitee's real public key.
+///
+/// # Returns
+/// A JsValue containing `PendingShareResult` with the replacement `file_dek`
+/// rows. The pending rows and the invite should then be deleted.
+#[wasm_bindgen]
+pub fn claim_invite_wrappings(input_js: JsValue) -> JsValue {
+    log("[claim_invite_wrappings] Claiming pending shares...");
+
+    let result = serde_wasm_bindgen::from_value::<ClaimInviteInput>(input_js)
+        .map_err(|e| format!("Failed to parse input: {}", e))
+        .and_then(|input| {
+            let recipient = RecipientInput {
+                user_id: input.invitee_id.clone(),
+                group_id: None,
+                public_key: input.public_key.clone(),
+                not_before: None,
+                not_after: None,
+            };
+            let invite_secret = derive_invite_secret(&input.invite_code, &input.invitee_id)?;
+            let mut invite_private_key = invite_secret.to_bytes();
+            let entries = rewrap_rows(&mut OsRng, &invite_private_key, &input.dek_entries, &recipient)
+                .map_err(|e| format!("Failed to claim pending share ({}). Check the invite code.", e));
+            invite_private_key.zeroize();
+            entries
+        });
+
+    if let Ok(entries) = &result {
+        log(&format!("[claim_invite_wrappings] Claimed {} pending shares", entries.len()));
+    }
+
+    serde_wasm_bindgen::to_value(&PendingShareResult::from_result(result)).unwrap()
+}
@@ -0,0 +1,205 @@
+//! Progress reporting and cancellation for long-running operations.
+//!
+//! Operations that hash, encrypt or decrypt whole files take a `Progress`
+//! sink. They report `ProgressEvent`s as they go and check `aborted()`
+//! between chunks (and before and after the Argon2 step, which cannot be
+//! interrupted). An aborted operation fails with `ProgressError::Cancelled`,
+//! which results expose as the error code `ERROR_CANCELLED`. Work that is
+//! done in one call (the unchunked payload layout, the Argon2 step) reports
+//! 0 and then its total, and an abort during it takes effect afterwards.
+//!
+//! From JS the sink is a controls object, `{ on_progress, signal }`:
+//! * `on_progress(event)` receives `{ kind: "bytes", stage, processed, total }`,
+//!   `{ kind: "kdf_started" }` or `{ kind: "kdf_finished" }`
+//! * `signal` is an `AbortSignal`, or an `Int32Array` whose first element is
+//!   set non-zero to abort. The call blocks its thread, so an `AbortSignal`
+//!   can only be triggered from `on_progress`; to abort from another thread,
+//!   run the call in a worker and share an `Int32Array` over a
+//!   `SharedArrayBuffer`.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use js_sys::{Atomics, Function, Int32Array, Reflect};
+use serde::Serialize;
+use std::fmt;
+
+pub use crate::log;
+
+/// The error message of a cancelled operation
+pub const CANCELLED_MESSAGE: &str = "Operation cancelled";
+
+/// Error code for a cancelled operation
+pub const ERROR_CANCELLED: &str = "cancelled";
+/// Error code for any other failure
+pub const ERROR_FAILED: &str = "failed";
+
+/// Minimum bytes between two `Bytes` events of one stage (1 MiB)
+pub const REPORT_INTERVAL: u64 = 1024 * 1024;
+
+/// Chunk size for work that is not chunked by its format
+pub const PROGRESS_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Why an operation that reports progress failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProgressError {
+    /// The caller aborted
+    Cancelled,
+    /// Anything else, with its message
+    Failed(String),
+}
+
+impl ProgressError {
+    /// `ERROR_CANCELLED` or `ERROR_FAILED`
+    pub fn code(&self) -> &'static str {
+        match self {
+            ProgressError::Cancelled => ERROR_CANCELLED,
+            ProgressError::Failed(_) => ERROR_FAILED,
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, ProgressError::Cancelled)
+    }
+}
+
+impl fmt::Display for ProgressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgressError::Cancelled => f.write_str(CANCELLED_MESSAGE),
+            ProgressError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for ProgressError {
+    fn from(message: String) -> Self {
+        ProgressError::Failed(message)
+    }
+}
+
+impl From<ProgressError> for String {
+    fn from(error: ProgressError) -> Self {
+        error.to_string()
+    }
+}
+
+/// What an operation is doing with the bytes it reports.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Hash,
+    Encrypt,
+    Decrypt,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    Bytes { stage: Stage, processed: u64, total: u64 },
+    KdfStarted,
+    KdfFinished,
+}
+
+/// Receives progress events and decides whether to go on.
+pub trait Progress {
+    fn report(&mut self, event: ProgressEvent);
+
+    /// Whether the caller asked to stop; checked between chunks.
+    fn aborted(&mut self) -> bool;
+}
+
+/// Ignores events and never aborts.
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn report(&mut self, _event: ProgressEvent) {}
+
+    fn aborted(&mut self) -> bool {
+        false
+    }
+}
+
+/// Fails with `ProgressError::Cancelled` if the caller aborted.
+pub fn checkpoint(progress: &mut dyn Progress) -> Result<(), ProgressError> {
+    if progress.aborted() {
+        return Err(ProgressError::Cancelled);
+    }
+    Ok(())
+}
+
+/// Counts the bytes of one stage, reporting at most every `REPORT_INTERVAL`.
+pub struct ByteCounter {
+    stage: Stage,
+    processed: u64,
+    total: u64,
+    last_reported: u64,
+}
+
+impl ByteCounter {
+    /// Starts a stage of `total` bytes, reporting 0 of `total`.
+    pub fn start(progress: &mut dyn Progress, stage: Stage, total: u64) -> Result<Self, ProgressError> {
+        checkpoint(progress)?;
+        progress.report(ProgressEvent::Bytes { stage, processed: 0, total });
+        Ok(ByteCounter { stage, processed: 0, total, last_reported: 0 })
+    }
+
+    /// Records `n` more bytes, then checks for an abort.
+    pub fn advance(&mut self, progress: &mut dyn Progress, n: u64) -> Result<(), ProgressError> {
+        self.processed = (self.processed + n).min(self.total);
+        if self.processed == self.total || self.processed - self.last_reported >= REPORT_INTERVAL {
+            self.last_reported = self.processed;
+            progress.report(ProgressEvent::Bytes { stage: self.stage, processed: self.processed, total: self.total });
+        }
+        checkpoint(progress)
+    }
+}
+
+/// A `Progress` sink backed by a JS controls object.
+pub struct JsProgress {
+    on_progress: Option<Function>,
+    signal: JsValue,
+}
+
+impl JsProgress {
+    /// Reads `{ on_progress, signal }`; undefined or null means no controls.
+    pub fn from_controls(controls: &JsValue) -> Result<Self, String> {
+        if controls.is_undefined() || controls.is_null() {
+            return Ok(JsProgress { on_progress: None, signal: JsValue::UNDEFINED });
+        }
+
+        let on_progress = Reflect::get(controls, &JsValue::from_str("on_progress"))
+            .map_err(|_| "Failed to read on_progress".to_string())?;
+        let on_progress = if on_progress.is_undefined() || on_progress.is_null() {
+            None
+        } else {
+            Some(on_progress.dyn_into::<Function>().map_err(|_| "on_progress must be a function".to_string())?)
+        };
+        let signal = Reflect::get(controls, &JsValue::from_str("signal"))
+            .map_err(|_| "Failed to read signal".to_string())?;
+
+        Ok(JsProgress { on_progress, signal })
+    }
+}
+
+impl Progress for JsProgress {
+    fn report(&mut self, event: ProgressEvent) {
+        let Some(on_progress) = &self.on_progress else {
+            return;
+        };
+        if let Ok(event) = serde_wasm_bindgen::to_value(&event)
+            && on_progress.call1(&JsValue::NULL, &event).is_err()
+        {
+            log("[progress] on_progress threw; ignoring");
+        }
+    }
+
+    fn aborted(&mut self) -> bool {
+        if self.signal.is_undefined() || self.signal.is_null() {
+            return false;
+        }
+        if let Some(flags) = self.signal.dyn_ref::<Int32Array>() {
+            return Atomics::load(flags, 0).is_ok_and(|flag| flag != 0);
+        }
+        Reflect::get(&self.signal, &JsValue::from_str("aborted")).is_ok_and(|aborted| aborted.is_truthy())
+    }
+}
@@ -0,0 +1,323 @@
+//! Range reads over chunked ciphertexts.
+//!
+//! A chunked payload (see `payload_frame`) can be read a piece at a time: for
+//! a plaintext range, `RangeReader::fetch_range` names the stored bytes that
+//! hold it, and `RangeReader::decrypt_range` authenticates just those
+//! segments and returns the requested bytes. Segment 0 is read once up front,
+//! since it holds the data length.
+//!
+//! Ranges are clamped to the end of the file, as with HTTP range requests.
+//! Padding is never returned. Compressed payloads cannot be read by range.
+
+use std::ops::Range;
+
+use wasm_bindgen::prelude::*;
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroize;
+
+pub use crate::log;
+use crate::encrypt_file::FileDekInput;
+use crate::group_key::{GroupKeyEntryInput, unwrap_group_private_key};
+use crate::masterkey_decryptor::PrivateKeyCredentials;
+use crate::payload_frame::{ChunkedLayout, open_chunked_head, open_segment};
+
+const LENGTH_PREFIX_LEN: u64 = 8;
+
+/// Reads plaintext ranges of one chunked payload with an unlocked DEK.
+pub struct RangeReader {
+    dek: Vec<u8>,
+    file_nonce: Vec<u8>,
+    header: Vec<u8>,
+    layout: ChunkedLayout,
+    data_len: u64,
+}
+
+impl Drop for RangeReader {
+    fn drop(&mut self) {
+        self.dek.zeroize();
+    }
+}
+
+impl RangeReader {
+    /// Opens a payload from its first bytes (through the end of segment 0)
+    /// and its total stored size.
+    pub fn new(dek: &[u8], file_nonce: &[u8], head: &[u8], blob_len: u64) -> Result<Self, String> {
+        if dek.len() != 32 {
+            return Err(format!("DEK must be 32 bytes, got {}", dek.len()));
+        }
+        if file_nonce.len() != 12 {
+            return Err(format!("File nonce must be 12 bytes, got {}", file_nonce.len()));
+        }
+        let (layout, data_len) = open_chunked_head(dek, file_nonce, head, blob_len)?;
+
+        Ok(RangeReader {
+            dek: dek.to_vec(),
+            file_nonce: file_nonce.to_vec(),
+            header: head[..ChunkedLayout::HEADER_LEN].to_vec(),
+            layout,
+            data_len,
+        })
+    }
+
+    /// The plaintext length.
+    pub fn data_len(&self) -> u64 {
+        self.data_len
+    }
+
+    /// The plaintext range actually served for a request.
+    fn clamp(&self, offset: u64, len: u64) -> Result<Range<u64>, String> {
+        if offset > self.data_len {
+            return Err(format!("Range starts at {} beyond the end of the file ({} bytes)", offset, self.data_len));
+        }
+        Ok(offset..offset.saturating_add(len).min(self.data_len))
+    }
+
+    /// The segments holding a non-empty plaintext range.
+    fn segments(&self, range: &Range<u64>) -> Range<u64> {
+        let first = (range.start + LENGTH_PREFIX_LEN) / self.layout.segment_size;
+        let last = (range.end + LENGTH_PREFIX_LEN - 1) / self.layout.segment_size;
+        first..last + 1
+    }
+
+    /// The stored bytes to fetch for `len` plaintext bytes at `offset`.
+    ///
+    /// Empty when the clamped range is empty.
+    pub fn fetch_range(&self, offset: u64, len: u64) -> Result<Range<u64>, String> {
+        let range = self.clamp(offset, len)?;
+        if range.is_empty() {
+            return Ok(0..0);
+        }
+        let segments = self.segments(&range);
+        Ok(self.layout.segment_range(segments.start).start..self.layout.segment_range(segments.end - 1).end)
+    }
+
+    /// Decrypts `len` plaintext bytes at `offset` from `fetched`, the stored
+    /// bytes named by [`RangeReader::fetch_range`].
+    pub fn decrypt_range(&self, offset: u64, len: u64, fetched: &[u8]) -> Result<Vec<u8>, String> {
+        let range = self.clamp(offset, len)?;
+        if range.is_empty() {
+            return Ok(vec![]);
+        }
+        let fetch = self.fetch_range(offset, len)?;
+        if fetched.len() as u64 != fetch.end - fetch.start {
+            return Err(format!(
+                "Expected stored bytes {}..{} ({} bytes), got {}",
+                fetch.start,
+                fetch.end,
+                fetch.end - fetch.start,
+                fetched.len()
+            ));
+        }
+
+        let segments = self.segments(&range);
+        let mut body = Vec::with_capacity(((segments.end - segments.start) * self.layout.segment_size) as usize);
+        for index in segments.clone() {
+            let stored = self.layout.segment_range(index);
+            let segment = &fetched[(stored.start - fetch.start) as usize..(stored.end - fetch.start) as usize];
+            match open_segment(&self.dek, &self.file_nonce, &self.header, &self.layout, index, segment) {
+                Ok(mut plaintext) => {
+                    body.extend_from_slice(&plaintext);
+                    plaintext.zeroize();
+                }
+                Err(e) => {
+                    body.zeroize();
+                    return Err(e);
+                }
+            }
+        }
+
+        // `body` starts at body offset segments.start * segment_size
+        let body_start = segments.start * self.layout.segment_size;
+        let start = (range.start + LENGTH_PREFIX_LEN - body_start) as usize;
+        let end = (range.end + LENGTH_PREFIX_LEN - body_start) as usize;
+        let data = body[start..end].to_vec();
+        body.zeroize();
+        Ok(data)
+    }
+}
+
+/// Input for opening a chunked file for range reads.
+/// Passed from JavaScript via serde.
+#[derive(Deserialize)]
+pub struct RangeDecryptionInput {
+    pub credentials: PrivateKeyCredentials,
+    /// The user's `file_dek` row
+    pub dek_entry: FileDekInput,
+    /// The user's group key entry, when the DEK was wrapped to a group
+    #[serde(default)]
+    pub group_entry: Option<GroupKeyEntryInput>,
+    /// The file's `file_nonce` (bytes)
+    pub file_nonce: Vec<u8>,
+    /// The first bytes of the stored object, through the end of segment 0
+    pub head: Vec<u8>,
+    /// The stored object's total size in bytes
+    pub blob_len: f64,
+    /// The caller's clock (Unix ms); required for time-limited entries
+    #[serde(default)]
+    pub now_ms: Option<f64>,
+}
+
+fn unlock_dek(input: &RangeDecryptionInput) -> Result<Vec<u8>, String> {
+    input.dek_entry.window().check(input.now_ms.map(|ms| ms as u64))?;
+
+    let mut private_key = input.credentials.unlock()?;
+    let mut recipient_key = match &input.group_entry {
+        Some(group_entry) => {
+            let group_private_key = unwrap_group_private_key(&private_key, group_entry);
+            private_key.zeroize();
+            group_private_key?
+        }
+        None => private_key,
+    };
+
+    let dek = input.dek_entry.unwrap_dek(&recipient_key);
+    recipient_key.zeroize();
+    dek
+}
+
+/// Converts a JS number to a byte count.
+fn to_u64(value: f64, name: &str) -> Result<u64, String> {
+    if !value.is_finite() || value < 0.0 || value.fract() != 0.0 || value > (1u64 << 53) as f64 {
+        return Err(format!("{} must be a non-negative integer", name));
+    }
+    Ok(value as u64)
+}
+
+/// A chunked file opened for range reads. The DEK stays inside WASM.
+#[wasm_bindgen]
+pub struct RangeDecryptor {
+    reader: Option<RangeReader>,
+    error_message: String,
+}
+
+#[derive(Serialize)]
+pub struct RangeFetchPlan {
+    pub success: bool,
+    /// Stored byte range to fetch, end exclusive; empty for an empty range
+    pub start: u64,
+    pub end: u64,
+    pub error_message: String,
+}
+
+#[wasm_bindgen]
+pub struct DecryptedRangeResult {
+    success: bool,
+    data: Vec<u8>,
+    error_message: String,
+}
+
+#[wasm_bindgen]
+impl DecryptedRangeResult {
+    #[wasm_bindgen(getter)]
+    pub fn success(&self) -> bool {
+        self.success
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> Vec<u8> {
+        self.data.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn error_message(&self) -> String {
+        self.error_message.clone()
+    }
+}
+
+impl From<Result<Vec<u8>, String>> for DecryptedRangeResult {
+    fn from(result: Result<Vec<u8>, String>) -> Self {
+        match result {
+            Ok(data) => DecryptedRangeResult { success: true, data, error_message: String::new() },
+            Err(e) => DecryptedRangeResult { success: false, data: vec![], error_message: e },
+        }
+    }
+}
+
+/// Unlocks a chunked file's DEK for range reads.
+///
+/// # Arguments
+/// * `input_js` - A JsValue representing a `RangeDecryptionInput`
+///
+/// # Returns
+/// A RangeDecryptor; check `success` before reading. Call `free()` when done
+/// to drop the DEK.
+#[wasm_bindgen]
+pub fn open_range_decryptor(input_js: JsValue) -> RangeDecryptor {
+    log("[open_range_decryptor] Opening file for range reads...");
+
+    let reader = serde_wasm_bindgen::from_value::<RangeDecryptionInput>(input_js)
+        .map_err(|e| format!("Failed to parse input: {}", e))
+        .and_then(|input| {
+            let blob_len = to_u64(input.blob_len, "Blob length")?;
+            let mut dek = unlock_dek(&input)?;
+            let reader = RangeReader::new(&dek, &input.file_nonce, &input.head, blob_len);
+            dek.zeroize();
+            reader
+        });
+
+    match reader {
+        Ok(reader) => {
+            log(&format!("[open_range_decryptor] Ready, {} plaintext bytes", reader.data_len()));
+            RangeDecryptor { reader: Some(reader), error_message: String::new() }
+        }
+        Err(e) => {
+            log(&format!("[open_range_decryptor] Failed: {}", e));
+            RangeDecryptor { reader: None, error_message: e }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl RangeDecryptor {
+    #[wasm_bindgen(getter)]
+    pub fn success(&self) -> bool {
+        self.reader.is_some()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn error_message(&self) -> String {
+        self.error_message.clone()
+    }
+
+    /// The plaintext length in bytes
+    #[wasm_bindgen(getter)]
+    pub fn plaintext_len(&self) -> f64 {
+        self.reader.as_ref().map_or(0.0, |reader| reader.data_len() as f64)
+    }
+
+    fn reader(&self) -> Result<&RangeReader, String> {
+        self.reader.as_ref().ok_or_else(|| format!("Range decryptor is not open: {}", self.error_message))
+    }
+
+    /// Which stored bytes to fetch for `len` plaintext bytes at `offset`.
+    ///
+    /// # Returns
+    /// A JsValue containing `RangeFetchPlan`
+    pub fn fetch_range(&self, offset: f64, len: f64) -> JsValue {
+        let plan = self.reader().and_then(|reader| {
+            reader.fetch_range(to_u64(offset, "Offset")?, to_u64(len, "Length")?)
+        });
+        let result = match plan {
+            Ok(range) => RangeFetchPlan { success: true, start: range.start, end: range.end, error_message: String::new() },
+            Err(e) => RangeFetchPlan { success: false, start: 0, end: 0, error_message: e },
+        };
+        serde_wasm_bindgen::to_value(&result).unwrap()
+    }
+
+    /// Decrypts `len` plaintext bytes at `offset`.
+    ///
+    /// # Arguments
+    /// * `offset` / `len` - The plaintext range; clamped to the end of the file
+    /// * `fetched` - The stored bytes named by `fetch_range(offset, len)`
+    pub fn decrypt_range(&self, offset: f64, len: f64, fetched: &[u8]) -> DecryptedRangeResult {
+        log(&format!("[decrypt_range] Decrypting {} bytes at {}...", len, offset));
+
+        let result = self.reader().and_then(|reader| {
+            reader.decrypt_range(to_u64(offset, "Offset")?, to_u64(len, "Length")?, fetched)
+        });
+        if let Err(e) = &result {
+            log(&format!("[decrypt_range] Failed: {}", e));
+        }
+        result.into()
+    }
+}
@@ -0,0 +1,254 @@
+//! Signed recipient manifests.
+//!
+//! After `encrypt_file_multi`, the uploader signs a manifest listing who the
+//! file was encrypted to: the file hash, each recipient's id and public key
+//! fingerprint, and a hash of each wrapped DEK. A `file_dek` row that is not
+//! covered by a valid manifest was never authorised by someone with access,
+//! so `decrypt_file` can refuse it.
+//!
+//! A manifest carries its signer's public key, but verification never trusts
+//! it: callers pin the uploader's key from their own records (e.g. the key pin
+//! store), otherwise anyone could sign a manifest naming themselves.
+//!
+//! The signed bytes are a length-prefixed encoding of every field, so ids
+//! cannot be shifted between fields.
+
+use wasm_bindgen::prelude::*;
+use sha2::{Sha256, Digest};
+use x25519_dalek::{PublicKey, StaticSecret};
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroize;
+
+pub use crate::{bytes_to_hex, hex_to_bytes, log, push_length_prefixed};
+use crate::encrypt_file::RecipientInput;
+use crate::fingerprint::fingerprint_bytes;
+use crate::identity_signature::{SignatureVerificationResult, sign_with_identity_key, verify_identity_signature};
+use crate::masterkey_decryptor::PrivateKeyCredentials;
+
+const MANIFEST_LABEL: &[u8] = b"filechain-recipient-manifest-v1";
+const MANIFEST_VERSION: u32 = 1;
+
+/// One recipient covered by a manifest.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ManifestRecipient {
+    #[serde(default)]
+    pub user_id: String,
+    #[serde(default)]
+    pub group_id: Option<String>,
+    pub fingerprint_hex: String,
+    /// SHA-256 over encrypted_dek || dek_nonce || ephemeral_public_key
+    pub wrapped_dek_hash_hex: String,
+}
+
+/// A signed list of the recipients a file was encrypted to.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RecipientManifest {
+    pub version: u32,
+    pub file_hash: String,
+    pub signer_id: String,
+    pub signer_public_key_hex: String,
+    pub timestamp_ms: u64,
+    pub recipients: Vec<ManifestRecipient>,
+    pub signature_hex: String,
+}
+
+/// Hashes a wrapped DEK the way manifests record it.
+pub fn wrapped_dek_hash(encrypted_dek: &[u8], dek_nonce: &[u8], ephemeral_public_key: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(encrypted_dek);
+    hasher.update(dek_nonce);
+    hasher.update(ephemeral_public_key);
+    bytes_to_hex(&hasher.finalize())
+}
+
+impl RecipientManifest {
+    /// The canonical bytes covered by `signature_hex`.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        push_length_prefixed(&mut out, MANIFEST_LABEL);
+        out.extend_from_slice(&self.version.to_be_bytes());
+        push_length_prefixed(&mut out, self.file_hash.as_bytes());
+        push_length_prefixed(&mut out, self.signer_id.as_bytes());
+        push_length_prefixed(&mut out, self.signer_public_key_hex.as_bytes());
+        out.extend_from_slice(&self.timestamp_ms.to_be_bytes());
+        out.extend_from_slice(&(self.recipients.len() as u32).to_be_bytes());
+        for recipient in &self.recipients {
+            push_length_prefixed(&mut out, recipient.user_id.as_bytes());
+            push_length_prefixed(&mut out, recipient.group_id.as_deref().unwrap_or("").as_bytes());
+            push_length_prefixed(&mut out, recipient.fingerprint_hex.as_bytes());
+            push_length_prefixed(&mut out, recipient.wrapped_dek_hash_hex.as_bytes());
+        }
+        out
+    }
+
+    /// Checks the version and the signer's signature.
+    pub fn verify_signature(&self) -> Result<(), String> {
+        if self.version != MANIFEST_VERSION {
+            return Err(format!("Unsupported manifest version {}", self.version));
+        }
+        let signer_public_key = hex_to_bytes(&self.signer_public_key_hex)?;
+        let signature = hex_to_bytes(&self.signature_hex)?;
+        verify_identity_signature(&signer_public_key, &self.signing_bytes(), &signature)
+            .map_err(|e| format!("Manifest signature invalid: {}", e))
+    }
+
+    /// Checks that the manifest was signed by `expected_signer_public_key_hex`,
+    /// the uploader's key as the caller knows it.
+    pub fn verify_signer(&self, expected_signer_public_key_hex: &str) -> Result<(), String> {
+        if expected_signer_public_key_hex.is_empty() {
+            return Err("The expected manifest signer key is required".to_string());
+        }
+        if !self.signer_public_key_hex.eq_ignore_ascii_case(expected_signer_public_key_hex) {
+            return Err("Manifest was signed by an unexpected key".to_string());
+        }
+        self.verify_signature()
+    }
+
+    /// Checks that a wrapped DEK is covered by the manifest for `user_id`
+    /// (or for a group, when `via_group` is set).
+    pub fn authorises(
+        &self,
+        user_id: &str,
+        via_group: bool,
+        encrypted_dek: &[u8],
+        dek_nonce: &[u8],
+        ephemeral_public_key: &[u8],
+    ) -> Result<(), String> {
+        let hash = wrapped_dek_hash(encrypted_dek, dek_nonce, ephemeral_public_key);
+        let covered = self.recipients.iter().any(|recipient| {
+            recipient.wrapped_dek_hash_hex == hash
+                && if via_group { recipient.group_id.is_some() } else { recipient.user_id == user_id }
+        });
+
+        if covered {
+            Ok(())
+        } else {
+            Err("DEK entry is not covered by the recipient manifest".to_string())
+        }
+    }
+}
+
+/// A `dek_entries` item as returned by `encrypt_file_multi` (hex fields).
+#[derive(Deserialize)]
+pub struct ManifestDekEntryInput {
+    #[serde(default)]
+    pub user_id: String,
+    #[serde(default)]
+    pub group_id: Option<String>,
+    pub encrypted_dek_hex: String,
+    pub dek_nonce_hex: String,
+    pub ephemeral_public_key_hex: String,
+}
+
+/// Input for signing a recipient manifest.
+#[derive(Deserialize)]
+pub struct CreateManifestInput {
+    /// The signer's own credentials
+    pub credentials: PrivateKeyCredentials,
+    pub signer_id: String,
+    /// `original_hash_hex` from `encrypt_file_multi`
+    pub file_hash: String,
+    /// Unix timestamp in milliseconds (from Date.now())
+    pub timestamp_ms: f64,
+    /// The recipients passed to `encrypt_file_multi`
+    pub recipients: Vec<RecipientInput>,
+    /// The `dek_entries` returned by `encrypt_file_multi`
+    pub dek_entries: Vec<ManifestDekEntryInput>,
+}
+
+#[derive(Serialize)]
+pub struct CreateManifestResult {
+    pub success: bool,
+    pub manifest: Option<RecipientManifest>,
+    pub error_message: String,
+}
+
+/// Signs a manifest of the recipients a file was encrypted to.
+///
+/// # Arguments
+/// * `input_js` - A JsValue representing a `CreateManifestInput`
+///
+/// # Returns
+/// A JsValue containing `CreateManifestResult`. The manifest is stored next to
+/// the file metadata and passed back to `decrypt_file` for verification.
+#[wasm_bindgen]
+pub fn create_recipient_manifest(input_js: JsValue) -> JsValue {
+    log("[create_recipient_manifest] Signing recipient manifest...");
+
+    let result = match serde_wasm_bindgen::from_value::<CreateManifestInput>(input_js)
+        .map_err(|e| format!("Failed to parse input: {}", e))
+        .and_then(|input| create_manifest(&input))
+    {
+        Ok(manifest) => CreateManifestResult { success: true, manifest: Some(manifest), error_message: String::new() },
+        Err(e) => {
+            log(&format!("[create_recipient_manifest] Failed: {}", e));
+            CreateManifestResult { success: false, manifest: None, error_message: e }
+        }
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// The core of [`create_recipient_manifest`] for a parsed input.
+pub fn create_manifest(input: &CreateManifestInput) -> Result<RecipientManifest, String> {
+    if input.signer_id.is_empty() {
+        return Err("Signer ID cannot be empty".to_string());
+    }
+    if input.file_hash.is_empty() {
+        return Err("File hash cannot be empty".to_string());
+    }
+
+    let mut recipients = Vec::with_capacity(input.dek_entries.len());
+    for entry in &input.dek_entries {
+        let recipient = input
+            .recipients
+            .iter()
+            .find(|r| r.user_id == entry.user_id && r.group_id == entry.group_id)
+            .ok_or_else(|| format!("No public key given for DEK entry of {}", entry.user_id))?;
+
+        let id = recipient.label();
+        recipients.push(ManifestRecipient {
+            user_id: entry.user_id.clone(),
+            group_id: entry.group_id.clone(),
+            fingerprint_hex: bytes_to_hex(&fingerprint_bytes(id, &recipient.public_key)?),
+            wrapped_dek_hash_hex: wrapped_dek_hash(
+                &hex_to_bytes(&entry.encrypted_dek_hex)?,
+                &hex_to_bytes(&entry.dek_nonce_hex)?,
+                &hex_to_bytes(&entry.ephemeral_public_key_hex)?,
+            ),
+        });
+    }
+
+    let mut private_key = input.credentials.unlock()?;
+    let private_array: [u8; 32] = private_key.as_slice().try_into().unwrap();
+    let signer_public = PublicKey::from(&StaticSecret::from(private_array));
+
+    let mut manifest = RecipientManifest {
+        version: MANIFEST_VERSION,
+        file_hash: input.file_hash.clone(),
+        signer_id: input.signer_id.clone(),
+        signer_public_key_hex: bytes_to_hex(signer_public.as_bytes()),
+        timestamp_ms: input.timestamp_ms as u64,
+        recipients,
+        signature_hex: String::new(),
+    };
+
+    let signature = sign_with_identity_key(&private_key, &manifest.signing_bytes());
+    private_key.zeroize();
+    manifest.signature_hex = bytes_to_hex(&signature?);
+
+    Ok(manifest)
+}
+
+/// Verifies a manifest's signature against the uploader's pinned key.
+///
+/// # Arguments
+/// * `manifest_js` - A JsValue representing a `RecipientManifest`
+/// * `expected_signer_public_key_hex` - The uploader's known public key (required)
+#[wasm_bindgen]
+pub fn verify_recipient_manifest(manifest_js: JsValue, expected_signer_public_key_hex: &str) -> SignatureVerificationResult {
+    serde_wasm_bindgen::from_value::<RecipientManifest>(manifest_js)
+        .map_err(|e| format!("Failed to parse manifest: {}", e))
+        .and_then(|manifest| manifest.verify_signer(expected_signer_public_key_hex))
+        .into()
+}
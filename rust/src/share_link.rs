@@ -0,0 +1,209 @@
+//! Link-based sharing with the key carried in the URL fragment.
+//!
+//! A random 32-byte link secret wraps the file DEK. The wrapped DEK is stored
+//! server-side under a random link id; the secret is base64url-encoded for the
+//! URL fragment (`/s/{link_id}#{link_secret}`), which browsers never send to
+//! the server. The wrapping key is HKDF-SHA256 of the secret, with the label
+//! and link id length-prefixed into `info`; the link id and file nonce are
+//! bound as AAD.
+
+use wasm_bindgen::prelude::*;
+use aes_gcm::aead::{OsRng, rand_core::{CryptoRng, RngCore}};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroize;
+
+pub use crate::{bytes_to_hex, hex_to_bytes, log, push_length_prefixed};
+use crate::decrypt_file::{DecryptedFileResult, decrypt_with_dek};
+use crate::encrypt_file::{unwrap_dek_with_private_key, unwrap_key_with_key, wrap_key_with_key_with_rng};
+use crate::masterkey_decryptor::PrivateKeyCredentials;
+
+const LINK_LABEL: &[u8] = b"filechain-share-link-v1";
+
+/// The server-side record for a share link. Holds nothing that can open the
+/// file without the link secret.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ShareLinkRecord {
+    pub link_id: String,
+    pub file_nonce_hex: String,
+    pub wrapped_dek_hex: String,
+    pub wrapped_dek_nonce_hex: String,
+}
+
+impl ShareLinkRecord {
+    fn aad(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        push_length_prefixed(&mut out, LINK_LABEL);
+        push_length_prefixed(&mut out, self.link_id.as_bytes());
+        push_length_prefixed(&mut out, self.file_nonce_hex.as_bytes());
+        out
+    }
+
+    /// Unwraps the file DEK with the base64url link secret from the URL fragment.
+    pub fn unwrap_dek(&self, link_secret: &str) -> Result<Vec<u8>, String> {
+        let mut secret = URL_SAFE_NO_PAD
+            .decode(link_secret.trim_start_matches('#'))
+            .map_err(|_| "Link secret is not valid base64url".to_string())?;
+        let wrapping_key = derive_link_key(&secret, &self.link_id);
+        secret.zeroize();
+        let mut wrapping_key = wrapping_key?;
+
+        let dek = unwrap_key_with_key(
+            &wrapping_key,
+            &hex_to_bytes(&self.wrapped_dek_hex)?,
+            &hex_to_bytes(&self.wrapped_dek_nonce_hex)?,
+            &self.aad(),
+        );
+        wrapping_key.zeroize();
+        dek.map_err(|_| "Failed to open share link. The link may be incomplete.".to_string())
+    }
+}
+
+/// Derives the DEK wrapping key from a link secret.
+fn derive_link_key(secret: &[u8], link_id: &str) -> Result<Vec<u8>, String> {
+    if secret.len() != 32 {
+        return Err(format!("Link secret must be 32 bytes, got {}", secret.len()));
+    }
+    let mut info = Vec::new();
+    push_length_prefixed(&mut info, LINK_LABEL);
+    push_length_prefixed(&mut info, link_id.as_bytes());
+
+    let mut key = vec![0u8; 32];
+    Hkdf::<Sha256>::new(None, secret)
+        .expand(&info, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Input for creating a share link from the sharer's own `file_dek` row.
+#[derive(Deserialize)]
+pub struct CreateShareLinkInput {
+    pub credentials: PrivateKeyCredentials,
+    pub encrypted_dek: Vec<u8>,
+    pub dek_nonce: Vec<u8>,
+    pub ephemeral_public_key: Vec<u8>,
+    /// The file's `file_nonce` (bytes)
+    pub file_nonce: Vec<u8>,
+}
+
+#[derive(Serialize)]
+pub struct ShareLinkResult {
+    pub success: bool,
+    /// Store this server-side under `record.link_id`
+    pub record: Option<ShareLinkRecord>,
+    /// Put this in the URL fragment only; never send it to the server
+    pub link_secret: String,
+    pub error_message: String,
+}
+
+/// Creates a link secret and the matching wrapped-DEK record.
+///
+/// # Arguments
+/// * `input_js` - A JsValue representing a `CreateShareLinkInput`
+///
+/// # Returns
+/// A JsValue containing `ShareLinkResult`
+#[wasm_bindgen]
+pub fn create_share_link(input_js: JsValue) -> JsValue {
+    log("[create_share_link] Creating share link...");
+
+    let result = match serde_wasm_bindgen::from_value::<CreateShareLinkInput>(input_js)
+        .map_err(|e| format!("Failed to parse input: {}", e))
+        .and_then(|input| create_link_with_rng(&mut OsRng, &input))
+    {
+        Ok((record, link_secret)) => ShareLinkResult {
+            success: true,
+            record: Some(record),
+            link_secret,
+            error_message: String::new(),
+        },
+        Err(e) => {
+            log(&format!("[create_share_link] Failed: {}", e));
+            ShareLinkResult { success: false, record: None, link_secret: String::new(), error_message: e }
+        }
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// The core of [`create_share_link`], with the link id, secret and nonce drawn from `rng`.
+///
+/// Returns the record and the link secret.
+pub fn create_link_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    input: &CreateShareLinkInput,
+) -> Result<(ShareLinkRecord, String), String> {
+    if input.file_nonce.len() != 12 {
+        return Err(format!("File nonce must be 12 bytes, got {}", input.file_nonce.len()));
+    }
+
+    let mut private_key = input.credentials.unlock()?;
+    let dek = unwrap_dek_with_private_key(
+        &private_key,
+        &input.ephemeral_public_key,
+        &input.dek_nonce,
+        &input.encrypted_dek,
+    );
+    private_key.zeroize();
+    let mut dek = dek?;
+
+    let mut link_id_bytes = [0u8; 16];
+    rng.fill_bytes(&mut link_id_bytes);
+    let mut secret = [0u8; 32];
+    rng.fill_bytes(&mut secret);
+
+    let mut record = ShareLinkRecord {
+        link_id: URL_SAFE_NO_PAD.encode(link_id_bytes),
+        file_nonce_hex: bytes_to_hex(&input.file_nonce),
+        wrapped_dek_hex: String::new(),
+        wrapped_dek_nonce_hex: String::new(),
+    };
+
+    let wrapped = derive_link_key(&secret, &record.link_id).and_then(|mut wrapping_key| {
+        let wrapped = wrap_key_with_key_with_rng(rng, &wrapping_key, &dek, &record.aad());
+        wrapping_key.zeroize();
+        wrapped
+    });
+    dek.zeroize();
+    let link_secret = URL_SAFE_NO_PAD.encode(secret);
+    secret.zeroize();
+    let (wrapped_dek, wrapped_dek_nonce) = wrapped?;
+
+    record.wrapped_dek_hex = bytes_to_hex(&wrapped_dek);
+    record.wrapped_dek_nonce_hex = bytes_to_hex(&wrapped_dek_nonce);
+
+    log("[create_share_link] Share link created");
+    Ok((record, link_secret))
+}
+
+/// Decrypts a file shared by link.
+///
+/// # Arguments
+/// * `record_js` - A JsValue representing the `ShareLinkRecord` fetched by link id
+/// * `link_secret` - The base64url secret from the URL fragment
+/// * `encrypted_data` - The encrypted file blob
+///
+/// # Returns
+/// DecryptedFileResult containing decrypted data and its hash for verification
+#[wasm_bindgen]
+pub fn open_share_link(record_js: JsValue, link_secret: &str, encrypted_data: &[u8]) -> DecryptedFileResult {
+    log("[open_share_link] Opening share link...");
+
+    let result = serde_wasm_bindgen::from_value::<ShareLinkRecord>(record_js)
+        .map_err(|e| format!("Failed to parse share link record: {}", e))
+        .and_then(|record| {
+            let mut dek = record.unwrap_dek(link_secret)?;
+            let decrypted = hex_to_bytes(&record.file_nonce_hex)
+                .and_then(|file_nonce| decrypt_with_dek(&dek, &file_nonce, encrypted_data));
+            dek.zeroize();
+            decrypted
+        });
+
+    if let Err(e) = &result {
+        log(&format!("[open_share_link] Failed: {}", e));
+    }
+
+    DecryptedFileResult::from_decryption(result)
+}
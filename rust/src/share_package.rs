@@ -0,0 +1,275 @@
+//! Password-protected share packages for recipients without an account.
+//!
+//! The file DEK is wrapped under a key derived from a share passphrase with
+//! Argon2id. The package carries everything needed to open it (KDF parameters,
+//! salt, expiry, file metadata and the file nonce); all of it is bound into the
+//! AES-GCM AAD, so none of it can be changed without breaking the wrapping.
+//! The encrypted blob itself is sent alongside the package.
+//!
+//! Unlike `get_key_encryption_key`, no pepper is mixed in, so any
+//! implementation that knows this format can open a package.
+
+use wasm_bindgen::prelude::*;
+use aes_gcm::aead::{OsRng, rand_core::{CryptoRng, RngCore}};
+use argon2::{Argon2, Algorithm, Version, Params, password_hash::SaltString};
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroize;
+
+pub use crate::{bytes_to_hex, hex_to_bytes, log, push_length_prefixed};
+use crate::decrypt_file::{DecryptedFileResult, decrypt_with_dek};
+use crate::encrypt_file::{unwrap_dek_with_private_key, unwrap_key_with_key, wrap_key_with_key_with_rng};
+use crate::masterkey_decryptor::PrivateKeyCredentials;
+
+const PACKAGE_LABEL: &[u8] = b"filechain-share-package-v1";
+const PACKAGE_VERSION: u32 = 1;
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+// Same cost as the account KEK
+const KDF_MEMORY_KIB: u32 = 65536;
+const KDF_ITERATIONS: u32 = 3;
+const KDF_PARALLELISM: u32 = 1;
+
+// Upper bounds accepted when opening, so a crafted package cannot exhaust memory
+const MAX_KDF_MEMORY_KIB: u32 = 1 << 20;
+const MAX_KDF_ITERATIONS: u32 = 16;
+const MAX_KDF_PARALLELISM: u32 = 8;
+
+/// File details shown to the recipient before download.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ShareFileMetadata {
+    pub file_name: String,
+    pub mime_type: String,
+    pub size: u64,
+    /// SHA-256 hex of the original file
+    pub file_hash: String,
+}
+
+/// A standalone share package.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SharePackage {
+    pub version: u32,
+    pub kdf_memory_kib: u32,
+    pub kdf_iterations: u32,
+    pub kdf_parallelism: u32,
+    pub salt: String,
+    /// Unix timestamp in milliseconds after which the package is refused
+    pub expires_at_ms: u64,
+    pub metadata: ShareFileMetadata,
+    pub file_nonce_hex: String,
+    pub wrapped_dek_hex: String,
+    pub wrapped_dek_nonce_hex: String,
+}
+
+impl SharePackage {
+    /// Everything except the wrapped DEK, bound as AAD.
+    fn aad(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        push_length_prefixed(&mut out, PACKAGE_LABEL);
+        out.extend_from_slice(&self.version.to_be_bytes());
+        out.extend_from_slice(&self.kdf_memory_kib.to_be_bytes());
+        out.extend_from_slice(&self.kdf_iterations.to_be_bytes());
+        out.extend_from_slice(&self.kdf_parallelism.to_be_bytes());
+        push_length_prefixed(&mut out, self.salt.as_bytes());
+        out.extend_from_slice(&self.expires_at_ms.to_be_bytes());
+        push_length_prefixed(&mut out, self.metadata.file_name.as_bytes());
+        push_length_prefixed(&mut out, self.metadata.mime_type.as_bytes());
+        out.extend_from_slice(&self.metadata.size.to_be_bytes());
+        push_length_prefixed(&mut out, self.metadata.file_hash.as_bytes());
+        push_length_prefixed(&mut out, self.file_nonce_hex.as_bytes());
+        out
+    }
+
+    fn derive_key(&self, passphrase: &str) -> Result<Vec<u8>, String> {
+        derive_share_key(passphrase, &self.salt, self.kdf_memory_kib, self.kdf_iterations, self.kdf_parallelism)
+    }
+
+    /// Unwraps the DEK, refusing expired packages.
+    pub fn unwrap_dek(&self, passphrase: &str, now_ms: u64) -> Result<Vec<u8>, String> {
+        if self.version != PACKAGE_VERSION {
+            return Err(format!("Unsupported share package version {}", self.version));
+        }
+        if now_ms > self.expires_at_ms {
+            return Err("Share package has expired".to_string());
+        }
+        if self.kdf_memory_kib > MAX_KDF_MEMORY_KIB
+            || self.kdf_iterations > MAX_KDF_ITERATIONS
+            || self.kdf_parallelism > MAX_KDF_PARALLELISM
+        {
+            return Err("Share package KDF parameters are out of range".to_string());
+        }
+
+        let mut share_key = self.derive_key(passphrase)?;
+        let dek = unwrap_key_with_key(
+            &share_key,
+            &hex_to_bytes(&self.wrapped_dek_hex)?,
+            &hex_to_bytes(&self.wrapped_dek_nonce_hex)?,
+            &self.aad(),
+        );
+        share_key.zeroize();
+        dek.map_err(|_| "Failed to open share package. Check the passphrase.".to_string())
+    }
+
+    /// Decrypts the shared file and checks it against the package metadata.
+    pub fn open(&self, passphrase: &str, now_ms: u64, encrypted_data: &[u8]) -> Result<Vec<u8>, String> {
+        let mut dek = self.unwrap_dek(passphrase, now_ms)?;
+        let decrypted = decrypt_with_dek(&dek, &hex_to_bytes(&self.file_nonce_hex)?, encrypted_data);
+        dek.zeroize();
+        let decrypted = decrypted?;
+
+        if !crate::hash_file(&decrypted).eq_ignore_ascii_case(&self.metadata.file_hash) {
+            return Err("Decrypted file does not match the package file hash".to_string());
+        }
+        Ok(decrypted)
+    }
+}
+
+/// Derives a 32-byte share key with Argon2id (no pepper).
+pub fn derive_share_key(
+    passphrase: &str,
+    salt: &str,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+) -> Result<Vec<u8>, String> {
+    let params = Params::new(memory_kib, iterations, parallelism, Some(32))
+        .map_err(|e| format!("Invalid KDF parameters: {}", e))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+    let mut key = vec![0u8; 32];
+    argon2
+        .hash_password_into(passphrase.as_bytes(), salt.as_bytes(), &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Input for creating a share package from the sharer's own `file_dek` row.
+#[derive(Deserialize, Zeroize)]
+#[zeroize(drop)]
+pub struct CreateSharePackageInput {
+    #[zeroize(skip)]
+    pub credentials: PrivateKeyCredentials,
+    pub encrypted_dek: Vec<u8>,
+    pub dek_nonce: Vec<u8>,
+    pub ephemeral_public_key: Vec<u8>,
+    /// The file's `file_nonce` (bytes)
+    pub file_nonce: Vec<u8>,
+    /// The passphrase given to the external recipient
+    pub passphrase: String,
+    /// Unix timestamp in milliseconds after which the package is refused
+    pub expires_at_ms: f64,
+    #[zeroize(skip)]
+    pub metadata: ShareFileMetadata,
+}
+
+#[derive(Serialize)]
+pub struct SharePackageResult {
+    pub success: bool,
+    pub package: Option<SharePackage>,
+    pub error_message: String,
+}
+
+/// Creates a password-protected share package for an external recipient.
+///
+/// # Arguments
+/// * `input_js` - A JsValue representing a `CreateSharePackageInput`
+///
+/// # Returns
+/// A JsValue containing `SharePackageResult`
+#[wasm_bindgen]
+pub fn create_share_package(input_js: JsValue) -> JsValue {
+    log("[create_share_package] Creating share package...");
+
+    let result = match serde_wasm_bindgen::from_value::<CreateSharePackageInput>(input_js)
+        .map_err(|e| format!("Failed to parse input: {}", e))
+        .and_then(|input| create_package_with_rng(&mut OsRng, &input))
+    {
+        Ok(package) => SharePackageResult { success: true, package: Some(package), error_message: String::new() },
+        Err(e) => {
+            log(&format!("[create_share_package] Failed: {}", e));
+            SharePackageResult { success: false, package: None, error_message: e }
+        }
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// The core of [`create_share_package`], with the salt and nonce drawn from `rng`.
+pub fn create_package_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    input: &CreateSharePackageInput,
+) -> Result<SharePackage, String> {
+    if input.passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!("Passphrase must be at least {} characters", MIN_PASSPHRASE_LEN));
+    }
+    if input.file_nonce.len() != 12 {
+        return Err(format!("File nonce must be 12 bytes, got {}", input.file_nonce.len()));
+    }
+
+    let mut private_key = input.credentials.unlock()?;
+    let dek = unwrap_dek_with_private_key(
+        &private_key,
+        &input.ephemeral_public_key,
+        &input.dek_nonce,
+        &input.encrypted_dek,
+    );
+    private_key.zeroize();
+    let mut dek = dek?;
+
+    let mut package = SharePackage {
+        version: PACKAGE_VERSION,
+        kdf_memory_kib: KDF_MEMORY_KIB,
+        kdf_iterations: KDF_ITERATIONS,
+        kdf_parallelism: KDF_PARALLELISM,
+        salt: SaltString::generate(&mut *rng).as_str().to_string(),
+        expires_at_ms: input.expires_at_ms as u64,
+        metadata: input.metadata.clone(),
+        file_nonce_hex: bytes_to_hex(&input.file_nonce),
+        wrapped_dek_hex: String::new(),
+        wrapped_dek_nonce_hex: String::new(),
+    };
+
+    let share_key = package.derive_key(&input.passphrase);
+    let wrapped = share_key.and_then(|mut share_key| {
+        let wrapped = wrap_key_with_key_with_rng(rng, &share_key, &dek, &package.aad());
+        share_key.zeroize();
+        wrapped
+    });
+    dek.zeroize();
+    let (wrapped_dek, wrapped_dek_nonce) = wrapped?;
+
+    package.wrapped_dek_hex = bytes_to_hex(&wrapped_dek);
+    package.wrapped_dek_nonce_hex = bytes_to_hex(&wrapped_dek_nonce);
+
+    log("[create_share_package] Share package created");
+    Ok(package)
+}
+
+/// Opens a share package and decrypts the shared file.
+///
+/// # Arguments
+/// * `package_js` - A JsValue representing a `SharePackage`
+/// * `passphrase` - The share passphrase
+/// * `now_ms` - The current Unix timestamp in milliseconds (from Date.now())
+/// * `encrypted_data` - The encrypted file blob
+///
+/// # Returns
+/// DecryptedFileResult containing decrypted data and its hash for verification
+#[wasm_bindgen]
+pub fn open_share_package(
+    package_js: JsValue,
+    passphrase: &str,
+    now_ms: f64,
+    encrypted_data: &[u8],
+) -> DecryptedFileResult {
+    log("[open_share_package] Opening share package...");
+
+    let result = serde_wasm_bindgen::from_value::<SharePackage>(package_js)
+        .map_err(|e| format!("Failed to parse share package: {}", e))
+        .and_then(|package| package.open(passphrase, now_ms as u64, encrypted_data));
+
+    if let Err(e) = &result {
+        log(&format!("[open_share_package] Failed: {}", e));
+    }
+
+    DecryptedFileResult::from_decryption(result)
+}
@@ -0,0 +1,334 @@
+//! Proof-of-storage audits for chunked ciphertexts.
+//!
+//! At upload time `create_storage_audit` precomputes one-time challenges over
+//! segments of a chunked ciphertext (see `payload_frame`), under a secret
+//! 32-byte audit key held by whoever runs the audits:
+//!
+//!   challenge_key_i = HMAC-SHA256(audit_key, KEY_LABEL || audit_nonce || i)
+//!   segment_i       = HMAC-SHA256(audit_key, POSITION_LABEL || audit_nonce || i) mod segment_count
+//!   token_i         = HMAC-SHA256(challenge_key_i, i || segment_i || stored segment bytes)
+//!
+//! with i and segment_i as u64 BE. The record kept next to the file metadata
+//! holds only the audit nonce, the layout and the tokens, so it does not say
+//! which segments will be asked for. To audit, the verifier opens challenge i
+//! with the audit key and hands the byte range and challenge key to a
+//! responder next to the bucket, which HMACs the stored bytes
+//! (`respond_to_storage_challenge`); the response must equal token i.
+//!
+//! Each challenge is single use: once its key is revealed the provider could
+//! keep the answer and drop the segment. Use them in order and create a new
+//! record when they run out.
+
+use wasm_bindgen::prelude::*;
+use aes_gcm::aead::{OsRng, rand_core::{CryptoRng, RngCore}};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use serde::{Serialize, Deserialize};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+
+pub use crate::{bytes_to_hex, hex_to_bytes, log};
+use crate::identity_signature::SignatureVerificationResult;
+use crate::payload_frame::ChunkedLayout;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const KEY_LABEL: &[u8] = b"filechain-storage-audit-key-v1";
+const POSITION_LABEL: &[u8] = b"filechain-storage-audit-position-v1";
+const AUDIT_VERSION: u32 = 1;
+
+/// Most challenges a single record may hold
+pub const MAX_CHALLENGES: u32 = 1024;
+
+fn hmac(key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+fn parse_audit_key(audit_key_hex: &str) -> Result<Vec<u8>, String> {
+    let key = hex_to_bytes(audit_key_hex)?;
+    if key.len() != 32 {
+        return Err(format!("Audit key must be 32 bytes, got {}", key.len()));
+    }
+    Ok(key)
+}
+
+/// Precomputed challenges for one stored ciphertext.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StorageAuditRecord {
+    pub version: u32,
+    pub audit_nonce_hex: String,
+    /// Layout of the audited ciphertext (see `ChunkedLayout`)
+    pub segment_size: u64,
+    pub segment_count: u64,
+    pub blob_len: u64,
+    /// `token_i` for each challenge, in order
+    pub tokens: Vec<String>,
+}
+
+impl StorageAuditRecord {
+    fn layout(&self) -> ChunkedLayout {
+        ChunkedLayout { segment_size: self.segment_size, segment_count: self.segment_count, blob_len: self.blob_len }
+    }
+
+    fn challenge_key(&self, audit_key: &[u8], index: u64) -> Result<[u8; 32], String> {
+        let nonce = hex_to_bytes(&self.audit_nonce_hex)?;
+        Ok(hmac(audit_key, &[KEY_LABEL, &nonce, &index.to_be_bytes()]))
+    }
+
+    fn segment_index(&self, audit_key: &[u8], index: u64) -> Result<u64, String> {
+        let nonce = hex_to_bytes(&self.audit_nonce_hex)?;
+        let position = hmac(audit_key, &[POSITION_LABEL, &nonce, &index.to_be_bytes()]);
+        Ok(u64::from_be_bytes(position[..8].try_into().unwrap()) % self.segment_count)
+    }
+
+    /// Reveals challenge `index`: which bytes to prove and the key to prove them with.
+    pub fn challenge(&self, audit_key: &[u8], index: u32) -> Result<StorageChallenge, String> {
+        if self.version != AUDIT_VERSION {
+            return Err(format!("Unsupported audit record version {}", self.version));
+        }
+        if index as usize >= self.tokens.len() {
+            return Err(format!("Challenge {} out of range for {} challenges", index, self.tokens.len()));
+        }
+        self.open(audit_key, index)
+    }
+
+    fn open(&self, audit_key: &[u8], index: u32) -> Result<StorageChallenge, String> {
+        if self.segment_count == 0 {
+            return Err("Audit record has no segments".to_string());
+        }
+
+        let segment_index = self.segment_index(audit_key, index as u64)?;
+        let range = self.layout().segment_range(segment_index);
+        let mut challenge_key = self.challenge_key(audit_key, index as u64)?;
+        let challenge = StorageChallenge {
+            challenge_index: index,
+            segment_index,
+            offset: range.start,
+            length: range.end - range.start,
+            challenge_key_hex: bytes_to_hex(&challenge_key),
+        };
+        challenge_key.zeroize();
+        Ok(challenge)
+    }
+
+    /// Checks a responder's answer to challenge `index` against its token.
+    pub fn verify_response(&self, index: u32, response: &[u8]) -> Result<(), String> {
+        let token = self
+            .tokens
+            .get(index as usize)
+            .ok_or_else(|| format!("Challenge {} out of range for {} challenges", index, self.tokens.len()))?;
+        let token = hex_to_bytes(token)?;
+        if token.len() != 32 || !bool::from(token.ct_eq(response)) {
+            return Err(format!("Storage challenge {} failed: the stored segment is missing or altered", index));
+        }
+        Ok(())
+    }
+}
+
+/// One opened challenge, sent to the responder.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StorageChallenge {
+    pub challenge_index: u32,
+    pub segment_index: u64,
+    /// Byte range of the segment within the stored object
+    pub offset: u64,
+    pub length: u64,
+    pub challenge_key_hex: String,
+}
+
+impl StorageChallenge {
+    /// HMACs the stored bytes at `offset..offset + length`.
+    pub fn respond(&self, segment: &[u8]) -> Result<[u8; 32], String> {
+        if segment.len() as u64 != self.length {
+            return Err(format!("Segment must be {} bytes, got {}", self.length, segment.len()));
+        }
+        let challenge_key = hex_to_bytes(&self.challenge_key_hex)?;
+        Ok(hmac(
+            &challenge_key,
+            &[&(self.challenge_index as u64).to_be_bytes(), &self.segment_index.to_be_bytes(), segment],
+        ))
+    }
+}
+
+/// The core of [`create_storage_audit`], drawing the audit nonce from `rng`.
+pub fn create_storage_audit_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    encrypted_data: &[u8],
+    audit_key: &[u8],
+    challenge_count: u32,
+) -> Result<StorageAuditRecord, String> {
+    if audit_key.len() != 32 {
+        return Err(format!("Audit key must be 32 bytes, got {}", audit_key.len()));
+    }
+    if challenge_count == 0 || challenge_count > MAX_CHALLENGES {
+        return Err(format!("Challenge count must be between 1 and {}", MAX_CHALLENGES));
+    }
+    let layout = ChunkedLayout::parse(encrypted_data, encrypted_data.len() as u64)?;
+
+    let mut audit_nonce = [0u8; 16];
+    rng.fill_bytes(&mut audit_nonce);
+
+    let mut record = StorageAuditRecord {
+        version: AUDIT_VERSION,
+        audit_nonce_hex: bytes_to_hex(&audit_nonce),
+        segment_size: layout.segment_size,
+        segment_count: layout.segment_count,
+        blob_len: layout.blob_len,
+        tokens: Vec::with_capacity(challenge_count as usize),
+    };
+
+    for index in 0..challenge_count {
+        let challenge = record.open(audit_key, index)?;
+        let segment = &encrypted_data[challenge.offset as usize..(challenge.offset + challenge.length) as usize];
+        let token = challenge.respond(segment)?;
+        record.tokens.push(bytes_to_hex(&token));
+    }
+
+    Ok(record)
+}
+
+#[derive(Serialize)]
+pub struct StorageAuditResult {
+    pub success: bool,
+    pub record: Option<StorageAuditRecord>,
+    pub error_message: String,
+}
+
+#[derive(Serialize)]
+pub struct StorageChallengeResult {
+    pub success: bool,
+    pub challenge: Option<StorageChallenge>,
+    pub error_message: String,
+}
+
+#[derive(Serialize)]
+pub struct StorageResponseResult {
+    pub success: bool,
+    pub response_hex: String,
+    pub error_message: String,
+}
+
+/// Generates a random 32-byte audit key (hex). Keep it away from the storage provider.
+#[wasm_bindgen]
+pub fn generate_audit_key() -> String {
+    let mut key = [0u8; 32];
+    OsRng.fill_bytes(&mut key);
+    let key_hex = bytes_to_hex(&key);
+    key.zeroize();
+    key_hex
+}
+
+/// Precomputes storage challenges for a chunked ciphertext at upload time.
+///
+/// # Arguments
+/// * `encrypted_data` - The ciphertext as uploaded (`encrypt_file_multi` with `chunked: true`)
+/// * `audit_key_hex` - The 32-byte audit key (hex)
+/// * `challenge_count` - How many one-time challenges to precompute
+///
+/// # Returns
+/// A JsValue containing `StorageAuditResult`; store `record` with the file metadata.
+#[wasm_bindgen]
+pub fn create_storage_audit(encrypted_data: &[u8], audit_key_hex: &str, challenge_count: u32) -> JsValue {
+    log(&format!("[create_storage_audit] Precomputing {} challenges...", challenge_count));
+
+    let result = match parse_audit_key(audit_key_hex).and_then(|mut audit_key| {
+        let record = create_storage_audit_with_rng(&mut OsRng, encrypted_data, &audit_key, challenge_count);
+        audit_key.zeroize();
+        record
+    }) {
+        Ok(record) => StorageAuditResult { success: true, record: Some(record), error_message: String::new() },
+        Err(e) => {
+            log(&format!("[create_storage_audit] Failed: {}", e));
+            StorageAuditResult { success: false, record: None, error_message: e }
+        }
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// Opens one challenge of an audit record for the responder.
+///
+/// # Arguments
+/// * `audit_key_hex` - The audit key the record was created with
+/// * `record_js` - A JsValue representing a `StorageAuditRecord`
+/// * `challenge_index` - The next unused challenge
+///
+/// # Returns
+/// A JsValue containing `StorageChallengeResult`
+#[wasm_bindgen]
+pub fn open_storage_challenge(audit_key_hex: &str, record_js: JsValue, challenge_index: u32) -> JsValue {
+    log(&format!("[open_storage_challenge] Opening challenge {}...", challenge_index));
+
+    let result = match serde_wasm_bindgen::from_value::<StorageAuditRecord>(record_js)
+        .map_err(|e| format!("Failed to parse audit record: {}", e))
+        .and_then(|record| {
+            let mut audit_key = parse_audit_key(audit_key_hex)?;
+            let challenge = record.challenge(&audit_key, challenge_index);
+            audit_key.zeroize();
+            challenge
+        }) {
+        Ok(challenge) => StorageChallengeResult { success: true, challenge: Some(challenge), error_message: String::new() },
+        Err(e) => {
+            log(&format!("[open_storage_challenge] Failed: {}", e));
+            StorageChallengeResult { success: false, challenge: None, error_message: e }
+        }
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// Answers a challenge from the stored bytes; runs next to the bucket.
+///
+/// # Arguments
+/// * `challenge_js` - A JsValue representing a `StorageChallenge`
+/// * `segment_data` - The stored object's bytes at `offset..offset + length`
+///
+/// # Returns
+/// A JsValue containing `StorageResponseResult`
+#[wasm_bindgen]
+pub fn respond_to_storage_challenge(challenge_js: JsValue, segment_data: &[u8]) -> JsValue {
+    log("[respond_to_storage_challenge] Computing response...");
+
+    let result = match serde_wasm_bindgen::from_value::<StorageChallenge>(challenge_js)
+        .map_err(|e| format!("Failed to parse challenge: {}", e))
+        .and_then(|challenge| challenge.respond(segment_data))
+    {
+        Ok(response) => StorageResponseResult {
+            success: true,
+            response_hex: bytes_to_hex(&response),
+            error_message: String::new(),
+        },
+        Err(e) => {
+            log(&format!("[respond_to_storage_challenge] Failed: {}", e));
+            StorageResponseResult { success: false, response_hex: String::new(), error_message: e }
+        }
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// Checks a responder's answer against the precomputed token.
+///
+/// # Arguments
+/// * `record_js` - A JsValue representing a `StorageAuditRecord`
+/// * `challenge_index` - The challenge that was opened
+/// * `response_hex` - The responder's `response_hex`
+#[wasm_bindgen]
+pub fn verify_storage_response(record_js: JsValue, challenge_index: u32, response_hex: &str) -> SignatureVerificationResult {
+    log(&format!("[verify_storage_response] Verifying challenge {}...", challenge_index));
+
+    let record = match serde_wasm_bindgen::from_value::<StorageAuditRecord>(record_js) {
+        Ok(record) => record,
+        Err(e) => return Err(format!("Failed to parse audit record: {}", e)).into(),
+    };
+
+    let result = hex_to_bytes(response_hex).and_then(|response| record.verify_response(challenge_index, &response));
+    if let Err(e) = &result {
+        log(&format!("[verify_storage_response] Verification failed: {}", e));
+    }
+    result.into()
+}
@@ -0,0 +1,64 @@
+//! Seeded variants of the randomised APIs (and fixed-clock variants of the
+//! timestamped ones), for golden-output tests and for reproducing a specific
+//! ciphertext from a bug report.
+//!
+//! Only compiled with the `testing` feature. A seed of any length is hashed
+//! into a ChaCha20 seed, so the same seed always yields the same keys, salts,
+//! nonces and object names. Never ship a build with this feature enabled.
+
+use wasm_bindgen::prelude::*;
+use rand_chacha::{ChaCha20Rng, rand_core::SeedableRng};
+use sha2::{Sha256, Digest};
+
+use crate::block_signature::{BlockSignatureResult, generate_block_signature_with_clock};
+use crate::clock::FixedClock;
+use crate::encrypt_file::{EncryptOptions, MultiKeyEncryptResultData, RecipientInput, encrypt_file_multi_with_rng};
+use crate::masterkey_generator::{EncryptedMasterKeyWithRecovery, encrypt_master_key_with_recovery_with_rng};
+
+/// A deterministic RNG for `seed`.
+pub fn seeded_rng(seed: &[u8]) -> ChaCha20Rng {
+    ChaCha20Rng::from_seed(Sha256::digest(seed).into())
+}
+
+/// `encrypt_file_multi_with_options` with all randomness drawn from `seed`.
+#[wasm_bindgen]
+pub fn encrypt_file_multi_seeded(
+    seed: &[u8],
+    file_data: &[u8],
+    recipients_js: JsValue,
+    options_js: JsValue,
+) -> JsValue {
+    let result = match serde_wasm_bindgen::from_value::<Vec<RecipientInput>>(recipients_js) {
+        Ok(recipients) => {
+            let options = if options_js.is_undefined() || options_js.is_null() {
+                Ok(EncryptOptions::default())
+            } else {
+                serde_wasm_bindgen::from_value::<EncryptOptions>(options_js)
+            };
+            match options {
+                Ok(options) => encrypt_file_multi_with_rng(&mut seeded_rng(seed), file_data, &recipients, &options),
+                Err(e) => MultiKeyEncryptResultData::error(format!("Failed to parse options: {}", e)),
+            }
+        }
+        Err(e) => MultiKeyEncryptResultData::error(format!("Failed to parse recipients: {}", e)),
+    };
+
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// `encrypt_master_key_with_recovery` with all randomness drawn from `seed`.
+#[wasm_bindgen]
+pub fn encrypt_master_key_with_recovery_seeded(seed: &[u8], input: &str) -> EncryptedMasterKeyWithRecovery {
+    encrypt_master_key_with_recovery_with_rng(&mut seeded_rng(seed), input)
+}
+
+/// `generate_block_signature_now` with the clock fixed at `now_ms`.
+#[wasm_bindgen]
+pub fn generate_block_signature_at(
+    now_ms: f64,
+    uploader_id: &str,
+    file_hash: &str,
+    previous_block_hash: &str,
+) -> BlockSignatureResult {
+    generate_block_signature_with_clock(&FixedClock(now_ms as u64), uploader_id, file_hash, "", previous_block_hash)
+}
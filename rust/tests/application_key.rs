@@ -0,0 +1,120 @@
+//! Application keys: members are validated like any other recipient, and files
+//! encrypted under an application key are framed like any other upload.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+use rust::application_key::{
+    ApplicationFileInput, ApplicationKeyEntryInput, create_key_with_rng, encrypt_for_application_with_rng,
+    unlock_application_key, unwrap_file_dek,
+};
+use rust::encrypt_file::{EncryptOptions, RecipientInput};
+use rust::file_metadata::{FileMetadata, decrypt_metadata};
+use rust::masterkey_decryptor::PrivateKeyCredentials;
+use rust::masterkey_generator::{EncryptedMasterKeyWithRecovery, encrypt_master_key_with_recovery_with_rng};
+use rust::payload_frame::{CompressionScheme, DEFAULT_SEGMENT_SIZE, FrameOptions, PaddingScheme, open_payload_with_options};
+use rust::{hash_file, hex_to_bytes};
+
+const PASSWORD: &str = "correct horse battery staple";
+const APPLICATION_ID: &str = "app-2024-001";
+
+fn member(user_id: &str, keys: &EncryptedMasterKeyWithRecovery) -> RecipientInput {
+    RecipientInput {
+        user_id: user_id.to_string(),
+        group_id: None,
+        public_key: keys.public_key(),
+        not_before: None,
+        not_after: None,
+    }
+}
+
+fn credentials(keys: &EncryptedMasterKeyWithRecovery) -> PrivateKeyCredentials {
+    PrivateKeyCredentials {
+        password: PASSWORD.to_string(),
+        pk_salt: keys.salt(),
+        encrypted_private_key: keys.encrypted_private_key(),
+        pk_nonce: keys.nonce(),
+    }
+}
+
+#[test]
+fn members_are_validated_before_wrapping() {
+    let mut rng = ChaCha20Rng::seed_from_u64(28);
+    let keys = encrypt_master_key_with_recovery_with_rng(&mut rng, PASSWORD);
+    let mut create_key = |members: &[RecipientInput]| create_key_with_rng(&mut rng, APPLICATION_ID, members);
+    assert_eq!(create_key(&[member("examiner", &keys)]).unwrap().member_entries.len(), 1);
+
+    let unnamed = member("", &keys);
+    assert!(create_key(&[unnamed]).is_err());
+
+    let group = RecipientInput { user_id: String::new(), group_id: Some("examiners".to_string()), ..member("", &keys) };
+    let error = create_key(&[group]).err().unwrap();
+    assert!(error.contains("must have a user_id"), "{}", error);
+
+    let mut non_canonical = member("examiner", &keys);
+    non_canonical.public_key[31] |= 0x80;
+    let error = create_key(&[member("examiner", &keys), non_canonical]).err().unwrap();
+    assert!(error.contains("canonical"), "{}", error);
+
+    let short = RecipientInput { public_key: vec![9; 31], ..member("examiner", &keys) };
+    assert!(create_key(&[short]).is_err());
+    assert!(create_key(&[]).is_err());
+}
+
+#[test]
+fn application_files_use_the_requested_frame() {
+    let mut rng = ChaCha20Rng::seed_from_u64(2028);
+    let keys = encrypt_master_key_with_recovery_with_rng(&mut rng, PASSWORD);
+    let created = create_key_with_rng(&mut rng, APPLICATION_ID, &[member("examiner", &keys)]).unwrap();
+    let entry = &created.member_entries[0];
+    let caller_entry = || ApplicationKeyEntryInput {
+        encrypted_app_key: hex_to_bytes(&entry.encrypted_app_key_hex).unwrap(),
+        app_key_nonce: hex_to_bytes(&entry.app_key_nonce_hex).unwrap(),
+        ephemeral_public_key: hex_to_bytes(&entry.ephemeral_public_key_hex).unwrap(),
+    };
+
+    let data: Vec<u8> = b"claims ".iter().copied().cycle().take(2 * DEFAULT_SEGMENT_SIZE + 5).collect();
+    let input = ApplicationFileInput {
+        credentials: credentials(&keys),
+        application_id: APPLICATION_ID.to_string(),
+        caller_entry: caller_entry(),
+        options: EncryptOptions {
+            metadata: Some(FileMetadata { name: "claims.txt".to_string(), ..FileMetadata::default() }),
+            padding: PaddingScheme::PowerOfTwo,
+            compression: CompressionScheme::None,
+            chunked: true,
+        },
+    };
+    let encrypted = encrypt_for_application_with_rng(&mut rng.clone(), &data, &input).unwrap();
+    let again = encrypt_for_application_with_rng(&mut rng, &data, &input).unwrap();
+    assert_eq!(again.encrypted_data, encrypted.encrypted_data);
+    assert_eq!(again.wrapped_dek_hex, encrypted.wrapped_dek_hex);
+    assert_eq!(encrypted.ciphertext_hash_hex, hash_file(&encrypted.encrypted_data));
+    assert_eq!(encrypted.original_hash_hex, hash_file(&data));
+
+    let app_key = unlock_application_key(&credentials(&keys), APPLICATION_ID, &caller_entry()).unwrap();
+    let dek = unwrap_file_dek(
+        &app_key,
+        APPLICATION_ID,
+        &hex_to_bytes(&encrypted.wrapped_dek_hex).unwrap(),
+        &hex_to_bytes(&encrypted.wrapped_dek_nonce_hex).unwrap(),
+    )
+    .unwrap();
+    let file_nonce = hex_to_bytes(&encrypted.file_nonce_hex).unwrap();
+    let (opened, frame) = open_payload_with_options(&dek, &file_nonce, &encrypted.encrypted_data).unwrap();
+    assert_eq!(opened, data);
+    assert_eq!(
+        frame,
+        FrameOptions {
+            padding: PaddingScheme::PowerOfTwo,
+            compression: CompressionScheme::None,
+            segment_size: Some(DEFAULT_SEGMENT_SIZE),
+        }
+    );
+
+    let metadata_nonce = hex_to_bytes(&encrypted.metadata_nonce_hex).unwrap();
+    let metadata = decrypt_metadata(&dek, &encrypted.encrypted_metadata, &metadata_nonce).unwrap();
+    assert_eq!(metadata.name, "claims.txt");
+    assert_eq!(metadata.size, data.len() as u64);
+}
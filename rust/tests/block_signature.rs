@@ -0,0 +1,75 @@
+//! Block signatures only accept SHA-256 hex digests as hashes, so the
+//! five-field input can never read as the four-field one.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use rust::block_signature::{
+    LedgerEntry, compute_block_signature, generate_block_signature, generate_file_block_signature,
+};
+
+const FILE_HASH: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+const CIPHERTEXT_HASH: &str = "fa030e7f71b7605c6251c0505941a8a0170ea50e2bb9b307d27de1820da98e35";
+
+#[test]
+fn file_block_signature_requires_a_ciphertext_hash() {
+    let result = generate_file_block_signature("uploader", 1_700_000_000_000.0, FILE_HASH, CIPHERTEXT_HASH, "0");
+    assert!(result.success(), "{}", result.error_message());
+    assert_eq!(
+        result.signature_hex(),
+        compute_block_signature("uploader", 1_700_000_000_000, FILE_HASH, CIPHERTEXT_HASH, "0")
+    );
+
+    let short = &CIPHERTEXT_HASH[..62];
+    let with_dash = format!("{}-{}", &CIPHERTEXT_HASH[..31], &CIPHERTEXT_HASH[32..]);
+    let non_hex = format!("{}g", &CIPHERTEXT_HASH[..63]);
+    for bad in ["", short, &with_dash, &non_hex] {
+        let result = generate_file_block_signature("uploader", 1_700_000_000_000.0, FILE_HASH, bad, "0");
+        assert!(!result.success(), "accepted {:?}", bad);
+        assert!(result.error_message().contains("64 hex characters"), "{}", result.error_message());
+    }
+}
+
+#[test]
+fn four_field_blocks_cannot_pose_as_five_field_blocks() {
+    // "{file_hash}-{x}" as the file hash with no ciphertext hash would hash the
+    // same input as "{file_hash}" with ciphertext hash "{x}"; so would a
+    // previous block hash of "{x}-0"
+    let dashed_file_hash = format!("{}-{}", FILE_HASH, CIPHERTEXT_HASH);
+    assert_eq!(
+        compute_block_signature("uploader", 1, &dashed_file_hash, "", "0"),
+        compute_block_signature("uploader", 1, FILE_HASH, CIPHERTEXT_HASH, "0"),
+        "the ambiguity being guarded against"
+    );
+    let error = LedgerEntry::new("uploader", 1, &dashed_file_hash, "", "0").err().unwrap();
+    assert!(error.contains("File hash must be 64 hex characters"), "{}", error);
+    let result = generate_block_signature("uploader", 1.0, &dashed_file_hash, "0");
+    assert!(!result.success());
+
+    let dashed_previous = format!("{}-0", CIPHERTEXT_HASH);
+    assert!(LedgerEntry::new("uploader", 1, FILE_HASH, "", &dashed_previous).is_err());
+    assert!(!generate_block_signature("uploader", 1.0, FILE_HASH, &dashed_previous).success());
+
+    let five_field = LedgerEntry::new("uploader", 1, FILE_HASH, CIPHERTEXT_HASH, "0").unwrap();
+    let mut forged = five_field.clone();
+    forged.file_hash = dashed_file_hash;
+    forged.ciphertext_hash = String::new();
+    assert_eq!(forged.signature_hex, five_field.signature_hex);
+    assert!(five_field.is_consistent());
+    assert!(!forged.is_consistent());
+
+    let mut forged = five_field.clone();
+    forged.ciphertext_hash = String::new();
+    forged.previous_block_hash = dashed_previous;
+    assert!(!forged.is_consistent());
+}
+
+#[test]
+fn ledger_entry_accepts_empty_or_hex_ciphertext_hash() {
+    let without = LedgerEntry::new("uploader", 1, FILE_HASH, "", "").unwrap();
+    assert!(without.is_consistent());
+    assert_eq!(without.previous_block_hash, "0");
+
+    let with = LedgerEntry::new("uploader", 1, FILE_HASH, &CIPHERTEXT_HASH.to_uppercase(), "0").unwrap();
+    assert!(with.is_consistent());
+    assert!(LedgerEntry::revocation("owner", 2, FILE_HASH, "not-a-hash", &with.signature_hex).is_err());
+}
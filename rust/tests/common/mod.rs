@@ -0,0 +1,88 @@
+//! The known-answer vector corpus in `tests/vectors/filechain-v1.json`.
+//!
+//! Shared by the native and wasm conformance harnesses.
+
+#![allow(dead_code)]
+
+use serde::Deserialize;
+
+const CORPUS: &str = include_str!("../vectors/filechain-v1.json");
+
+#[derive(Deserialize)]
+pub struct Corpus {
+    pub version: u32,
+    pub kek: Vec<KekVector>,
+    pub private_key_wrap: Vec<PrivateKeyWrapVector>,
+    pub dek_wrap: Vec<DekWrapVector>,
+    pub file_encryption: Vec<FileEncryptionVector>,
+    pub block_signature: Vec<BlockSignatureVector>,
+}
+
+#[derive(Deserialize)]
+pub struct KekVector {
+    pub password: String,
+    pub salt: String,
+    pub kek: String,
+}
+
+#[derive(Deserialize)]
+pub struct PrivateKeyWrapVector {
+    pub password: String,
+    pub salt: String,
+    pub nonce: String,
+    pub private_key: String,
+    pub public_key: String,
+    pub encrypted_private_key: String,
+}
+
+#[derive(Deserialize)]
+pub struct DekWrapVector {
+    pub dek: String,
+    pub recipient_private_key: String,
+    pub recipient_public_key: String,
+    pub ephemeral_private_key: String,
+    pub ephemeral_public_key: String,
+    pub dek_nonce: String,
+    pub not_before: Option<u64>,
+    pub not_after: Option<u64>,
+    pub encrypted_dek: String,
+}
+
+#[derive(Deserialize)]
+pub struct FileEncryptionVector {
+    pub name: String,
+    pub dek: String,
+    pub file_nonce: String,
+    pub padding: String,
+    pub compression: String,
+    /// Segment size of the chunked layout, if used
+    #[serde(default)]
+    pub segment_size: Option<usize>,
+    /// The ciphertext depends on the deflate encoder, so only decryption is pinned
+    pub decrypt_only: bool,
+    pub plaintext: String,
+    pub original_hash: String,
+    pub ciphertext: String,
+}
+
+#[derive(Deserialize)]
+pub struct BlockSignatureVector {
+    pub uploader_id: String,
+    pub timestamp_ms: u64,
+    pub file_hash: String,
+    /// Empty for blocks in the format without a ciphertext hash
+    #[serde(default)]
+    pub ciphertext_hash: String,
+    pub previous_block_hash: String,
+    pub signature: String,
+}
+
+pub fn corpus() -> Corpus {
+    let corpus: Corpus = serde_json::from_str(CORPUS).expect("vector corpus is valid JSON");
+    assert_eq!(corpus.version, 1, "unexpected vector corpus version");
+    corpus
+}
+
+pub fn unhex(hex: &str) -> Vec<u8> {
+    rust::hex_to_bytes(hex).expect("vector field is valid hex")
+}
@@ -0,0 +1,102 @@
+//! Destruction certificates: only the expected actor's key verifies, and the
+//! certificate must be anchored by a destruction block, not an upload.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+use rust::block_signature::{BlockKind, LedgerEntry};
+use rust::crypto_shred::{ShredFileInput, shred};
+use rust::encrypt_file::{EncryptOptions, FileDekInput, RecipientInput, encrypt_file_multi_with_rng};
+use rust::masterkey_decryptor::PrivateKeyCredentials;
+use rust::masterkey_generator::{EncryptedMasterKeyWithRecovery, encrypt_master_key_with_recovery_with_rng};
+use rust::{bytes_to_hex, hex_to_bytes};
+
+const PASSWORD: &str = "correct horse battery staple";
+
+fn credentials(keys: &EncryptedMasterKeyWithRecovery) -> PrivateKeyCredentials {
+    PrivateKeyCredentials {
+        password: PASSWORD.to_string(),
+        pk_salt: keys.salt(),
+        encrypted_private_key: keys.encrypted_private_key(),
+        pk_nonce: keys.nonce(),
+    }
+}
+
+#[test]
+fn certificate_needs_the_expected_signer_and_a_destruction_block() {
+    let mut rng = ChaCha20Rng::seed_from_u64(36);
+    let owner = encrypt_master_key_with_recovery_with_rng(&mut rng, PASSWORD);
+    let impostor = encrypt_master_key_with_recovery_with_rng(&mut rng, PASSWORD);
+
+    let recipient = RecipientInput {
+        user_id: "owner".to_string(),
+        group_id: None,
+        public_key: owner.public_key(),
+        not_before: None,
+        not_after: None,
+    };
+    let encrypted = encrypt_file_multi_with_rng(&mut rng, b"ledger export", &[recipient], &EncryptOptions::default());
+    assert!(encrypted.success, "{}", encrypted.error_message);
+    let upload = LedgerEntry::new("owner", 1_700_000_000_000, &encrypted.original_hash_hex, &encrypted.ciphertext_hash_hex, "0")
+        .unwrap();
+
+    let row = &encrypted.dek_entries[0];
+    let shred_as = |keys: &EncryptedMasterKeyWithRecovery| {
+        shred(&ShredFileInput {
+            credentials: credentials(keys),
+            actor_id: "owner".to_string(),
+            file_id: "file-1".to_string(),
+            file_hash: encrypted.original_hash_hex.clone(),
+            dek_entries: vec![FileDekInput {
+                file_id: "file-1".to_string(),
+                encrypted_dek: hex_to_bytes(&row.encrypted_dek_hex).unwrap(),
+                dek_nonce: hex_to_bytes(&row.dek_nonce_hex).unwrap(),
+                ephemeral_public_key: hex_to_bytes(&row.ephemeral_public_key_hex).unwrap(),
+                not_before: None,
+                not_after: None,
+            }],
+            timestamp_ms: 1_700_000_100_000.0,
+            previous_block_hash: upload.signature_hex.clone(),
+        })
+        .unwrap()
+    };
+
+    let owner_key = bytes_to_hex(&owner.public_key());
+    let (certificate, block) = shred_as(&owner);
+    assert!(block.is_destruction());
+    assert_eq!(block.file_hash, certificate.certificate_hash());
+    let ledger = [upload.clone(), block.clone()];
+    assert_eq!(certificate.verify_against_ledger(&ledger, &owner_key).unwrap(), 1);
+
+    let error = certificate.verify_against_ledger(&ledger, "").unwrap_err();
+    assert!(error.contains("signer key is required"), "{}", error);
+
+    // A certificate self-signed by another key does not verify, even when anchored
+    let (forged, forged_block) = shred_as(&impostor);
+    let forged_ledger = [upload.clone(), forged_block];
+    let error = forged.verify_against_ledger(&forged_ledger, &owner_key).unwrap_err();
+    assert!(error.contains("unexpected key"), "{}", error);
+
+    // An upload block carrying the certificate hash does not anchor it
+    let as_upload = LedgerEntry::new(
+        &block.uploader_id,
+        block.timestamp_ms,
+        &block.file_hash,
+        "",
+        &block.previous_block_hash,
+    )
+    .unwrap();
+    assert_ne!(as_upload.signature_hex, block.signature_hex);
+    let error = certificate.verify_against_ledger(&[upload.clone(), as_upload], &owner_key).unwrap_err();
+    assert!(error.contains("not recorded"), "{}", error);
+
+    let mut relabelled = block.clone();
+    relabelled.kind = BlockKind::Upload;
+    assert!(!relabelled.is_consistent());
+    assert!(certificate.verify_against_ledger(&[upload, relabelled], &owner_key).is_err());
+
+    let stored: LedgerEntry = serde_json::from_str(&serde_json::to_string(&block).unwrap()).unwrap();
+    assert_eq!(stored.kind, BlockKind::Destruction);
+    assert!(stored.is_consistent());
+}
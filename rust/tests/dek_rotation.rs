@@ -0,0 +1,175 @@
+//! File DEK rotation: the remaining recipients read the re-encrypted file, the
+//! revoked one cannot, the file keeps its frame and metadata, and the rotation
+//! lands in the ledger as a revocation.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+use rust::block_signature::{BlockKind, LedgerEntry, verify_ledger_chain};
+use rust::decrypt_file::decrypt_with_dek;
+use rust::dek_rotation::{RotateFileDekInput, rotate};
+use rust::encrypt_file::{
+    EncryptOptions, EncryptedDekEntry, RecipientInput, encrypt_file_multi_with_rng, unwrap_dek_with_private_key,
+};
+use rust::file_metadata::{FileMetadata, decrypt_metadata};
+use rust::masterkey_decryptor::unlock_private_key;
+use rust::masterkey_generator::{EncryptedMasterKeyWithRecovery, encrypt_master_key_with_recovery_with_rng};
+use rust::hex_to_bytes;
+use rust::payload_frame::{CompressionScheme, DEFAULT_SEGMENT_SIZE, FrameOptions, PaddingScheme, open_payload_with_options};
+
+const PASSWORD: &str = "correct horse battery staple";
+
+fn recipient(user_id: &str, keys: &EncryptedMasterKeyWithRecovery) -> RecipientInput {
+    RecipientInput {
+        user_id: user_id.to_string(),
+        group_id: None,
+        public_key: keys.public_key(),
+        not_before: None,
+        not_after: None,
+    }
+}
+
+fn unwrap_entry(keys: &EncryptedMasterKeyWithRecovery, entry: &EncryptedDekEntry) -> Vec<u8> {
+    let private_key = unlock_private_key(PASSWORD, &keys.salt(), &keys.encrypted_private_key(), &keys.nonce()).unwrap();
+    unwrap_dek_with_private_key(
+        &private_key,
+        &hex_to_bytes(&entry.ephemeral_public_key_hex).unwrap(),
+        &hex_to_bytes(&entry.dek_nonce_hex).unwrap(),
+        &hex_to_bytes(&entry.encrypted_dek_hex).unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn revocation_is_recorded_as_a_revocation_block() {
+    let mut rng = ChaCha20Rng::seed_from_u64(27);
+    let owner = encrypt_master_key_with_recovery_with_rng(&mut rng, PASSWORD);
+    let revoked = encrypt_master_key_with_recovery_with_rng(&mut rng, PASSWORD);
+
+    let data = b"quarterly figures".to_vec();
+    let recipients = [recipient("owner", &owner), recipient("revoked", &revoked)];
+    let encrypted = encrypt_file_multi_with_rng(&mut rng, &data, &recipients, &EncryptOptions::default());
+    assert!(encrypted.success, "{}", encrypted.error_message);
+    let upload = LedgerEntry::new("owner", 1_700_000_000_000, &encrypted.original_hash_hex, &encrypted.ciphertext_hash_hex, "0")
+        .unwrap();
+
+    let row = &encrypted.dek_entries[0];
+    let input = RotateFileDekInput {
+        encrypted_data: encrypted.encrypted_data.clone(),
+        file_nonce: hex_to_bytes(&encrypted.file_nonce_hex).unwrap(),
+        encrypted_metadata: vec![],
+        metadata_nonce: vec![],
+        password: PASSWORD.to_string(),
+        pk_salt: owner.salt(),
+        encrypted_private_key: owner.encrypted_private_key(),
+        pk_nonce: owner.nonce(),
+        encrypted_dek: hex_to_bytes(&row.encrypted_dek_hex).unwrap(),
+        dek_nonce: hex_to_bytes(&row.dek_nonce_hex).unwrap(),
+        ephemeral_public_key: hex_to_bytes(&row.ephemeral_public_key_hex).unwrap(),
+        recipients: vec![recipient("owner", &owner)],
+        actor_id: "owner".to_string(),
+        timestamp_ms: 1_700_000_100_000.0,
+        previous_block_hash: upload.signature_hex.clone(),
+    };
+    let result = rotate(&input).unwrap();
+    assert_eq!(result.dek_entries.len(), 1);
+    assert_eq!(result.dek_entries[0].user_id, "owner");
+
+    let dek = unwrap_entry(&owner, &result.dek_entries[0]);
+    let new_nonce = hex_to_bytes(&result.file_nonce_hex).unwrap();
+    assert_eq!(decrypt_with_dek(&dek, &new_nonce, &result.encrypted_data).unwrap(), data);
+
+    // The revoked user's old wrapping no longer opens the stored file
+    let old_dek = unwrap_entry(&revoked, &encrypted.dek_entries[1]);
+    assert!(decrypt_with_dek(&old_dek, &new_nonce, &result.encrypted_data).is_err());
+
+    let block = result.ledger_entry.clone().unwrap();
+    assert!(block.is_revocation());
+    assert!(!upload.is_revocation());
+    assert_eq!(block.ciphertext_hash, result.ciphertext_hash_hex);
+    verify_ledger_chain(&[upload.clone(), block.clone()]).unwrap();
+
+    // The marker survives storage, and is covered by the signature
+    let stored: LedgerEntry = serde_json::from_str(&serde_json::to_string(&block).unwrap()).unwrap();
+    assert_eq!(stored.kind, BlockKind::Revocation);
+    assert!(stored.is_consistent());
+    assert!(!serde_json::to_string(&upload).unwrap().contains("kind"));
+
+    let mut relabelled = block.clone();
+    relabelled.kind = BlockKind::Upload;
+    assert!(!relabelled.is_consistent());
+    assert!(verify_ledger_chain(&[upload.clone(), relabelled]).is_err());
+
+    let as_upload = LedgerEntry::new(
+        &block.uploader_id,
+        block.timestamp_ms,
+        &block.file_hash,
+        &block.ciphertext_hash,
+        &block.previous_block_hash,
+    )
+    .unwrap();
+    assert_ne!(as_upload.signature_hex, block.signature_hex);
+}
+
+#[test]
+fn rotation_keeps_the_frame_and_moves_the_metadata() {
+    let mut rng = ChaCha20Rng::seed_from_u64(2027);
+    let owner = encrypt_master_key_with_recovery_with_rng(&mut rng, PASSWORD);
+    let revoked = encrypt_master_key_with_recovery_with_rng(&mut rng, PASSWORD);
+
+    let data: Vec<u8> = b"board minutes ".iter().copied().cycle().take(3 * DEFAULT_SEGMENT_SIZE).collect();
+    let options = EncryptOptions {
+        metadata: Some(FileMetadata { name: "minutes.txt".to_string(), ..FileMetadata::default() }),
+        padding: PaddingScheme::Padme,
+        compression: CompressionScheme::Deflate,
+        chunked: true,
+    };
+    let recipients = [recipient("owner", &owner), recipient("revoked", &revoked)];
+    let encrypted = encrypt_file_multi_with_rng(&mut rng, &data, &recipients, &options);
+    assert!(encrypted.success, "{}", encrypted.error_message);
+
+    let row = &encrypted.dek_entries[0];
+    let input = RotateFileDekInput {
+        encrypted_data: encrypted.encrypted_data.clone(),
+        file_nonce: hex_to_bytes(&encrypted.file_nonce_hex).unwrap(),
+        encrypted_metadata: encrypted.encrypted_metadata.clone(),
+        metadata_nonce: hex_to_bytes(&encrypted.metadata_nonce_hex).unwrap(),
+        password: PASSWORD.to_string(),
+        pk_salt: owner.salt(),
+        encrypted_private_key: owner.encrypted_private_key(),
+        pk_nonce: owner.nonce(),
+        encrypted_dek: hex_to_bytes(&row.encrypted_dek_hex).unwrap(),
+        dek_nonce: hex_to_bytes(&row.dek_nonce_hex).unwrap(),
+        ephemeral_public_key: hex_to_bytes(&row.ephemeral_public_key_hex).unwrap(),
+        recipients: vec![recipient("owner", &owner)],
+        actor_id: "owner".to_string(),
+        timestamp_ms: 1_700_000_100_000.0,
+        previous_block_hash: "0".to_string(),
+    };
+    let result = rotate(&input).unwrap();
+
+    let dek = unwrap_entry(&owner, &result.dek_entries[0]);
+    let new_nonce = hex_to_bytes(&result.file_nonce_hex).unwrap();
+    let (opened, frame) = open_payload_with_options(&dek, &new_nonce, &result.encrypted_data).unwrap();
+    assert_eq!(opened, data);
+    assert_eq!(
+        frame,
+        FrameOptions {
+            padding: PaddingScheme::Padme,
+            compression: CompressionScheme::Deflate,
+            segment_size: Some(DEFAULT_SEGMENT_SIZE),
+        }
+    );
+    assert_eq!(result.encrypted_data.len(), encrypted.encrypted_data.len());
+
+    let metadata_nonce = hex_to_bytes(&result.metadata_nonce_hex).unwrap();
+    let metadata = decrypt_metadata(&dek, &result.encrypted_metadata, &metadata_nonce).unwrap();
+    assert_eq!(metadata.name, "minutes.txt");
+    assert_eq!(metadata.size, data.len() as u64);
+
+    // The revoked user's DEK opens neither the new file nor the new metadata
+    let old_dek = unwrap_entry(&revoked, &encrypted.dek_entries[1]);
+    assert!(decrypt_metadata(&old_dek, &result.encrypted_metadata, &metadata_nonce).is_err());
+    assert!(open_payload_with_options(&old_dek, &new_nonce, &result.encrypted_data).is_err());
+}
@@ -0,0 +1,75 @@
+//! `FileHasher`: chunked input gives the same digest as hashing in one go,
+//! and each algorithm matches its published test vector.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use rust::file_hasher::FileHasher;
+use rust::{bytes_to_hex, hash_file};
+use sha2::{Digest, Sha512};
+
+fn file_data(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+/// Feeds `data` in uneven chunks, including empty ones.
+fn hash_in_chunks(mut hasher: FileHasher, data: &[u8]) -> String {
+    let mut rest = data;
+    for size in [0, 1, 63, 64, 1000, 1024, 4097].iter().cycle() {
+        if rest.is_empty() {
+            break;
+        }
+        let (chunk, tail) = rest.split_at((*size).min(rest.len()));
+        hasher.update(chunk);
+        rest = tail;
+    }
+    assert_eq!(hasher.bytes_hashed(), data.len() as f64);
+    hasher.finalize_hex()
+}
+
+fn hash_at_once(mut hasher: FileHasher, data: &[u8]) -> String {
+    hasher.update(data);
+    hasher.finalize_hex()
+}
+
+#[test]
+fn chunked_sha256_matches_hash_file() {
+    for len in [0, 1, 64, 1025, 100_000] {
+        let data = file_data(len);
+        assert_eq!(hash_in_chunks(FileHasher::new(), &data), hash_file(&data), "{} bytes", len);
+    }
+}
+
+#[test]
+fn chunked_sha512_and_blake3_match_one_shot() {
+    let data = file_data(100_000);
+    assert_eq!(hash_in_chunks(FileHasher::sha512(), &data), bytes_to_hex(&Sha512::digest(&data)));
+    assert_eq!(hash_in_chunks(FileHasher::blake3(), &data), blake3::hash(&data).to_hex().to_string());
+}
+
+#[test]
+fn known_answer_digests() {
+    assert_eq!(
+        hash_at_once(FileHasher::sha256(), b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_eq!(
+        hash_at_once(FileHasher::sha512(), b"abc"),
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+         2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+    );
+    assert_eq!(
+        hash_at_once(FileHasher::blake3(), b"abc"),
+        "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+    );
+    assert_eq!(
+        hash_at_once(FileHasher::blake3(), b""),
+        "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+    );
+}
+
+#[test]
+fn algorithm_names() {
+    assert_eq!(FileHasher::new().algorithm(), "sha256");
+    assert_eq!(FileHasher::sha512().algorithm(), "sha512");
+    assert_eq!(FileHasher::blake3().algorithm(), "blake3");
+}
@@ -0,0 +1,78 @@
+//! Re-sharing a time-limited grant: the new entry's window has to stay inside
+//! the sharer's, so a 30-day grant cannot mint an unlimited one.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+use rust::encrypt_file::{EncryptOptions, RecipientInput, ReShareDekInput, encrypt_file_multi_with_rng, re_share_dek};
+use rust::grant_window::GrantWindow;
+use rust::hex_to_bytes;
+use rust::masterkey_generator::encrypt_master_key_with_recovery_with_rng;
+
+const PASSWORD: &str = "correct horse battery staple";
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+const GRANTED_AT: u64 = 1_700_000_000_000;
+
+#[test]
+fn check_within_requires_closed_bounds_inside_the_outer_window() {
+    let outer = GrantWindow::new(Some(GRANTED_AT), Some(GRANTED_AT + 30 * DAY_MS));
+
+    assert!(outer.check_within(&outer).is_ok());
+    assert!(GrantWindow::new(Some(GRANTED_AT + DAY_MS), Some(GRANTED_AT + 2 * DAY_MS)).check_within(&outer).is_ok());
+    assert!(GrantWindow::default().check_within(&outer).is_err());
+    assert!(GrantWindow::new(Some(GRANTED_AT), None).check_within(&outer).is_err());
+    assert!(GrantWindow::new(None, Some(GRANTED_AT + DAY_MS)).check_within(&outer).is_err());
+    assert!(GrantWindow::new(Some(GRANTED_AT), Some(GRANTED_AT + 31 * DAY_MS)).check_within(&outer).is_err());
+    assert!(GrantWindow::new(Some(GRANTED_AT - 1), Some(GRANTED_AT + DAY_MS)).check_within(&outer).is_err());
+
+    let unbounded = GrantWindow::default();
+    assert!(GrantWindow::default().check_within(&unbounded).is_ok());
+    assert!(outer.check_within(&unbounded).is_ok());
+}
+
+#[test]
+fn bounded_grant_cannot_be_re_shared_beyond_its_window() {
+    let mut rng = ChaCha20Rng::seed_from_u64(35);
+    let counsel = encrypt_master_key_with_recovery_with_rng(&mut rng, PASSWORD);
+    let target = encrypt_master_key_with_recovery_with_rng(&mut rng, PASSWORD);
+
+    let not_after = GRANTED_AT + 30 * DAY_MS;
+    let recipient = RecipientInput {
+        user_id: "counsel".to_string(),
+        group_id: None,
+        public_key: counsel.public_key(),
+        not_before: Some(GRANTED_AT),
+        not_after: Some(not_after),
+    };
+    let encrypted = encrypt_file_multi_with_rng(&mut rng, b"case file", &[recipient], &EncryptOptions::default());
+    assert!(encrypted.success, "{}", encrypted.error_message);
+    let entry = &encrypted.dek_entries[0];
+
+    let re_share = |target_not_before: Option<u64>, target_not_after: Option<u64>| {
+        re_share_dek(&ReShareDekInput {
+            password: PASSWORD.to_string(),
+            pk_salt: counsel.salt(),
+            encrypted_private_key: counsel.encrypted_private_key(),
+            pk_nonce: counsel.nonce(),
+            encrypted_dek: hex_to_bytes(&entry.encrypted_dek_hex).unwrap(),
+            dek_nonce: hex_to_bytes(&entry.dek_nonce_hex).unwrap(),
+            ephemeral_public_key: hex_to_bytes(&entry.ephemeral_public_key_hex).unwrap(),
+            target_public_key: target.public_key(),
+            group_entry: None,
+            not_before: entry.not_before,
+            not_after: entry.not_after,
+            now_ms: Some((GRANTED_AT + DAY_MS) as f64),
+            target_not_before,
+            target_not_after,
+        })
+    };
+
+    let error = re_share(None, None).err().unwrap();
+    assert!(error.contains("sharer's own grant"), "{}", error);
+    assert!(re_share(Some(GRANTED_AT), None).is_err());
+    assert!(re_share(Some(GRANTED_AT), Some(not_after + 1)).is_err());
+
+    let wrapped = re_share(Some(GRANTED_AT + DAY_MS), Some(not_after)).unwrap();
+    assert_eq!(wrapped.window, GrantWindow::new(Some(GRANTED_AT + DAY_MS), Some(not_after)));
+}
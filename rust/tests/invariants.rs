@@ -0,0 +1,554 @@
+//! Property tests for the guarantees the product depends on: every recipient
+//! of a file can decrypt it, through password changes, recovery and re-sharing,
+//! and no single-bit change to any stored ciphertext field goes unnoticed.
+//!
+//! Every chunk of a file proves against the file's Merkle root, and no other
+//! bytes do. Storage audits pass on the stored ciphertext and fail once the
+//! challenged segment changes. Range reads return exactly the bytes a full
+//! decryption would.
+//!
+//! Progress events of each stage only move forward and finish at the total.
+//! An abort at any point fails with the cancelled error code, and an
+//! operation that is never aborted produces the same output as one without
+//! progress reporting.
+//!
+//! Every decryption goes through the full `decrypt_file` path, including the
+//! Argon2 unlock, so case counts are kept low. Raise them with `PROPTEST_CASES`.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::sync::OnceLock;
+
+use proptest::prelude::*;
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+use rust::decrypt_file::{DecryptionContext, decrypt_with_context};
+use rust::encrypt_file::{
+    EncryptOptions, MultiKeyEncryptResultData, RecipientInput, ReShareDekInput, encrypt_file_multi_with_rng,
+    encrypt_file_multi_with_rng_and_progress, re_share_dek,
+};
+use rust::masterkey_decryptor::{re_encrypt_private_key, recover_and_reencrypt_private_key};
+use rust::masterkey_generator::encrypt_master_key_with_recovery_with_rng;
+use rust::merkle::{MERKLE_CHUNK_SIZE, MerkleTree};
+use rust::storage_audit::create_storage_audit_with_rng;
+use rust::payload_frame::{
+    ChunkedLayout, CompressionScheme, DEFAULT_SEGMENT_SIZE, FrameOptions, PaddingScheme, open_payload, open_payload_with_progress,
+    padme, seal_payload,
+};
+use rust::progress::{ERROR_CANCELLED, Progress, ProgressError, ProgressEvent, Stage};
+use rust::range_decrypt::RangeReader;
+use rust::{hash_file, hex_to_bytes};
+
+const USERS: usize = 4;
+
+/// Padding, compression and whether the chunked layout is used.
+type Framing = (PaddingScheme, CompressionScheme, bool);
+
+/// What a user profile stores about its password-wrapped private key.
+#[derive(Clone)]
+struct Credentials {
+    password: String,
+    salt: String,
+    encrypted_private_key: Vec<u8>,
+    nonce: Vec<u8>,
+}
+
+struct User {
+    user_id: String,
+    public_key: Vec<u8>,
+    credentials: Credentials,
+    recovery_key_hex: String,
+    recovery: Credentials,
+}
+
+/// A `file_dek` row.
+struct DekRow {
+    encrypted_dek: Vec<u8>,
+    dek_nonce: Vec<u8>,
+    ephemeral_public_key: Vec<u8>,
+}
+
+/// A fixed set of users, created once: Argon2 makes account creation slow.
+fn users() -> &'static [User] {
+    static USERS_CELL: OnceLock<Vec<User>> = OnceLock::new();
+    USERS_CELL.get_or_init(|| {
+        let mut rng = ChaCha20Rng::from_seed([7; 32]);
+        (0..USERS)
+            .map(|i| {
+                let password = format!("password-{}", i);
+                let keys = encrypt_master_key_with_recovery_with_rng(&mut rng, &password);
+                User {
+                    user_id: format!("user-{}", i),
+                    public_key: keys.public_key(),
+                    credentials: Credentials {
+                        password,
+                        salt: keys.salt(),
+                        encrypted_private_key: keys.encrypted_private_key(),
+                        nonce: keys.nonce(),
+                    },
+                    recovery_key_hex: keys.recovery_key_hex(),
+                    recovery: Credentials {
+                        password: keys.recovery_key_hex(),
+                        salt: keys.recovery_salt(),
+                        encrypted_private_key: hex_to_bytes(&keys.recovery_encrypted_private_key_hex()).unwrap(),
+                        nonce: hex_to_bytes(&keys.recovery_nonce_hex()).unwrap(),
+                    },
+                }
+            })
+            .collect()
+    })
+}
+
+fn encrypt_for(seed: u64, data: &[u8], recipients: &[usize], framing: Framing) -> MultiKeyEncryptResultData {
+    let (padding, compression, chunked) = framing;
+    let options = EncryptOptions { metadata: None, padding, compression, chunked };
+    let encrypted =
+        encrypt_file_multi_with_rng(&mut ChaCha20Rng::seed_from_u64(seed), data, &recipient_inputs(recipients), &options);
+    assert!(encrypted.success, "{}", encrypted.error_message);
+    encrypted
+}
+
+fn recipient_inputs(recipients: &[usize]) -> Vec<RecipientInput> {
+    recipients
+        .iter()
+        .map(|&i| RecipientInput {
+            user_id: users()[i].user_id.clone(),
+            group_id: None,
+            public_key: users()[i].public_key.clone(),
+            not_before: None,
+            not_after: None,
+        })
+        .collect()
+}
+
+fn row_for(encrypted: &MultiKeyEncryptResultData, user: usize) -> DekRow {
+    let entry = encrypted
+        .dek_entries
+        .iter()
+        .find(|entry| entry.user_id == users()[user].user_id)
+        .expect("recipient has a DEK entry");
+    DekRow {
+        encrypted_dek: hex_to_bytes(&entry.encrypted_dek_hex).unwrap(),
+        dek_nonce: hex_to_bytes(&entry.dek_nonce_hex).unwrap(),
+        ephemeral_public_key: hex_to_bytes(&entry.ephemeral_public_key_hex).unwrap(),
+    }
+}
+
+fn context(credentials: &Credentials, row: &DekRow, encrypted: &MultiKeyEncryptResultData) -> DecryptionContext {
+    DecryptionContext {
+        encrypted_data: encrypted.encrypted_data.clone(),
+        password: credentials.password.clone(),
+        pk_salt: credentials.salt.clone(),
+        encrypted_private_key: credentials.encrypted_private_key.clone(),
+        pk_nonce: credentials.nonce.clone(),
+        ephemeral_public_key: row.ephemeral_public_key.clone(),
+        encrypted_dek: row.encrypted_dek.clone(),
+        dek_nonce: row.dek_nonce.clone(),
+        file_nonce: hex_to_bytes(&encrypted.file_nonce_hex).unwrap(),
+        group_entry: None,
+        manifest: None,
+        user_id: String::new(),
+        manifest_signer_public_key_hex: String::new(),
+        not_before: None,
+        not_after: None,
+        now_ms: None,
+    }
+}
+
+fn assert_decrypts(context: &DecryptionContext, data: &[u8]) -> Result<(), TestCaseError> {
+    let result = decrypt_with_context(context);
+    prop_assert!(result.success(), "{}", result.error_message());
+    prop_assert_eq!(result.decrypted_data(), data.to_vec());
+    prop_assert_eq!(result.file_hash_hex(), hash_file(data));
+    Ok(())
+}
+
+/// `default_cases` cases, unless `PROPTEST_CASES` is set.
+fn config(default_cases: u32) -> ProptestConfig {
+    let cases = std::env::var("PROPTEST_CASES").ok().and_then(|cases| cases.parse().ok());
+    ProptestConfig::with_cases(cases.unwrap_or(default_cases))
+}
+
+fn framing() -> impl Strategy<Value = Framing> {
+    let padding = prop_oneof![Just(PaddingScheme::None), Just(PaddingScheme::Padme), Just(PaddingScheme::PowerOfTwo)];
+    let compression = prop_oneof![Just(CompressionScheme::None), Just(CompressionScheme::Deflate)];
+    (padding, compression, any::<bool>())
+}
+
+/// Mostly-repetitive data so that deflate actually kicks in for some cases.
+fn file_data() -> impl Strategy<Value = Vec<u8>> {
+    prop_oneof![
+        prop::collection::vec(any::<u8>(), 0..2048),
+        (any::<u8>(), 0usize..4096).prop_map(|(byte, len)| vec![byte; len]),
+    ]
+}
+
+proptest! {
+    #![proptest_config(config(8))]
+
+    #[test]
+    fn every_recipient_can_decrypt(
+        seed in any::<u64>(),
+        data in file_data(),
+        recipients in prop::sample::subsequence((0..USERS).collect::<Vec<_>>(), 1..=3),
+        framing in framing(),
+    ) {
+        let encrypted = encrypt_for(seed, &data, &recipients, framing);
+        prop_assert_eq!(encrypted.dek_entries.len(), recipients.len());
+
+        for &user in &recipients {
+            let row = row_for(&encrypted, user);
+            assert_decrypts(&context(&users()[user].credentials, &row, &encrypted), &data)?;
+        }
+    }
+
+    #[test]
+    fn decrypts_after_password_change(
+        seed in any::<u64>(),
+        data in file_data(),
+        user in 0..USERS,
+        new_password in "\\PC{1,24}",
+    ) {
+        let encrypted = encrypt_for(seed, &data, &[user], Framing::default());
+        let old = &users()[user].credentials;
+
+        let changed = re_encrypt_private_key(&old.password, &old.salt, &old.encrypted_private_key, &old.nonce, &new_password);
+        prop_assert!(changed.success(), "{}", changed.error_message());
+        let new = Credentials {
+            password: new_password,
+            salt: changed.salt(),
+            encrypted_private_key: changed.encrypted_private_key(),
+            nonce: changed.nonce(),
+        };
+
+        assert_decrypts(&context(&new, &row_for(&encrypted, user), &encrypted), &data)?;
+    }
+
+    #[test]
+    fn decrypts_after_recovery(
+        seed in any::<u64>(),
+        data in file_data(),
+        user in 0..USERS,
+        new_password in "\\PC{1,24}",
+    ) {
+        let encrypted = encrypt_for(seed, &data, &[user], Framing::default());
+        let recovery = &users()[user].recovery;
+
+        let recovered = recover_and_reencrypt_private_key(
+            &users()[user].recovery_key_hex,
+            &recovery.salt,
+            &recovery.encrypted_private_key,
+            &recovery.nonce,
+            &new_password,
+        );
+        prop_assert!(recovered.success(), "{}", recovered.error_message());
+        let new = Credentials {
+            password: new_password,
+            salt: recovered.salt(),
+            encrypted_private_key: hex_to_bytes(&recovered.encrypted_private_key_hex()).unwrap(),
+            nonce: hex_to_bytes(&recovered.nonce_hex()).unwrap(),
+        };
+
+        assert_decrypts(&context(&new, &row_for(&encrypted, user), &encrypted), &data)?;
+    }
+
+    #[test]
+    fn decrypts_after_reshare_chain(
+        seed in any::<u64>(),
+        data in file_data(),
+        chain in prop::sample::subsequence((0..USERS).collect::<Vec<_>>(), 2..=USERS).prop_shuffle(),
+        framing in framing(),
+    ) {
+        let encrypted = encrypt_for(seed, &data, &chain[..1], framing);
+        let mut row = row_for(&encrypted, chain[0]);
+
+        for pair in chain.windows(2) {
+            let sharer = &users()[pair[0]].credentials;
+            let wrapped = re_share_dek(&ReShareDekInput {
+                password: sharer.password.clone(),
+                pk_salt: sharer.salt.clone(),
+                encrypted_private_key: sharer.encrypted_private_key.clone(),
+                pk_nonce: sharer.nonce.clone(),
+                encrypted_dek: row.encrypted_dek,
+                dek_nonce: row.dek_nonce,
+                ephemeral_public_key: row.ephemeral_public_key,
+                target_public_key: users()[pair[1]].public_key.clone(),
+                group_entry: None,
+                not_before: None,
+                not_after: None,
+                now_ms: None,
+                target_not_before: None,
+                target_not_after: None,
+            });
+            prop_assert!(wrapped.is_ok(), "{:?}", wrapped.as_ref().err());
+            let wrapped = wrapped.unwrap();
+            row = DekRow {
+                encrypted_dek: wrapped.encrypted_dek,
+                dek_nonce: wrapped.dek_nonce,
+                ephemeral_public_key: wrapped.ephemeral_public_key,
+            };
+        }
+
+        let last = *chain.last().unwrap();
+        assert_decrypts(&context(&users()[last].credentials, &row, &encrypted), &data)?;
+    }
+
+    #[test]
+    fn any_bit_flip_fails(
+        seed in any::<u64>(),
+        data in prop::collection::vec(any::<u8>(), 1..512),
+        framing in framing(),
+        field in 0usize..7,
+        bit in any::<prop::sample::Index>(),
+    ) {
+        let encrypted = encrypt_for(seed, &data, &[0], framing);
+        let mut context = context(&users()[0].credentials, &row_for(&encrypted, 0), &encrypted);
+
+        let (name, bytes) = match field {
+            0 => ("encrypted_data", &mut context.encrypted_data),
+            1 => ("file_nonce", &mut context.file_nonce),
+            2 => ("encrypted_dek", &mut context.encrypted_dek),
+            3 => ("dek_nonce", &mut context.dek_nonce),
+            4 => ("ephemeral_public_key", &mut context.ephemeral_public_key),
+            5 => ("encrypted_private_key", &mut context.encrypted_private_key),
+            _ => ("pk_nonce", &mut context.pk_nonce),
+        };
+        let bit = bit.index(bytes.len() * 8);
+        bytes[bit / 8] ^= 1 << (bit % 8);
+
+        let result = decrypt_with_context(&context);
+        prop_assert!(!result.success(), "flipping bit {} of {} went unnoticed", bit, name);
+    }
+}
+
+/// Between one and four chunks, the last one partial more often than not.
+fn chunked_file_data() -> impl Strategy<Value = Vec<u8>> {
+    (1..4 * MERKLE_CHUNK_SIZE, any::<u8>()).prop_map(|(len, seed)| {
+        (0..len).map(|i| (i / 251) as u8 ^ seed).collect()
+    })
+}
+
+proptest! {
+    #![proptest_config(config(8))]
+
+    #[test]
+    fn every_chunk_proves_against_the_root(
+        seed in any::<u64>(),
+        data in chunked_file_data(),
+        bit in any::<prop::sample::Index>(),
+    ) {
+        let encrypted = encrypt_for(seed, &data, &[0], Framing::default());
+        let tree = MerkleTree::from_data(&data);
+        prop_assert_eq!(&encrypted.merkle_root_hex, &tree.root_hex());
+        let root = hex_to_bytes(&encrypted.merkle_root_hex).unwrap();
+
+        for (index, chunk) in data.chunks(MERKLE_CHUNK_SIZE).enumerate() {
+            let proof = tree.proof(index).unwrap();
+            prop_assert!(proof.verify(chunk, &root).is_ok(), "chunk {} did not verify", index);
+
+            let mut damaged = chunk.to_vec();
+            let bit = bit.index(damaged.len() * 8);
+            damaged[bit / 8] ^= 1 << (bit % 8);
+            prop_assert!(proof.verify(&damaged, &root).is_err(), "damaged chunk {} verified", index);
+
+            // Right bytes, wrong position
+            if tree.chunk_count() > 1 {
+                let other = tree.proof((index + 1) % tree.chunk_count()).unwrap();
+                prop_assert!(other.verify(chunk, &root).is_err(), "chunk {} verified at another index", index);
+            }
+        }
+    }
+
+    #[test]
+    fn storage_audits_detect_altered_segments(
+        seed in any::<u64>(),
+        data in prop::collection::vec(any::<u8>(), 0..300_000),
+        padding in prop_oneof![Just(PaddingScheme::None), Just(PaddingScheme::Padme)],
+        bit in any::<prop::sample::Index>(),
+    ) {
+        let encrypted = encrypt_for(seed, &data, &[0], (padding, CompressionScheme::None, true));
+        let blob = &encrypted.encrypted_data;
+        let audit_key = [0xa5; 32];
+        let record = create_storage_audit_with_rng(&mut ChaCha20Rng::seed_from_u64(seed), blob, &audit_key, 8).unwrap();
+
+        for index in 0..8 {
+            let challenge = record.challenge(&audit_key, index).unwrap();
+            let range = challenge.offset as usize..(challenge.offset + challenge.length) as usize;
+            let response = challenge.respond(&blob[range.clone()]).unwrap();
+            prop_assert!(record.verify_response(index, &response).is_ok(), "challenge {} failed on intact data", index);
+
+            let mut damaged = blob[range].to_vec();
+            let bit = bit.index(damaged.len() * 8);
+            damaged[bit / 8] ^= 1 << (bit % 8);
+            let response = challenge.respond(&damaged).unwrap();
+            prop_assert!(record.verify_response(index, &response).is_err(), "challenge {} passed on altered data", index);
+        }
+    }
+
+    #[test]
+    fn padded_lengths_fit_or_fail(
+        len in prop_oneof![any::<usize>(), 0usize..1 << 20, Just((1 << (usize::BITS - 2)) + 1), Just(isize::MAX as usize)],
+    ) {
+        for padding in [PaddingScheme::None, PaddingScheme::Padme, PaddingScheme::PowerOfTwo] {
+            let exact = match padding {
+                _ if len > isize::MAX as usize => None,
+                PaddingScheme::None => Some(len as u128),
+                PaddingScheme::Padme => Some(padme(len as u64) as u128),
+                PaddingScheme::PowerOfTwo => Some((len as u128).next_power_of_two()),
+            };
+            let expected = exact.filter(|&padded| padded <= isize::MAX as u128).map(|padded| padded as usize);
+            prop_assert_eq!(padding.padded_len(len).ok(), expected, "{:?} of {}", padding, len);
+        }
+    }
+
+    #[test]
+    fn range_reads_match_full_decryption(
+        data in prop::collection::vec(any::<u8>(), 0..200_000),
+        padding in prop_oneof![Just(PaddingScheme::None), Just(PaddingScheme::Padme), Just(PaddingScheme::PowerOfTwo)],
+        segment_size in prop_oneof![8usize..100, Just(DEFAULT_SEGMENT_SIZE)],
+        ranges in prop::collection::vec((0.0f64..=1.0, 0usize..100_000), 1..8),
+        bit in any::<prop::sample::Index>(),
+    ) {
+        let dek = [0x42; 32];
+        let file_nonce = [7; 12];
+        let options = FrameOptions { padding, compression: CompressionScheme::None, segment_size: Some(segment_size) };
+        let blob = seal_payload(&dek, &file_nonce, &data, &options).unwrap();
+        prop_assert_eq!(open_payload(&dek, &file_nonce, &blob).unwrap(), data.clone());
+
+        let reader = RangeReader::new(&dek, &file_nonce, &blob, blob.len() as u64).unwrap();
+        prop_assert_eq!(reader.data_len(), data.len() as u64);
+
+        for (position, len) in ranges {
+            let offset = (position * data.len() as f64) as u64;
+            let fetch = reader.fetch_range(offset, len as u64).unwrap();
+            let mut fetched = blob[fetch.start as usize..fetch.end as usize].to_vec();
+
+            let end = (offset as usize + len).min(data.len());
+            let range = reader.decrypt_range(offset, len as u64, &fetched).unwrap();
+            prop_assert_eq!(&range[..], &data[offset as usize..end]);
+
+            if !fetched.is_empty() {
+                let bit = bit.index(fetched.len() * 8);
+                fetched[bit / 8] ^= 1 << (bit % 8);
+                prop_assert!(reader.decrypt_range(offset, len as u64, &fetched).is_err(), "altered range decrypted");
+            }
+        }
+        prop_assert!(reader.fetch_range(data.len() as u64 + 1, 1).is_err());
+    }
+}
+
+/// Records every event; aborts once it has seen `abort_after` of them.
+struct Recorder {
+    events: Vec<ProgressEvent>,
+    abort_after: Option<usize>,
+}
+
+impl Recorder {
+    fn new(abort_after: Option<usize>) -> Self {
+        Recorder { events: vec![], abort_after }
+    }
+
+    /// Checks that each stage's byte counts only grow and end at the total.
+    fn assert_well_ordered(&self) -> Result<(), TestCaseError> {
+        let mut current = None;
+        for event in &self.events {
+            if let ProgressEvent::Bytes { stage, processed, total } = *event {
+                prop_assert!(processed <= total, "{:?} reported {} of {}", stage, processed, total);
+                match current {
+                    Some((s, p, t)) if s == stage && t == total && processed > 0 => {
+                        prop_assert!(processed > p, "{:?} went from {} to {}", stage, p, processed);
+                    }
+                    Some((s, p, t)) => {
+                        prop_assert_eq!(p, t, "{:?} stopped at {} of {}", s, p, t);
+                        prop_assert_eq!(processed, 0, "{:?} started at {}", stage, processed);
+                    }
+                    None => prop_assert_eq!(processed, 0, "{:?} started at {}", stage, processed),
+                }
+                current = Some((stage, processed, total));
+            }
+        }
+        if let Some((stage, processed, total)) = current {
+            prop_assert_eq!(processed, total, "{:?} stopped at {} of {}", stage, processed, total);
+        }
+        Ok(())
+    }
+}
+
+impl Progress for Recorder {
+    fn report(&mut self, event: ProgressEvent) {
+        self.events.push(event);
+    }
+
+    fn aborted(&mut self) -> bool {
+        self.abort_after.is_some_and(|n| self.events.len() >= n)
+    }
+}
+
+proptest! {
+    #![proptest_config(config(8))]
+
+    #[test]
+    fn progress_is_ordered_and_aborts_cancel(
+        seed in any::<u64>(),
+        data in prop_oneof![file_data(), chunked_file_data()],
+        framing in framing(),
+        abort_at in any::<prop::sample::Index>(),
+    ) {
+        let (padding, compression, chunked) = framing;
+        let options = EncryptOptions { metadata: None, padding, compression, chunked };
+        let recipients = recipient_inputs(&[0, 1]);
+
+        let mut recorder = Recorder::new(None);
+        let encrypted = encrypt_file_multi_with_rng_and_progress(
+            &mut ChaCha20Rng::seed_from_u64(seed), &data, &recipients, &options, &mut recorder,
+        );
+        prop_assert!(encrypted.success, "{}", encrypted.error_message);
+        prop_assert_eq!(&encrypted.error_code, "");
+        prop_assert_eq!(&encrypted.encrypted_data, &encrypt_for(seed, &data, &[0, 1], framing).encrypted_data);
+        recorder.assert_well_ordered()?;
+
+        let abort_after = abort_at.index(recorder.events.len() + 1);
+        let mut aborting = Recorder::new(Some(abort_after));
+        let cancelled = encrypt_file_multi_with_rng_and_progress(
+            &mut ChaCha20Rng::seed_from_u64(seed), &data, &recipients, &options, &mut aborting,
+        );
+        prop_assert!(!cancelled.success, "encryption finished despite an abort after {} events", abort_after);
+        prop_assert_eq!(&cancelled.error_code, ERROR_CANCELLED);
+        prop_assert!(cancelled.encrypted_data.is_empty());
+
+        let dek = [0x42; 32];
+        let file_nonce = [7; 12];
+        let frame = FrameOptions { padding, compression, segment_size: chunked.then_some(DEFAULT_SEGMENT_SIZE) };
+        let blob = seal_payload(&dek, &file_nonce, &data, &frame).unwrap();
+
+        let mut recorder = Recorder::new(None);
+        prop_assert_eq!(open_payload_with_progress(&dek, &file_nonce, &blob, &mut recorder).unwrap(), data);
+        recorder.assert_well_ordered()?;
+
+        let abort_after = abort_at.index(recorder.events.len() + 1);
+        let error = open_payload_with_progress(&dek, &file_nonce, &blob, &mut Recorder::new(Some(abort_after))).unwrap_err();
+        prop_assert_eq!(error, ProgressError::Cancelled);
+    }
+
+    #[test]
+    fn tampered_chunked_payload_fails_without_restarting_progress(
+        data in chunked_file_data(),
+        flip in any::<prop::sample::Index>(),
+    ) {
+        let dek = [0x42; 32];
+        let file_nonce = [7; 12];
+        let frame = FrameOptions { segment_size: Some(DEFAULT_SEGMENT_SIZE), ..FrameOptions::default() };
+        let mut blob = seal_payload(&dek, &file_nonce, &data, &frame).unwrap();
+        let i = ChunkedLayout::HEADER_LEN + flip.index(blob.len() - ChunkedLayout::HEADER_LEN);
+        blob[i] ^= 1;
+
+        let mut recorder = Recorder::new(None);
+        let error = open_payload_with_progress(&dek, &file_nonce, &blob, &mut recorder).unwrap_err();
+        prop_assert!(!error.is_cancelled());
+        prop_assert!(error.to_string().contains("failed to authenticate"), "{}", error);
+        let starts = recorder.events.iter()
+            .filter(|event| matches!(event, ProgressEvent::Bytes { stage: Stage::Decrypt, processed: 0, .. }))
+            .count();
+        prop_assert_eq!(starts, 1);
+    }
+}
@@ -0,0 +1,80 @@
+//! Argon2id calibration, timed with a scripted clock so the refinement steps
+//! are deterministic.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::cell::Cell;
+
+use rust::clock::{Clock, FixedClock};
+use rust::kdf::{KdfParams, calibrate_kdf_with_clock, scale_params};
+
+const MIB: u32 = 1024;
+
+/// Returns the scripted times in order, one per reading.
+struct ScriptedClock<'a> {
+    times: &'a [u64],
+    next: Cell<usize>,
+}
+
+impl<'a> ScriptedClock<'a> {
+    fn new(times: &'a [u64]) -> Self {
+        ScriptedClock { times, next: Cell::new(0) }
+    }
+}
+
+impl Clock for ScriptedClock<'_> {
+    fn now_ms(&self) -> u64 {
+        let i = self.next.get();
+        self.next.set(i + 1);
+        self.times[i]
+    }
+}
+
+#[test]
+fn scale_params_grows_memory_then_passes() {
+    let floor = KdfParams::default();
+
+    let doubled = scale_params(&floor, 2.0, 1024 * MIB);
+    assert_eq!(doubled, KdfParams { memory_kib: 2 * floor.memory_kib, ..floor });
+
+    // Capped memory moves the rest of the factor into passes
+    let capped = scale_params(&floor, 4.0, 128 * MIB);
+    assert_eq!(capped, KdfParams { memory_kib: 128 * MIB, iterations: 6, ..floor });
+
+    // Memory is rounded down to whole MiB
+    let odd = scale_params(&floor, 1.5, 1024 * MIB);
+    assert_eq!(odd.memory_kib, 96 * MIB);
+    assert_eq!(odd.memory_kib % MIB, 0);
+
+    // Never weaker than the defaults
+    assert_eq!(scale_params(&floor, 0.1, 1024 * MIB), floor);
+    assert_eq!(scale_params(&floor, 2.0, 0), KdfParams { iterations: 6, ..floor });
+}
+
+#[test]
+fn calibration_stops_within_ten_percent_of_the_target() {
+    let clock = ScriptedClock::new(&[0, 950]);
+    let calibration = calibrate_kdf_with_clock(&clock, 1000, 1024 * MIB).unwrap();
+    assert_eq!(calibration.params, KdfParams::default());
+    assert_eq!(calibration.measured_ms, 950);
+}
+
+#[test]
+fn calibration_rescales_from_each_measurement() {
+    // Defaults take 250 ms against a 1 s target; the memory cap leaves passes to grow
+    let clock = ScriptedClock::new(&[0, 250, 1000, 2000]);
+    let calibration = calibrate_kdf_with_clock(&clock, 1000, 128 * MIB).unwrap();
+    assert_eq!(calibration.params, KdfParams { memory_kib: 128 * MIB, iterations: 6, parallelism: 1 });
+    assert_eq!(calibration.measured_ms, 1000);
+}
+
+#[test]
+fn calibration_on_a_slow_device_keeps_the_defaults() {
+    // A stopped clock cannot be rescaled from, and a slow one only ever asks for less
+    let slow = ScriptedClock::new(&[0, 5000]);
+    assert_eq!(calibrate_kdf_with_clock(&slow, 1000, 1024 * MIB).unwrap().params, KdfParams::default());
+
+    let stopped = calibrate_kdf_with_clock(&FixedClock(1_700_000_000_000), 0, 0).unwrap();
+    assert_eq!(stopped.params, KdfParams::default());
+    assert_eq!(stopped.measured_ms, 0);
+}
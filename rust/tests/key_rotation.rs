@@ -0,0 +1,100 @@
+//! Identity key rotation: every re-wrapped `file_dek` row opens under the new
+//! key (and only the new key), and the superseded statement verifies.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+use rust::decrypt_file::decrypt_with_dek;
+use rust::encrypt_file::{EncryptOptions, FileDekInput, RecipientInput, encrypt_file_multi_with_rng};
+use rust::key_rotation::{KeyRotationInput, rotate, verify_superseded_statement};
+use rust::masterkey_decryptor::unlock_private_key;
+use rust::masterkey_generator::encrypt_master_key_with_recovery_with_rng;
+use rust::{bytes_to_hex, hex_to_bytes};
+
+const PASSWORD: &str = "correct horse battery staple";
+
+#[test]
+fn rotated_rows_open_under_the_new_key() {
+    let mut rng = ChaCha20Rng::seed_from_u64(26);
+    let keys = encrypt_master_key_with_recovery_with_rng(&mut rng, PASSWORD);
+    let old_private_key = unlock_private_key(PASSWORD, &keys.salt(), &keys.encrypted_private_key(), &keys.nonce()).unwrap();
+
+    let files: Vec<Vec<u8>> = vec![b"first file".to_vec(), vec![0x5a; 4096]];
+    let mut rows = vec![];
+    let mut blobs = vec![];
+    for (i, data) in files.iter().enumerate() {
+        let recipient = RecipientInput {
+            user_id: "user-1".to_string(),
+            group_id: None,
+            public_key: keys.public_key(),
+            not_before: None,
+            not_after: (i == 1).then_some(4_000_000_000_000),
+        };
+        let encrypted = encrypt_file_multi_with_rng(&mut rng, data, &[recipient], &EncryptOptions::default());
+        assert!(encrypted.success, "{}", encrypted.error_message);
+        let entry = &encrypted.dek_entries[0];
+        rows.push(FileDekInput {
+            file_id: format!("file-{}", i),
+            encrypted_dek: hex_to_bytes(&entry.encrypted_dek_hex).unwrap(),
+            dek_nonce: hex_to_bytes(&entry.dek_nonce_hex).unwrap(),
+            ephemeral_public_key: hex_to_bytes(&entry.ephemeral_public_key_hex).unwrap(),
+            not_before: entry.not_before,
+            not_after: entry.not_after,
+        });
+        blobs.push((hex_to_bytes(&encrypted.file_nonce_hex).unwrap(), encrypted.encrypted_data));
+    }
+
+    let input = KeyRotationInput {
+        password: PASSWORD.to_string(),
+        pk_salt: keys.salt(),
+        encrypted_private_key: keys.encrypted_private_key(),
+        pk_nonce: keys.nonce(),
+        timestamp_ms: 1_700_000_000_000.0,
+        dek_entries: rows,
+    };
+    let result = rotate(&input).unwrap();
+    assert!(result.success);
+    assert_ne!(result.public_key_hex, bytes_to_hex(&keys.public_key()));
+
+    let new_private_key = unlock_private_key(
+        PASSWORD,
+        &result.salt,
+        &hex_to_bytes(&result.encrypted_private_key_hex).unwrap(),
+        &hex_to_bytes(&result.nonce_hex).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(result.dek_entries.len(), files.len());
+    for (i, rotated) in result.dek_entries.iter().enumerate() {
+        assert_eq!(rotated.file_id, format!("file-{}", i));
+        assert_eq!(rotated.not_after, input.dek_entries[i].not_after, "grant window kept");
+
+        let row = FileDekInput {
+            file_id: rotated.file_id.clone(),
+            encrypted_dek: hex_to_bytes(&rotated.encrypted_dek_hex).unwrap(),
+            dek_nonce: hex_to_bytes(&rotated.dek_nonce_hex).unwrap(),
+            ephemeral_public_key: hex_to_bytes(&rotated.ephemeral_public_key_hex).unwrap(),
+            not_before: rotated.not_before,
+            not_after: rotated.not_after,
+        };
+        assert!(row.unwrap_dek(&old_private_key).is_err(), "old key opens rotated row {}", i);
+
+        let dek = row.unwrap_dek(&new_private_key).unwrap();
+        let (file_nonce, blob) = &blobs[i];
+        assert_eq!(decrypt_with_dek(&dek, file_nonce, blob).unwrap(), files[i]);
+    }
+
+    let statement = result.superseded_statement.clone().unwrap();
+    assert_eq!(statement.old_public_key_hex, bytes_to_hex(&keys.public_key()));
+    assert_eq!(statement.new_public_key_hex, result.public_key_hex);
+    verify_superseded_statement(&statement).unwrap();
+
+    let mut backdated = statement.clone();
+    backdated.timestamp_ms -= 1;
+    assert!(verify_superseded_statement(&backdated).is_err());
+
+    let mut swapped = statement;
+    std::mem::swap(&mut swapped.old_key_signature_hex, &mut swapped.new_key_signature_hex);
+    assert!(verify_superseded_statement(&swapped).is_err());
+}
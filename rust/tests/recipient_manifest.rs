@@ -0,0 +1,200 @@
+//! Recipient manifests in `decrypt_file`: only a manifest signed by the pinned
+//! uploader key, listing the requester with the exact wrapped DEK they hold,
+//! lets the decryption through.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+use rust::decrypt_file::{DecryptionContext, decrypt_with_context};
+use rust::encrypt_file::{
+    EncryptOptions, EncryptedDekEntry, MultiKeyEncryptResultData, RecipientInput, encrypt_file_multi_with_rng,
+};
+use rust::masterkey_decryptor::PrivateKeyCredentials;
+use rust::masterkey_generator::{EncryptedMasterKeyWithRecovery, encrypt_master_key_with_recovery_with_rng};
+use rust::recipient_manifest::{CreateManifestInput, ManifestDekEntryInput, RecipientManifest, create_manifest};
+use rust::{bytes_to_hex, hex_to_bytes};
+
+const PASSWORD: &str = "correct horse battery staple";
+const DATA: &[u8] = b"board minutes";
+
+struct User {
+    id: &'static str,
+    keys: EncryptedMasterKeyWithRecovery,
+}
+
+impl User {
+    fn new(rng: &mut ChaCha20Rng, id: &'static str) -> Self {
+        User { id, keys: encrypt_master_key_with_recovery_with_rng(rng, PASSWORD) }
+    }
+
+    fn recipient(&self) -> RecipientInput {
+        RecipientInput {
+            user_id: self.id.to_string(),
+            group_id: None,
+            public_key: self.keys.public_key(),
+            not_before: None,
+            not_after: None,
+        }
+    }
+
+    fn public_key_hex(&self) -> String {
+        bytes_to_hex(&self.keys.public_key())
+    }
+
+    fn credentials(&self) -> PrivateKeyCredentials {
+        PrivateKeyCredentials {
+            password: PASSWORD.to_string(),
+            pk_salt: self.keys.salt(),
+            encrypted_private_key: self.keys.encrypted_private_key(),
+            pk_nonce: self.keys.nonce(),
+        }
+    }
+
+    /// This user's `file_dek` row in an upload.
+    fn row<'a>(&self, encrypted: &'a MultiKeyEncryptResultData) -> &'a EncryptedDekEntry {
+        encrypted.dek_entries.iter().find(|entry| entry.user_id == self.id).unwrap()
+    }
+
+    /// Signs a manifest over `covered` recipients of an upload.
+    fn sign(&self, encrypted: &MultiKeyEncryptResultData, covered: &[&User]) -> RecipientManifest {
+        let input = CreateManifestInput {
+            credentials: self.credentials(),
+            signer_id: self.id.to_string(),
+            file_hash: encrypted.original_hash_hex.clone(),
+            timestamp_ms: 1_700_000_000_000.0,
+            recipients: covered.iter().map(|user| user.recipient()).collect(),
+            dek_entries: covered
+                .iter()
+                .map(|user| {
+                    let row = user.row(encrypted);
+                    ManifestDekEntryInput {
+                        user_id: row.user_id.clone(),
+                        group_id: row.group_id.clone(),
+                        encrypted_dek_hex: row.encrypted_dek_hex.clone(),
+                        dek_nonce_hex: row.dek_nonce_hex.clone(),
+                        ephemeral_public_key_hex: row.ephemeral_public_key_hex.clone(),
+                    }
+                })
+                .collect(),
+        };
+        create_manifest(&input).unwrap()
+    }
+
+    /// A decryption of an upload with this user's row, checked against `manifest`.
+    fn context(
+        &self,
+        encrypted: &MultiKeyEncryptResultData,
+        manifest: &RecipientManifest,
+        pinned_signer: &str,
+    ) -> DecryptionContext {
+        let row = self.row(encrypted);
+        DecryptionContext {
+            encrypted_data: encrypted.encrypted_data.clone(),
+            password: PASSWORD.to_string(),
+            pk_salt: self.keys.salt(),
+            encrypted_private_key: self.keys.encrypted_private_key(),
+            pk_nonce: self.keys.nonce(),
+            ephemeral_public_key: hex_to_bytes(&row.ephemeral_public_key_hex).unwrap(),
+            encrypted_dek: hex_to_bytes(&row.encrypted_dek_hex).unwrap(),
+            dek_nonce: hex_to_bytes(&row.dek_nonce_hex).unwrap(),
+            file_nonce: hex_to_bytes(&encrypted.file_nonce_hex).unwrap(),
+            group_entry: None,
+            manifest: Some(manifest.clone()),
+            user_id: self.id.to_string(),
+            manifest_signer_public_key_hex: pinned_signer.to_string(),
+            not_before: None,
+            not_after: None,
+            now_ms: None,
+        }
+    }
+}
+
+struct Setup {
+    uploader: User,
+    reader: User,
+    outsider: User,
+    upload: MultiKeyEncryptResultData,
+}
+
+fn setup(seed: u64) -> Setup {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    let uploader = User::new(&mut rng, "uploader");
+    let reader = User::new(&mut rng, "reader");
+    let outsider = User::new(&mut rng, "outsider");
+    let recipients = [uploader.recipient(), reader.recipient()];
+    let upload = encrypt_file_multi_with_rng(&mut rng, DATA, &recipients, &EncryptOptions::default());
+    assert!(upload.success, "{}", upload.error_message);
+    Setup { uploader, reader, outsider, upload }
+}
+
+fn decryption_error(context: &DecryptionContext) -> String {
+    let result = decrypt_with_context(context);
+    assert!(!result.success(), "decryption was allowed");
+    result.error_message()
+}
+
+#[test]
+fn covered_recipient_decrypts() {
+    let Setup { uploader, reader, upload, .. } = setup(31);
+    let manifest = uploader.sign(&upload, &[&uploader, &reader]);
+
+    let result = decrypt_with_context(&reader.context(&upload, &manifest, &uploader.public_key_hex()));
+    assert!(result.success(), "{}", result.error_message());
+    assert_eq!(result.decrypted_data(), DATA);
+}
+
+#[test]
+fn pinned_signer_key_is_required() {
+    let Setup { uploader, reader, upload, .. } = setup(32);
+    let manifest = uploader.sign(&upload, &[&uploader, &reader]);
+
+    let error = decryption_error(&reader.context(&upload, &manifest, ""));
+    assert!(error.contains("signer key is required"), "{}", error);
+    assert!(manifest.verify_signer("").is_err());
+}
+
+#[test]
+fn manifest_signed_by_a_foreign_key_is_rejected() {
+    let Setup { uploader, reader, outsider, upload } = setup(33);
+    let forged = outsider.sign(&upload, &[&uploader, &reader]);
+    forged.verify_signature().unwrap();
+
+    let error = decryption_error(&reader.context(&upload, &forged, &uploader.public_key_hex()));
+    assert!(error.contains("unexpected key"), "{}", error);
+
+    // Claiming the uploader's key does not help without their signature
+    let mut relabelled = forged;
+    relabelled.signer_public_key_hex = uploader.public_key_hex();
+    let error = decryption_error(&reader.context(&upload, &relabelled, &uploader.public_key_hex()));
+    assert!(error.contains("signature invalid"), "{}", error);
+}
+
+#[test]
+fn requester_missing_from_the_manifest_is_rejected() {
+    let Setup { uploader, reader, upload, .. } = setup(34);
+    let manifest = uploader.sign(&upload, &[&uploader]);
+
+    let error = decryption_error(&reader.context(&upload, &manifest, &uploader.public_key_hex()));
+    assert!(error.contains("not covered"), "{}", error);
+}
+
+#[test]
+fn wrapped_dek_hash_mismatch_is_rejected() {
+    let Setup { uploader, reader, upload, .. } = setup(35);
+    let manifest = uploader.sign(&upload, &[&uploader, &reader]);
+
+    // A second wrapping of the same DEK for the reader, never listed
+    let mut rng = ChaCha20Rng::seed_from_u64(36);
+    let mut other = encrypt_file_multi_with_rng(&mut rng, DATA, &[reader.recipient()], &EncryptOptions::default());
+    other.encrypted_data = upload.encrypted_data.clone();
+    other.file_nonce_hex = upload.file_nonce_hex.clone();
+
+    let error = decryption_error(&reader.context(&other, &manifest, &uploader.public_key_hex()));
+    assert!(error.contains("not covered"), "{}", error);
+
+    let mut context = reader.context(&upload, &manifest, &uploader.public_key_hex());
+    context.dek_nonce[0] ^= 1;
+    let error = decryption_error(&context);
+    assert!(error.contains("not covered"), "{}", error);
+}
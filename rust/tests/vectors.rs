@@ -0,0 +1,157 @@
+//! Replays the known-answer vectors through the native core.
+//!
+//! Run with `cargo test --test vectors`. The wasm exports are covered by
+//! `tests/wasm_vectors.rs`.
+
+mod common;
+
+use common::{corpus, unhex};
+use rust::block_signature::{compute_block_signature, verify_ciphertext_hash};
+use rust::encrypt_file::{unwrap_dek_in_window, wrap_dek_with_ephemeral_key};
+use rust::grant_window::GrantWindow;
+use rust::masterkey_decryptor::unlock_private_key;
+use rust::masterkey_generator::seal_private_key;
+use rust::payload_frame::{CompressionScheme, FrameOptions, PaddingScheme, open_payload, seal_payload};
+use rust::{bytes_to_hex, get_key_encryption_key, hash_file};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+fn scheme<T: serde::de::DeserializeOwned>(name: &str) -> T {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).expect("known scheme name")
+}
+
+#[test]
+fn kek_derivation() {
+    for vector in corpus().kek {
+        let kek = get_key_encryption_key(&vector.password, &vector.salt);
+        assert_eq!(bytes_to_hex(&kek), vector.kek, "KEK for salt {}", vector.salt);
+    }
+}
+
+#[test]
+fn private_key_wrapping() {
+    for vector in corpus().private_key_wrap {
+        let private_key = unhex(&vector.private_key);
+        let private_array: [u8; 32] = private_key.as_slice().try_into().unwrap();
+        let public_key = PublicKey::from(&StaticSecret::from(private_array));
+        assert_eq!(bytes_to_hex(public_key.as_bytes()), vector.public_key);
+
+        let encrypted = seal_private_key(&vector.password, &vector.salt, &unhex(&vector.nonce), &private_key).unwrap();
+        assert_eq!(bytes_to_hex(&encrypted), vector.encrypted_private_key);
+
+        let unlocked = unlock_private_key(
+            &vector.password,
+            &vector.salt,
+            &unhex(&vector.encrypted_private_key),
+            &unhex(&vector.nonce),
+        )
+        .unwrap();
+        assert_eq!(unlocked, private_key);
+    }
+}
+
+#[test]
+fn dek_wrapping() {
+    for vector in corpus().dek_wrap {
+        let window = GrantWindow::new(vector.not_before, vector.not_after);
+        let ephemeral_private_key: [u8; 32] = unhex(&vector.ephemeral_private_key).try_into().unwrap();
+
+        let wrapped = wrap_dek_with_ephemeral_key(
+            &unhex(&vector.dek),
+            &unhex(&vector.recipient_public_key),
+            ephemeral_private_key,
+            &unhex(&vector.dek_nonce),
+            window,
+        )
+        .unwrap();
+        assert_eq!(bytes_to_hex(&wrapped.ephemeral_public_key), vector.ephemeral_public_key);
+        assert_eq!(bytes_to_hex(&wrapped.encrypted_dek), vector.encrypted_dek);
+
+        let dek = unwrap_dek_in_window(
+            &unhex(&vector.recipient_private_key),
+            &unhex(&vector.ephemeral_public_key),
+            &unhex(&vector.dek_nonce),
+            &unhex(&vector.encrypted_dek),
+            &window,
+        )
+        .unwrap();
+        assert_eq!(bytes_to_hex(&dek), vector.dek);
+
+        // The window is bound as AAD, so dropping it must break the unwrap
+        if !window.is_unbounded() {
+            assert!(unwrap_dek_in_window(
+                &unhex(&vector.recipient_private_key),
+                &unhex(&vector.ephemeral_public_key),
+                &unhex(&vector.dek_nonce),
+                &unhex(&vector.encrypted_dek),
+                &GrantWindow::default(),
+            )
+            .is_err());
+        }
+    }
+}
+
+#[test]
+fn file_encryption() {
+    for vector in corpus().file_encryption {
+        let dek = unhex(&vector.dek);
+        let file_nonce = unhex(&vector.file_nonce);
+        let plaintext = unhex(&vector.plaintext);
+        assert_eq!(hash_file(&plaintext), vector.original_hash, "{}", vector.name);
+
+        if !vector.decrypt_only {
+            let options = FrameOptions {
+                padding: scheme::<PaddingScheme>(&vector.padding),
+                compression: scheme::<CompressionScheme>(&vector.compression),
+                segment_size: vector.segment_size,
+            };
+            let ciphertext = seal_payload(&dek, &file_nonce, &plaintext, &options).unwrap();
+            assert_eq!(bytes_to_hex(&ciphertext), vector.ciphertext, "{}", vector.name);
+        }
+
+        let decrypted = open_payload(&dek, &file_nonce, &unhex(&vector.ciphertext)).unwrap();
+        assert_eq!(bytes_to_hex(&decrypted), vector.plaintext, "{}", vector.name);
+    }
+}
+
+#[test]
+fn block_signatures() {
+    for vector in corpus().block_signature {
+        let signature = compute_block_signature(
+            &vector.uploader_id,
+            vector.timestamp_ms,
+            &vector.file_hash,
+            &vector.ciphertext_hash,
+            &vector.previous_block_hash,
+        );
+        assert_eq!(signature, vector.signature);
+    }
+}
+
+/// The sections chain together: the wrapped private key receives the wrapped
+/// DEKs, which encrypt the files. The wasm harness relies on this.
+#[test]
+fn corpus_is_consistent() {
+    let corpus = corpus();
+    let identity = &corpus.private_key_wrap[0];
+    for vector in &corpus.dek_wrap {
+        assert_eq!(vector.recipient_private_key, identity.private_key);
+        assert_eq!(vector.recipient_public_key, identity.public_key);
+    }
+    for vector in &corpus.file_encryption {
+        assert_eq!(vector.dek, corpus.dek_wrap[0].dek, "{}", vector.name);
+    }
+    for pair in corpus.block_signature.windows(2) {
+        assert_eq!(pair[1].previous_block_hash, pair[0].signature);
+    }
+    for block in corpus.block_signature.iter().filter(|block| !block.ciphertext_hash.is_empty()) {
+        assert!(
+            corpus
+                .file_encryption
+                .iter()
+                .any(|file| file.original_hash == block.file_hash
+                    && verify_ciphertext_hash(&unhex(&file.ciphertext), &block.ciphertext_hash).is_ok()),
+            "ciphertext hash {} covers no file vector",
+            block.ciphertext_hash
+        );
+    }
+}
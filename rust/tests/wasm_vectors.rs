@@ -0,0 +1,112 @@
+//! Replays the known-answer vectors through the wasm exports under Node.
+//!
+//! Run with `wasm-pack test --node --release`.
+
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use common::{corpus, unhex};
+use rust::block_signature::{generate_block_signature, generate_file_block_signature};
+use rust::decrypt_file::decrypt_file;
+use rust::masterkey_decryptor::decrypt_private_key;
+use rust::{bytes_to_hex, master_key_bytes_to_hex};
+use serde::Serialize;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+/// The subset of `DecryptionContext` the vectors fill in.
+#[derive(Serialize)]
+struct DecryptionContextJs {
+    encrypted_data: Vec<u8>,
+    password: String,
+    pk_salt: String,
+    encrypted_private_key: Vec<u8>,
+    pk_nonce: Vec<u8>,
+    ephemeral_public_key: Vec<u8>,
+    encrypted_dek: Vec<u8>,
+    dek_nonce: Vec<u8>,
+    file_nonce: Vec<u8>,
+    not_before: Option<u64>,
+    not_after: Option<u64>,
+    now_ms: Option<f64>,
+}
+
+#[wasm_bindgen_test]
+fn kek_derivation() {
+    for vector in corpus().kek {
+        assert_eq!(master_key_bytes_to_hex(&vector.password, &vector.salt), vector.kek);
+    }
+}
+
+#[wasm_bindgen_test]
+fn private_key_unwrapping() {
+    for vector in corpus().private_key_wrap {
+        let result = decrypt_private_key(
+            &vector.password,
+            &vector.salt,
+            &unhex(&vector.encrypted_private_key),
+            &unhex(&vector.nonce),
+        );
+        assert!(result.success(), "{}", result.error_message());
+        assert_eq!(result.private_key_hex(), vector.private_key);
+    }
+}
+
+#[wasm_bindgen_test]
+fn file_decryption() {
+    let corpus = corpus();
+    let identity = &corpus.private_key_wrap[0];
+
+    // Every file vector through the unbounded entry, then one through the time-limited entry
+    let cases = corpus
+        .file_encryption
+        .iter()
+        .map(|file| (file, &corpus.dek_wrap[0]))
+        .chain(corpus.dek_wrap.iter().skip(1).map(|entry| (&corpus.file_encryption[0], entry)));
+
+    for (file, entry) in cases {
+        let context = DecryptionContextJs {
+            encrypted_data: unhex(&file.ciphertext),
+            password: identity.password.clone(),
+            pk_salt: identity.salt.clone(),
+            encrypted_private_key: unhex(&identity.encrypted_private_key),
+            pk_nonce: unhex(&identity.nonce),
+            ephemeral_public_key: unhex(&entry.ephemeral_public_key),
+            encrypted_dek: unhex(&entry.encrypted_dek),
+            dek_nonce: unhex(&entry.dek_nonce),
+            file_nonce: unhex(&file.file_nonce),
+            not_before: entry.not_before,
+            not_after: entry.not_after,
+            now_ms: entry.not_before.map(|not_before| not_before as f64),
+        };
+
+        let result = decrypt_file(serde_wasm_bindgen::to_value(&context).unwrap());
+        assert!(result.success(), "{}: {}", file.name, result.error_message());
+        assert_eq!(bytes_to_hex(&result.decrypted_data()), file.plaintext, "{}", file.name);
+        assert_eq!(result.file_hash_hex(), file.original_hash, "{}", file.name);
+    }
+}
+
+#[wasm_bindgen_test]
+fn block_signatures() {
+    for vector in corpus().block_signature {
+        let result = if vector.ciphertext_hash.is_empty() {
+            generate_block_signature(
+                &vector.uploader_id,
+                vector.timestamp_ms as f64,
+                &vector.file_hash,
+                &vector.previous_block_hash,
+            )
+        } else {
+            generate_file_block_signature(
+                &vector.uploader_id,
+                vector.timestamp_ms as f64,
+                &vector.file_hash,
+                &vector.ciphertext_hash,
+                &vector.previous_block_hash,
+            )
+        };
+        assert!(result.success(), "{}", result.error_message());
+        assert_eq!(result.signature_hex(), vector.signature);
+    }
+}